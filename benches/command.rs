@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use darkredis::{Command, CommandList};
+use darkredis::{parse_bench, Command, CommandList};
 
 fn serialize_arrays() {
     let elements_per_array = 1000;
@@ -34,10 +34,27 @@ fn serialize_commandlist() {
     black_box(command.serialize_bench());
 }
 
+fn parse_large_array(input: &[u8]) {
+    black_box(parse_bench(input).unwrap());
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
-    c.bench_function("Serialize many arrays", |b| b.iter(|| serialize_arrays()));
+    c.bench_function("Serialize many arrays", |b| b.iter(serialize_arrays));
     c.bench_function("Serialize large commandlist", |b| {
-        b.iter(|| serialize_commandlist())
+        b.iter(serialize_commandlist)
+    });
+
+    //Equivalent to the reply of an LRANGE on a list with 10000 elements of 100 bytes each
+    let elements = 10000;
+    let mut reply = format!("*{}\r\n", elements).into_bytes();
+    for _ in 0..elements {
+        reply.extend_from_slice(b"$100\r\n");
+        reply.extend_from_slice(&[b'a'; 100]);
+        reply.extend_from_slice(b"\r\n");
+    }
+    let reply = black_box(reply);
+    c.bench_function("Parse large array", |b| {
+        b.iter(|| parse_large_array(&reply))
     });
 }
 
@@ -17,7 +17,7 @@ macro_rules! create_commands {
 }
 
 async fn connect() -> Connection {
-    Connection::connect(TEST_ADDRESS, None).await.unwrap()
+    Connection::connect(TEST_ADDRESS).await.unwrap()
 }
 
 async fn pipelined_stream() {
@@ -29,11 +29,30 @@ async fn pipelined_stream() {
     let _: Vec<Result<Value>> = black_box(stream.collect().await);
 }
 
+async fn large_reply(conn: &mut Connection, list_key: &str) {
+    let values: Vec<Vec<u8>> = black_box(conn.lrange(list_key, 0, -1).await.unwrap());
+    assert_eq!(values.len(), 10000);
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let mut rt = tokio::runtime::Runtime::new().unwrap();
     c.bench_function("Pipelined Stream", |b| {
         b.iter(|| rt.block_on(pipelined_stream()))
     });
+
+    let list_key = "darkredis.bench.large_reply";
+    let mut conn = rt.block_on(async {
+        let mut conn = connect().await;
+        let element = vec![0u8; 100];
+        let elements = vec![element; 10000];
+        conn.del(list_key).await.unwrap();
+        conn.rpush_slice(list_key, &elements).await.unwrap();
+        conn
+    });
+    c.bench_function("Large reply", |b| {
+        b.iter(|| rt.block_on(large_reply(&mut conn, list_key)))
+    });
+    rt.block_on(conn.del(list_key)).unwrap();
 }
 
 criterion_group!(pipelining, criterion_benchmark);
@@ -18,7 +18,7 @@ pub struct Manager<A: ToSocketAddrs + Send + Sync> {
 impl<A: ToSocketAddrs + Send + Sync> Manager<A> {
     ///Create a new `Manager` which connects to Redis at `address`. When a connection is created, it
     ///will automatically set the Redis client name to `name`.
-    pub fn with_name<'a, P>(name: &str, address: A, password: Option<P>) -> Self
+    pub fn with_name<P>(name: &str, address: A, password: Option<P>) -> Self
     where
         P: AsRef<[u8]>,
     {
@@ -54,7 +54,7 @@ impl<A: ToSocketAddrs + Send + Sync> deadpool::Manager<Connection, Error> for Ma
             Connection::connect(&self.address).await?
         };
         if let Some(ref name) = self.name {
-            conn.run_command(Command::new("CLIENT").arg(b"SETNAME").arg(&name))
+            conn.run_command(Command::new("CLIENT").arg(b"SETNAME").arg(name))
                 .await?;
         }
         Ok(conn)
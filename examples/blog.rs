@@ -16,7 +16,7 @@ async fn show_posts(mut connection: Connection) {
 
     for post in posts {
         let post = String::from_utf8_lossy(&post);
-        let id = (&post[0..1]).parse::<usize>().unwrap();
+        let id = post[0..1].parse::<usize>().unwrap();
         let comment_key = format!("posts.{}.comments", id);
         println!("Post #{}: {}", id, &post[2..]);
         let comments = connection.lrange(&comment_key, 0, 10).await.unwrap();
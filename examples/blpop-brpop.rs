@@ -35,7 +35,7 @@ async fn main() -> darkredis::Result<()> {
     tokio::spawn(async move {
         let mut conn = Connection::connect("127.0.0.1:6379").await.unwrap();
         let step = Duration::from_millis(1_500);
-        let msgs = vec![
+        let msgs = [
             ("list_a", "msg1"),
             ("list_b", "msg2"),
             ("list_a", "msg3"),
@@ -12,12 +12,12 @@ async fn main() -> Result<()> {
     //moving it.
     let key = "some-key";
     let commands = CommandList::new("SET")
-        .arg(&key)
+        .arg(key)
         .arg(b"some-value")
         .command("GET")
-        .arg(&key)
+        .arg(key)
         .command("DEL")
-        .arg(&key);
+        .arg(key);
 
     let mut stream: ResponseStream = connection.run_commands(commands).await?;
 
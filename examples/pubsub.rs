@@ -40,11 +40,14 @@ async fn main() {
     //to always be listening for updates, so you don't miss any.
     messagestream
         .for_each(|e| async move {
-            println!(
-                "Received a message on channel '{}': {}",
-                String::from_utf8_lossy(&e.channel),
-                String::from_utf8_lossy(&e.message)
-            );
+            match e {
+                Ok(e) => println!(
+                    "Received a message on channel '{}': {}",
+                    String::from_utf8_lossy(&e.channel),
+                    String::from_utf8_lossy(&e.message)
+                ),
+                Err(e) => println!("Failed to receive a message: {}", e),
+            }
         })
         .await;
 
@@ -1,5 +1,5 @@
 use darkredis::{Connection, MSetBuilder};
-use futures::StreamExt;
+use futures::TryStreamExt;
 
 //In your own code, you'd use simply #[tokio::main] or #[async_std::main]
 #[cfg_attr(feature = "runtime_tokio", tokio::main)]
@@ -23,8 +23,9 @@ async fn main() {
         .sscan(&key)
         .pattern(b"*123")
         .run()
-        .collect::<Vec<Vec<u8>>>()
-        .await;
+        .try_collect::<Vec<Vec<u8>>>()
+        .await
+        .unwrap();
 
     println!("Got {} results!", users.len());
     for (i, u) in users.into_iter().enumerate() {
@@ -32,7 +33,12 @@ async fn main() {
     }
 
     //SCAN works the same:
-    let sets = connection.scan().run().collect::<Vec<Vec<u8>>>().await;
+    let sets = connection
+        .scan()
+        .run()
+        .try_collect::<Vec<Vec<u8>>>()
+        .await
+        .unwrap();
 
     println!("There are {} keys in the database!", sets.len());
 
@@ -47,9 +53,10 @@ async fn main() {
     let fields = connection
         .hscan(&hash_key)
         .run()
-        .map(|(f, v)| (String::from_utf8(f).unwrap(), String::from_utf8(v).unwrap()))
-        .collect::<Vec<(String, String)>>()
-        .await;
+        .map_ok(|(f, v)| (String::from_utf8(f).unwrap(), String::from_utf8(v).unwrap()))
+        .try_collect::<Vec<(String, String)>>()
+        .await
+        .unwrap();
     for (field, value) in fields {
         println!("Field {} is {}", field, value);
     }
@@ -1,5 +1,5 @@
-use crate::connection::builder::MSetBuilder;
-use std::io::Write;
+use crate::{connection::builder::MSetBuilder, ToArg};
+use std::{borrow::Cow, io::Write};
 
 ///Struct for defining commands manually, which allows for pipelining of several commands. If you need
 ///to only run one command, use [`Command`](struct.Command.html), which has almost the same API.
@@ -27,9 +27,16 @@ pub struct CommandList<'a> {
     commands: Vec<Command<'a>>,
 }
 
+///A [`CommandList`](struct.CommandList.html) which owns all of its data, and can therefore be
+///stored or sent to another task. Create one using [`CommandList::into_owned`](struct.CommandList.html#method.into_owned).
+pub type OwnedCommandList = CommandList<'static>;
+
 impl<'a> CommandList<'a> {
     ///Create a new command list starting with `cmd`.
-    pub fn new(cmd: &'a str) -> Self {
+    pub fn new<C>(cmd: C) -> Self
+    where
+        C: Into<Cow<'a, str>>,
+    {
         let commands = vec![Command::new(cmd)];
         Self { commands }
     }
@@ -38,20 +45,21 @@ impl<'a> CommandList<'a> {
     ///command for pipelining. That's what [`Commandlist::command`](struct.CommandList.html#method.command) is for.
     ///# See also
     ///[`append_arg`](struct.CommandList.html#method.append_arg)
-    pub fn arg<D>(mut self, data: &'a D) -> Self
+    pub fn arg<D>(mut self, data: D) -> Self
     where
-        D: AsRef<[u8]>,
+        D: ToArg<'a>,
     {
         self.append_arg(data);
         self
     }
 
-    ///Add multiple arguments from a slice, builder-style.
+    ///Add multiple arguments from a slice or iterator, builder-style.
     ///# See also
     ///[`append_args`](struct.CommandList.html#method.append_args)
-    pub fn args<D>(mut self, arguments: &'a [D]) -> Self
+    pub fn args<I>(mut self, arguments: I) -> Self
     where
-        D: AsRef<[u8]>,
+        I: IntoIterator,
+        I::Item: ToArg<'a>,
     {
         self.append_args(arguments);
         self
@@ -61,38 +69,42 @@ impl<'a> CommandList<'a> {
     ///now on.
     ///# See also
     ///[`append_command`](struct.CommandList.html#method.append_command)
-    pub fn command(mut self, cmd: &'a str) -> Self {
+    pub fn command<C>(mut self, cmd: C) -> Self
+    where
+        C: Into<Cow<'a, str>>,
+    {
         self.commands.push(Command::new(cmd));
         self
     }
 
-    ///Append arguments from a slice.
+    ///Append arguments from a slice or iterator.
     ///# See also
     ///[`args`](struct.CommandList.html#method.args)
-    pub fn append_args<D>(&mut self, arguments: &'a [D])
+    pub fn append_args<I>(&mut self, arguments: I)
     where
-        D: AsRef<[u8]>,
+        I: IntoIterator,
+        I::Item: ToArg<'a>,
     {
-        let last_command = self.commands.last_mut().unwrap();
-        for arg in arguments {
-            last_command.args.push(arg.as_ref());
-        }
+        self.commands.last_mut().unwrap().append_args(arguments);
     }
 
     ///Mutate `self` by adding an additional argument.
     ///# See also
     ///[`arg`](struct.CommandList.html#method.arg)
-    pub fn append_arg<D>(&mut self, data: &'a D)
+    pub fn append_arg<D>(&mut self, data: D)
     where
-        D: AsRef<[u8]>,
+        D: ToArg<'a>,
     {
-        self.commands.last_mut().unwrap().args.push(data.as_ref());
+        self.commands.last_mut().unwrap().append_arg(data);
     }
 
     ///Append a new command to `self`.
     ///# See also
     ///[`command`](struct.CommandList.html#method.command)
-    pub fn append_command(&mut self, cmd: &'a str) {
+    pub fn append_command<C>(&mut self, cmd: C)
+    where
+        C: Into<Cow<'a, str>>,
+    {
         self.commands.push(Command::new(cmd))
     }
 
@@ -101,6 +113,26 @@ impl<'a> CommandList<'a> {
         self.commands.len()
     }
 
+    ///Convert `self` into a command list which owns all of its arguments, copying any borrowed data.
+    pub fn into_owned(self) -> OwnedCommandList {
+        CommandList {
+            commands: self.commands.into_iter().map(Command::into_owned).collect(),
+        }
+    }
+
+    ///Serialize `self` once, so that it can be run any number of times using
+    ///[`Connection::run_serialized_commands`](struct.Connection.html#method.run_serialized_commands).
+    pub fn into_serialized(self) -> SerializedCommandList {
+        let command_count = self.command_count();
+        let mut data = Vec::new();
+        self.serialize(&mut data);
+
+        SerializedCommandList {
+            data,
+            command_count,
+        }
+    }
+
     //Convert to redis protocol encoding
     pub(crate) fn serialize(self, buffer: &mut Vec<u8>) {
         for command in self.commands {
@@ -114,7 +146,9 @@ impl<'a> CommandList<'a> {
     ///Workaround for benchmarking
     #[inline(always)]
     pub fn serialize_bench(self) -> Vec<u8> {
-        self.serialize()
+        let mut out = Vec::new();
+        self.serialize(&mut out);
+        out
     }
 }
 
@@ -138,36 +172,77 @@ impl<'a> CommandList<'a> {
 /// ```
 #[derive(Clone, Debug)]
 pub struct Command<'a> {
-    command: &'a str,
-    args: Vec<&'a [u8]>,
+    command: Cow<'a, str>,
+    args: Vec<Cow<'a, [u8]>>,
 }
 
+///A [`Command`](struct.Command.html) which owns all of its data, and can therefore be stored or
+///sent to another task. Create one using [`Command::into_owned`](struct.Command.html#method.into_owned).
+///# Example
+///```
+///use darkredis::{Command, OwnedCommand};
+///
+///fn expire_command(key: &str, seconds: u32) -> OwnedCommand {
+///    Command::new("EXPIRE").arg(key).arg(seconds).into_owned()
+///}
+///
+///let command = expire_command("some-key", 60);
+///std::thread::spawn(move || drop(command));
+///```
+pub type OwnedCommand = Command<'static>;
+
 impl<'a> Command<'a> {
     ///Create a new Command.
-    pub fn new(cmd: &'a str) -> Self {
+    pub fn new<C>(cmd: C) -> Self
+    where
+        C: Into<Cow<'a, str>>,
+    {
         Self {
-            command: cmd,
+            command: cmd.into(),
             args: Vec::new(),
         }
     }
 
-    ///Append an argument to this command, builder-style.
+    ///Convert `self` into a command which owns all of its arguments, copying any borrowed data.
+    pub fn into_owned(self) -> OwnedCommand {
+        Command {
+            command: Cow::Owned(self.command.into_owned()),
+            args: self
+                .args
+                .into_iter()
+                .map(|arg| Cow::Owned(arg.into_owned()))
+                .collect(),
+        }
+    }
+
+    ///Serialize `self` once, so that it can be run any number of times using
+    ///[`Connection::run_serialized_command`](struct.Connection.html#method.run_serialized_command).
+    pub fn into_serialized(self) -> SerializedCommand {
+        let mut data = Vec::new();
+        self.serialize(&mut data);
+
+        SerializedCommand { data }
+    }
+
+    ///Append an argument to this command, builder-style. Anything implementing
+    ///[`ToArg`](trait.ToArg.html) can be used, such as byte strings, owned strings and numbers.
     ///# See also
     ///[`append_arg`](struct.Command.html#method.append_arg)
-    pub fn arg<D>(mut self, data: &'a D) -> Self
+    pub fn arg<D>(mut self, data: D) -> Self
     where
-        D: AsRef<[u8]>,
+        D: ToArg<'a>,
     {
         self.append_arg(data);
         self
     }
 
-    ///Add multiple arguments to a command in slice form.
+    ///Add multiple arguments to a command from a slice or iterator.
     ///# See also
     ///[`append_args`](struct.Command.html#method.append_args)
-    pub fn args<D>(mut self, arguments: &'a [D]) -> Self
+    pub fn args<I>(mut self, arguments: I) -> Self
     where
-        D: AsRef<[u8]>,
+        I: IntoIterator,
+        I::Item: ToArg<'a>,
     {
         self.append_args(arguments);
 
@@ -177,23 +252,23 @@ impl<'a> Command<'a> {
     ///Append an argument to `self`.
     ///# See also
     ///[`arg`](struct.Command.html#method.append_arg)
-    pub fn append_arg<D>(&mut self, data: &'a D)
+    pub fn append_arg<D>(&mut self, data: D)
     where
-        D: AsRef<[u8]>,
+        D: ToArg<'a>,
     {
-        self.args.push(data.as_ref());
+        self.args.push(data.to_arg());
     }
 
     ///Append multiple arguments to `self`.
     ///# See also
     ///[`args`](struct.Command.html#method.args)
-    pub fn append_args<D>(&mut self, arguments: &'a [D])
+    pub fn append_args<I>(&mut self, arguments: I)
     where
-        D: AsRef<[u8]>,
+        I: IntoIterator,
+        I::Item: ToArg<'a>,
     {
-        for arg in arguments {
-            self.args.push(arg.as_ref());
-        }
+        self.args
+            .extend(arguments.into_iter().map(|arg| arg.to_arg()));
     }
 
     pub(crate) fn serialize(self, buffer: &mut Vec<u8>) {
@@ -210,21 +285,81 @@ impl<'a> Command<'a> {
         for arg in self.args {
             //Serialize as byte string
             write!(buffer, "${}\r\n", arg.len()).unwrap();
-            for byte in arg {
-                buffer.push(*byte);
-            }
-            buffer.push(b'\r');
-            buffer.push(b'\n');
+            buffer.extend_from_slice(&arg);
+            buffer.extend_from_slice(b"\r\n");
         }
     }
 
     pub(crate) fn append_msetbuilder(&mut self, builder: &'a MSetBuilder<'a>) {
         for item in builder.build() {
-            self.args.push(*item);
+            self.args.push(Cow::Borrowed(*item));
         }
     }
 }
 
+///A [`Command`](struct.Command.html) which has already been converted to the Redis protocol. Useful
+///for commands which are run very often, as they only have to be serialized once.
+///# Example
+///```
+///use darkredis::{Command, Connection, Value};
+///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+///# async fn main() {
+///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+///# connection.del("serialized-counter").await.unwrap();
+///let command = Command::new("INCR").arg(b"serialized-counter").into_serialized();
+///
+///for i in 1..=3 {
+///    let result = connection.run_serialized_command(&command).await.unwrap();
+///    assert_eq!(result, Value::Integer(i));
+///}
+///# connection.del("serialized-counter").await.unwrap();
+///# }
+///```
+#[derive(Clone, Debug)]
+pub struct SerializedCommand {
+    data: Vec<u8>,
+}
+
+impl SerializedCommand {
+    ///The serialized command, exactly as it is sent to Redis.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl<'a> From<Command<'a>> for SerializedCommand {
+    fn from(command: Command<'a>) -> Self {
+        command.into_serialized()
+    }
+}
+
+///A [`CommandList`](struct.CommandList.html) which has already been converted to the Redis protocol.
+///See [`SerializedCommand`](struct.SerializedCommand.html) for more information.
+#[derive(Clone, Debug)]
+pub struct SerializedCommandList {
+    data: Vec<u8>,
+    command_count: usize,
+}
+
+impl SerializedCommandList {
+    ///The serialized commands, exactly as they are sent to Redis.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    ///Count the number of commands in the pipeline.
+    pub fn command_count(&self) -> usize {
+        self.command_count
+    }
+}
+
+impl<'a> From<CommandList<'a>> for SerializedCommandList {
+    fn from(commands: CommandList<'a>) -> Self {
+        commands.into_serialized()
+    }
+}
+
 #[cfg(any(feature = "bench", test))]
 impl<'a> Command<'a> {
     #[inline(always)]
@@ -263,6 +398,63 @@ mod test {
         );
     }
 
+    #[test]
+    fn number_args() {
+        let command = Command::new("LRANGE")
+            .arg(b"some-key")
+            .arg(0)
+            .arg(-1)
+            .serialize_bench();
+        assert_eq!(
+            String::from_utf8_lossy(&command),
+            "*4\r\n$6\r\nLRANGE\r\n$8\r\nsome-key\r\n$1\r\n0\r\n$2\r\n-1\r\n"
+        );
+    }
+
+    #[test]
+    fn owned_and_iterator_args() {
+        let key = "some-key".to_string();
+        let command = Command::new("SADD")
+            .arg(key)
+            .args((1..=3).map(|i| format!("member-{}", i)))
+            .serialize_bench();
+        assert_eq!(
+            String::from_utf8_lossy(&command),
+            "*5\r\n$4\r\nSADD\r\n$8\r\nsome-key\r\n$8\r\nmember-1\r\n$8\r\nmember-2\r\n$8\r\nmember-3\r\n"
+        );
+    }
+
+    #[test]
+    fn owned() {
+        fn build(key: &str, value: usize) -> OwnedCommand {
+            let command = format!("{}{}", "S", "ET");
+            Command::new(command).arg(key).arg(value).into_owned()
+        }
+
+        let command = build("some-key", 10);
+        let serialized = std::thread::spawn(move || command.serialize_bench())
+            .join()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&serialized),
+            "*3\r\n$3\r\nSET\r\n$8\r\nsome-key\r\n$2\r\n10\r\n"
+        );
+
+        let key = "some-key".to_string();
+        let list: OwnedCommandList = CommandList::new("GET")
+            .arg(&key)
+            .command("DEL")
+            .arg(&key)
+            .into_owned();
+        drop(key);
+        let serialized = list.into_serialized();
+        assert_eq!(serialized.command_count(), 2);
+        assert_eq!(
+            String::from_utf8_lossy(serialized.as_bytes()),
+            "*2\r\n$3\r\nGET\r\n$8\r\nsome-key\r\n*2\r\n$3\r\nDEL\r\n$8\r\nsome-key\r\n"
+        );
+    }
+
     #[test]
     fn multiple_args() {
         let arguments = vec!["a", "b", "c"];
@@ -1,51 +1,65 @@
-use crate::{Command, CommandList, DataType, Error, Result, Value};
-use futures::{future::BoxFuture, lock::Mutex, FutureExt};
-
-#[cfg(feature = "runtime_async_std")]
-use async_std::{
-    io,
-    net::{TcpStream, ToSocketAddrs},
+use crate::{
+    Command, CommandList, DataType, Error, FromValue, FunctionRestorePolicy, GeoPosition,
+    GeoSearchBuilder, GeoSearchResult, GeoUnit, HashExpireResult, LibraryInfo, Result,
+    SerializedCommand, SerializedCommandList, StreamEntry, StreamId, StreamTrim, ToArg, Ttl, Value,
 };
+use futures::{lock::Mutex, StreamExt};
+
 #[cfg(feature = "runtime_async_std")]
-use futures::{AsyncReadExt, AsyncWriteExt};
+use async_std::net::{TcpStream, ToSocketAddrs};
 
 #[cfg(feature = "runtime_tokio")]
-use tokio::{
-    io::{self, AsyncReadExt, AsyncWriteExt},
-    net::{TcpStream, ToSocketAddrs},
-};
+use tokio::net::{TcpStream, ToSocketAddrs};
 
-use std::sync::Arc;
+use std::{borrow::Cow, collections::HashMap, future::Future, sync::Arc};
+
+//Defined before the submodules so that they can use it as well.
+macro_rules! check_slice_not_empty {
+    ($slice:ident) => {
+        if $slice.is_empty() {
+            return Err(Error::EmptySlice);
+        }
+    };
+}
 
+mod buffered;
 pub mod builder;
+pub mod migrate;
+mod parse;
+pub mod pubsub;
 pub mod scan;
 pub mod stream;
-pub use scan::{HScanBuilder, HScanStream, ScanBuilder, ScanStream};
+pub mod transaction;
+pub use migrate::{CopyKeysStream, CopyProgress};
+pub use pubsub::{PubSub, PubSubEvent};
+pub use scan::{HScanBuilder, HScanStream, ScanBuilder, ScanStream, ZScanBuilder, ZScanStream};
 pub use stream::{Message, MessageStream, PMessage, PMessageStream, ResponseStream};
+pub use transaction::Transaction;
 
-use builder::MSetBuilder;
+use crate::streams::{
+    self, AutoClaim, ConsumerInfo, GroupConsumerBuilder, GroupInfo, GroupRead, PendingEntry,
+    PendingSummary, StreamInfo, XPendingBuilder, XReadStream,
+};
+pub(crate) use buffered::BufferedStream;
+use builder::{
+    Aggregate, BitFieldBuilder, BitFieldType, BitOp, BitRange, ExpireCondition, GetExOption,
+    InsertPosition, ListSide, MSetBuilder, MigrateOptions, RestoreOptions, SetOptions, SortBuilder,
+    ZAddBuilder, ZRangeBuilder,
+};
+#[cfg(feature = "bench")]
+pub use parse::parse_bench;
 
 #[cfg(test)]
 mod test;
 
-macro_rules! check_slice_not_empty {
-    ($slice:ident) => {
-        if $slice.is_empty() {
-            return Err(Error::EmptySlice);
-        }
-    };
-}
-
-async fn read_until(r: &mut TcpStream, byte: u8) -> io::Result<Vec<u8>> {
-    let mut buffer = Vec::new();
-    let mut single = [0; 1];
-    loop {
-        r.read(&mut single).await?;
-        buffer.push(single[0]);
-        if single[0] == byte {
-            return Ok(buffer);
-        }
-    }
+///The version of the Redis protocol used by a connection. See the [Redis documentation](https://redis.io/topics/protocol)
+///for the differences between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolVersion {
+    ///RESP2, the default and the only version supported before Redis 6.
+    Resp2,
+    ///RESP3, which uses more specific reply types such as maps, sets and doubles. Requires Redis 6 or newer.
+    Resp3,
 }
 
 ///A connection to Redis. Copying is cheap as the inner type is a simple, futures-aware, `Arc<Mutex>`, and will
@@ -55,7 +69,7 @@ async fn read_until(r: &mut TcpStream, byte: u8) -> io::Result<Vec<u8>> {
 ///Check the [Redis command reference](https://redis.io/commands) for in-depth explanations of each command.
 #[derive(Clone, Debug)]
 pub struct Connection {
-    pub(crate) stream: Arc<Mutex<TcpStream>>,
+    pub(crate) stream: Arc<Mutex<BufferedStream>>,
 }
 
 impl Connection {
@@ -64,15 +78,47 @@ impl Connection {
     where
         A: ToSocketAddrs,
     {
-        let stream = Arc::new(Mutex::new(
-            TcpStream::connect(address)
-                .await
-                .map_err(Error::ConnectionFailed)?,
-        ));
+        let stream = TcpStream::connect(address)
+            .await
+            .map_err(Error::ConnectionFailed)?;
+        let stream = Arc::new(Mutex::new(BufferedStream::new(stream)));
 
         Ok(Self { stream })
     }
 
+    ///Connect to a Redis instance running at `address`, using `protocol` for every reply.
+    ///# Example
+    ///```
+    ///use darkredis::{Command, Connection, ProtocolVersion, Value};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///let mut connection = Connection::connect_with_protocol("127.0.0.1:6379", ProtocolVersion::Resp3)
+    ///    .await
+    ///    .unwrap();
+    ///# connection.del("resp3-hash").await.unwrap();
+    ///connection.hset("resp3-hash", "field", "value").await.unwrap();
+    ///
+    ///let command = Command::new("HGETALL").arg(b"resp3-hash");
+    ///assert_eq!(
+    ///    connection.run_command(command).await.unwrap(),
+    ///    Value::Map(vec![(Value::String(b"field".to_vec()), Value::String(b"value".to_vec()))])
+    ///);
+    ///# connection.del("resp3-hash").await.unwrap();
+    ///# }
+    ///```
+    pub async fn connect_with_protocol<A>(address: A, protocol: ProtocolVersion) -> Result<Self>
+    where
+        A: ToSocketAddrs,
+    {
+        let mut out = Self::connect(address).await?;
+        if protocol != ProtocolVersion::Resp2 {
+            out.hello(protocol).await?;
+        }
+
+        Ok(out)
+    }
+
     ///Connect to a Redis instance running at `address`, and authenticate using `password`.
     pub async fn connect_and_auth<A, P>(address: A, password: P) -> Result<Self>
     where
@@ -85,93 +131,6 @@ impl Connection {
         Ok(out)
     }
 
-    async fn parse_simple_value(buf: &[u8]) -> Result<Value> {
-        match buf[0] {
-            b'+' => {
-                if buf == b"+OK\r\n" {
-                    Ok(Value::Ok)
-                } else {
-                    Ok(Value::String(buf[1..].into()))
-                }
-            }
-            b'-' => Err(Error::RedisError(
-                String::from_utf8_lossy(&buf[1..]).to_string(),
-            )),
-            b':' => {
-                let string = String::from_utf8_lossy(&buf[1..]);
-                let num = string.trim().parse::<isize>().unwrap();
-                Ok(Value::Integer(num))
-            }
-            _ => Err(Error::UnexpectedResponse(
-                String::from_utf8_lossy(buf).to_string(),
-            )),
-        }
-    }
-
-    async fn parse_string(start: &[u8], stream: &mut TcpStream) -> Result<Value> {
-        if start == b"$-1\r\n" {
-            Ok(Value::Nil)
-        } else {
-            let num = String::from_utf8_lossy(&start[1..])
-                .trim()
-                .parse::<usize>()
-                .unwrap();
-            let mut buf = vec![0u8; num + 2]; // add two to catch the final \r\n from Redis
-            stream.read_exact(&mut buf).await?;
-
-            buf.pop(); //Discard the last \r\n
-            buf.pop();
-            Ok(Value::String(buf))
-        }
-    }
-
-    fn parse_array<'a>(start: &'a [u8], stream: &'a mut TcpStream) -> BoxFuture<'a, Result<Value>> {
-        async move {
-            let num_parsed = String::from_utf8_lossy(&start[1..])
-                .trim()
-                .parse::<i32>()
-                .unwrap();
-
-            // result can be negative (blpop/brpop return '-1' on timeout)
-            if num_parsed < 0 {
-                return Ok(Value::Nil);
-            }
-
-            let num = num_parsed as usize;
-            let mut values = Vec::with_capacity(num);
-
-            for _ in 0..num {
-                let buf = read_until(stream, b'\n').await?;
-                match buf[0] {
-                    b'+' | b'-' | b':' => values.push(Self::parse_simple_value(&buf).await?),
-                    b'$' => values.push(Self::parse_string(&buf, stream).await?),
-                    b'*' => values.push(Self::parse_array(&buf, stream).await?),
-                    _ => {
-                        return Err(Error::UnexpectedResponse(
-                            String::from_utf8_lossy(&buf).to_string(),
-                        ))
-                    }
-                }
-            }
-
-            Ok(Value::Array(values))
-        }
-        .boxed()
-    }
-
-    //Read a value from the connection.
-    pub(crate) async fn read_value(mut stream: &mut TcpStream) -> Result<Value> {
-        let buf = read_until(&mut stream, b'\n').await?;
-        match buf[0] {
-            b'+' | b'-' | b':' => Self::parse_simple_value(&buf).await,
-            b'$' => Self::parse_string(&buf, &mut stream).await,
-            b'*' => Self::parse_array(&buf, &mut stream).await,
-            _ => Err(Error::UnexpectedResponse(
-                String::from_utf8_lossy(&buf).to_string(),
-            )),
-        }
-    }
-
     ///Run a single command on this connection.
     #[inline]
     pub async fn run_command(&mut self, command: Command<'_>) -> Result<Value> {
@@ -180,6 +139,31 @@ impl Connection {
         self.run_command_with_buffer(command, &mut buffer).await
     }
 
+    ///Run a single command on this connection, converting the reply into `T`. Returns an
+    ///[`Error::UnexpectedResponse`](enum.Error.html#variant.UnexpectedResponse) if the reply
+    ///can't be converted, see [`FromValue`](trait.FromValue.html) for more details.
+    ///# Example
+    ///```
+    ///use darkredis::{Command, Connection};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///# connection.del("query-list").await.unwrap();
+    ///connection.rpush("query-list", "1").await.unwrap();
+    ///connection.rpush("query-list", "2").await.unwrap();
+    ///
+    ///let command = Command::new("LRANGE").arg(b"query-list").arg(b"0").arg(b"-1");
+    ///let list: Vec<u32> = connection.query(command).await.unwrap();
+    ///assert_eq!(list, vec![1, 2]);
+    ///# connection.del("query-list").await.unwrap();
+    ///# }
+    ///```
+    #[inline]
+    pub async fn query<T: FromValue>(&mut self, command: Command<'_>) -> Result<T> {
+        T::from_value(self.run_command(command).await?)
+    }
+
     ///Run a single command on this connection, using `buffer` for serialization.
     ///See [`run_commands_with_buffer`](struct.Connection.html#method.run_commands_with_buffer) for more details.
     pub async fn run_command_with_buffer(
@@ -189,9 +173,9 @@ impl Connection {
     ) -> Result<Value> {
         let mut stream = self.stream.lock().await;
         command.serialize(buffer);
-        stream.write_all(&buffer).await?;
+        stream.write_all(buffer).await?;
 
-        Ok(Self::read_value(&mut stream).await?)
+        stream.read_value().await
     }
 
     ///Run a series of commands on this connection, returning a stream of the results.
@@ -213,12 +197,152 @@ impl Connection {
         let mut lock = self.stream.lock().await;
         let command_count = command.command_count();
         command.serialize(buf);
-        lock.write_all(&buf).await?;
+        lock.write_all(buf).await?;
         buf.clear();
 
         Ok(ResponseStream::new(command_count, self.stream.clone()))
     }
 
+    ///Run a command which has already been serialized. Unlike
+    ///[`run_command`](struct.Connection.html#method.run_command), `command` can be run again afterwards.
+    pub async fn run_serialized_command(&mut self, command: &SerializedCommand) -> Result<Value> {
+        let mut stream = self.stream.lock().await;
+        stream.write_all(command.as_bytes()).await?;
+
+        stream.read_value().await
+    }
+
+    ///Run a series of commands which have already been serialized, returning a stream of the results.
+    ///Unlike [`run_commands`](struct.Connection.html#method.run_commands), `commands` can be run again afterwards.
+    pub async fn run_serialized_commands(
+        &mut self,
+        commands: &SerializedCommandList,
+    ) -> Result<ResponseStream> {
+        let mut lock = self.stream.lock().await;
+        lock.write_all(commands.as_bytes()).await?;
+
+        Ok(ResponseStream::new(
+            commands.command_count(),
+            self.stream.clone(),
+        ))
+    }
+
+    ///Run `transaction` atomically, converting the results of its commands into `T`. Usually `T` is
+    ///a tuple with one element per command, or a `Vec` of them.
+    ///# Return value
+    ///`None` if the transaction was aborted because a key watched using
+    ///[`watch`](struct.Connection.html#method.watch) was modified. If a command failed to be queued,
+    ///its error is returned and none of the commands are run.
+    ///See [`Transaction`](struct.Transaction.html) for an example.
+    pub async fn run_transaction<T>(&mut self, transaction: Transaction<'_>) -> Result<Option<T>>
+    where
+        T: FromValue,
+    {
+        let mut buffer = Vec::new();
+        //MULTI and every command reply with OK or QUEUED before the reply to EXEC
+        let reply_count = transaction.command_count() + 1;
+        transaction.serialize(&mut buffer);
+
+        let mut stream = self.stream.lock().await;
+        stream.write_all(&buffer).await?;
+
+        //Read every reply even after an error, so that the connection can still be used.
+        let mut error = None;
+        for _ in 0..reply_count {
+            match stream.read_value().await {
+                Ok(_) => (),
+                Err(e @ Error::RedisError(_)) => {
+                    if error.is_none() {
+                        error = Some(e);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        let result = stream.read_value().await;
+        //The reply to EXEC is just EXECABORT, the error which caused it is more useful.
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        match result? {
+            Value::Nil => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+
+    ///Run the transaction built by `func` while watching `keys`, retrying until none of them are
+    ///modified by someone else before it is run. `func` receives a handle to this connection, which
+    ///it can use to read the current values of `keys`.
+    ///If `func` returns an error, the keys are unwatched and the error is returned.
+    ///# Example
+    ///```
+    ///use darkredis::{Command, Connection, Transaction};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///# connection.del("watched-key").await.unwrap();
+    ///connection.set("watched-key", "2").await.unwrap();
+    ///
+    /////Double the value of the key, without using INCRBY
+    ///let ((), value): ((), isize) = connection
+    ///    .watch_transaction(&["watched-key"], |mut connection| async move {
+    ///        let value: isize = connection.query(Command::new("GET").arg("watched-key")).await?;
+    ///        Ok(Transaction::new()
+    ///            .command(Command::new("SET").arg("watched-key").arg(value * 2))
+    ///            .command(Command::new("GET").arg("watched-key")))
+    ///    })
+    ///    .await
+    ///    .unwrap();
+    ///assert_eq!(value, 4);
+    ///# connection.del("watched-key").await.unwrap();
+    ///# }
+    ///```
+    pub async fn watch_transaction<'t, K, F, Fut, T>(
+        &mut self,
+        keys: &[K],
+        mut func: F,
+    ) -> Result<T>
+    where
+        K: AsRef<[u8]>,
+        F: FnMut(Connection) -> Fut,
+        Fut: Future<Output = Result<Transaction<'t>>>,
+        T: FromValue,
+    {
+        check_slice_not_empty!(keys);
+
+        loop {
+            self.watch(keys).await?;
+            let transaction = match func(self.clone()).await {
+                Ok(t) => t,
+                Err(e) => {
+                    self.unwatch().await?;
+                    return Err(e);
+                }
+            };
+
+            if let Some(result) = self.run_transaction(transaction).await? {
+                return Ok(result);
+            }
+        }
+    }
+
+    ///Watch `keys`, making the next transaction fail if any of them are modified before it is run.
+    pub async fn watch<K>(&mut self, keys: &[K]) -> Result<()>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(keys);
+
+        self.query(Command::new("WATCH").args(keys)).await
+    }
+
+    ///Stop watching every key watched using [`watch`](struct.Connection.html#method.watch).
+    pub async fn unwatch(&mut self) -> Result<()> {
+        self.query(Command::new("UNWATCH")).await
+    }
+
     ///Delete `field` from the hash set stored at `key`.
     ///# Return value
     ///`true` when the field was deleted, `false` if it didn't exist
@@ -227,9 +351,7 @@ impl Connection {
         K: AsRef<[u8]>,
         F: AsRef<[u8]>,
     {
-        self.run_command(Command::new("HDEL").arg(&key).arg(&field))
-            .await
-            .map(|v| v.unwrap_bool())
+        self.query(Command::new("HDEL").arg(&key).arg(&field)).await
     }
 
     ///Delete every field in `fields` from the hash set stored at `key`.
@@ -242,9 +364,8 @@ impl Connection {
     {
         check_slice_not_empty!(fields);
 
-        self.run_command(Command::new("HDEL").arg(&key).args(&fields))
+        self.query(Command::new("HDEL").arg(&key).args(fields))
             .await
-            .map(|v| v.unwrap_integer())
     }
 
     ///Check if `field` exists in the hash set `key`.
@@ -253,9 +374,8 @@ impl Connection {
         K: AsRef<[u8]>,
         F: AsRef<[u8]>,
     {
-        self.run_command(Command::new("HEXISTS").arg(&key).arg(&field))
+        self.query(Command::new("HEXISTS").arg(&key).arg(&field))
             .await
-            .map(|v| v.unwrap_bool())
     }
 
     ///Get the value of `field` in the hash set at `key`.
@@ -264,9 +384,7 @@ impl Connection {
         K: AsRef<[u8]>,
         F: AsRef<[u8]>,
     {
-        self.run_command(Command::new("HGET").arg(&key).arg(&field))
-            .await
-            .map(|v| v.optional_string())
+        self.query(Command::new("HGET").arg(&key).arg(&field)).await
     }
 
     ///Set the value of `field` in the hash set stored at `key` to `Value`.
@@ -278,9 +396,8 @@ impl Connection {
         F: AsRef<[u8]>,
         V: AsRef<[u8]>,
     {
-        self.run_command(Command::new("HSET").arg(&key).arg(&field).arg(&value))
+        self.query(Command::new("HSET").arg(&key).arg(&field).arg(&value))
             .await
-            .map(|v| v.unwrap_integer())
     }
 
     ///Set the value of `field` in the hash set stored at `key` to `Value`. If `field`
@@ -293,9 +410,8 @@ impl Connection {
         F: AsRef<[u8]>,
         V: AsRef<[u8]>,
     {
-        self.run_command(Command::new("HSETNX").arg(&key).arg(&field).arg(&value))
+        self.query(Command::new("HSETNX").arg(&key).arg(&field).arg(&value))
             .await
-            .map(|v| v.unwrap_bool())
     }
 
     ///Set fields in the hash set at `key` to their values in `builder`. See
@@ -309,7 +425,7 @@ impl Connection {
         let mut command = Command::new("HSET").arg(&key);
         command.append_msetbuilder(&builder);
 
-        self.run_command(command).await.map(|v| v.unwrap_integer())
+        self.query(command).await
     }
 
     ///Increment `field` in the hash set `key` by `val`.
@@ -320,10 +436,8 @@ impl Connection {
         K: AsRef<[u8]>,
         F: AsRef<[u8]>,
     {
-        let val = val.to_string();
-        self.run_command(Command::new("HINCRBY").arg(&key).arg(&field).arg(&val))
+        self.query(Command::new("HINCRBY").arg(&key).arg(&field).arg(val))
             .await
-            .map(|v| v.unwrap_integer())
     }
 
     ///Increment `field` in the hash set `key` by `val`, floating point version.
@@ -334,10 +448,8 @@ impl Connection {
         K: AsRef<[u8]>,
         F: AsRef<[u8]>,
     {
-        let val = val.to_string();
-        let command = Command::new("HINCRBYFLOAT").arg(&key).arg(&field).arg(&val);
-        let result = self.run_command(command).await?.unwrap_string();
-        Ok(String::from_utf8_lossy(&result).parse::<f64>().unwrap())
+        let command = Command::new("HINCRBYFLOAT").arg(&key).arg(&field).arg(val);
+        self.query(command).await
     }
 
     ///Get the name of each hash field stored at `key`.
@@ -345,9 +457,7 @@ impl Connection {
     where
         K: AsRef<[u8]>,
     {
-        self.run_command(Command::new("HKEYS").arg(&key))
-            .await
-            .map(|v| v.unwrap_string_array())
+        self.query(Command::new("HKEYS").arg(&key)).await
     }
 
     ///Get the number of fields in the hash stored at `key`.
@@ -355,9 +465,7 @@ impl Connection {
     where
         K: AsRef<[u8]>,
     {
-        self.run_command(Command::new("HLEN").arg(&key))
-            .await
-            .map(|v| v.unwrap_integer())
+        self.query(Command::new("HLEN").arg(&key)).await
     }
 
     ///Get the number of bytes in `field` in the hash set `key`
@@ -366,9 +474,8 @@ impl Connection {
         K: AsRef<[u8]>,
         F: AsRef<[u8]>,
     {
-        self.run_command(Command::new("HSTRLEN").arg(&key).arg(&field))
+        self.query(Command::new("HSTRLEN").arg(&key).arg(&field))
             .await
-            .map(|v| v.unwrap_integer())
     }
 
     ///Get the value of each field in the hash field stored at `key`.
@@ -376,822 +483,3217 @@ impl Connection {
     where
         K: AsRef<[u8]>,
     {
-        self.run_command(Command::new("HVALS").arg(&key))
-            .await
-            .map(|v| v.unwrap_array())
+        self.query(Command::new("HVALS").arg(&key)).await
     }
 
-    ///Send a `PING` to the server, returning Ok(()) on success.
-    pub async fn ping(&mut self) -> Result<()> {
-        self.run_command(Command::new("PING")).await.map(|_| ())
+    ///Get every field and value in the hash set stored at `key`.
+    pub async fn hgetall<K>(&mut self, key: K) -> Result<HashMap<Vec<u8>, Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.query(Command::new("HGETALL").arg(&key)).await
     }
 
-    ///Consume `self`, and subscribe to `channels`, returning a stream of [`Message's`](struct.Message.html). As of now, there's no way to get the connection back, nor change the subscribed topics.
-    pub async fn subscribe<K>(mut self, channels: &[K]) -> Result<stream::MessageStream>
+    ///Like [`hgetall`](struct.Connection.html#method.hgetall), but convert the fields and values
+    ///into any type implementing [`FromValue`](trait.FromValue.html).
+    ///# Example
+    ///```
+    ///use darkredis::Connection;
+    ///use std::collections::BTreeMap;
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///connection.hset("hgetall-example", "apples", "3").await.unwrap();
+    ///connection.hset("hgetall-example", "pears", "5").await.unwrap();
+    ///
+    ///let fruit: BTreeMap<String, isize> = connection.hgetall_as("hgetall-example").await.unwrap();
+    ///assert_eq!(fruit["apples"], 3);
+    ///assert_eq!(fruit["pears"], 5);
+    ///# connection.del("hgetall-example").await.unwrap();
+    ///# }
+    ///```
+    pub async fn hgetall_as<K, T>(&mut self, key: K) -> Result<T>
     where
         K: AsRef<[u8]>,
+        T: FromValue,
     {
-        let command = Command::new("SUBSCRIBE").args(channels);
-
-        //TODO: Find out if we care about the values given here
-        let _ = self.run_command(command).await?;
-        {
-            let mut stream = self.stream.lock().await;
-            for _ in 0..channels.len() - 1 {
-                let response = Self::read_value(&mut stream).await?;
-                assert_eq!(
-                    response.unwrap_array()[0],
-                    Value::String("subscribe".into())
-                );
-            }
-        }
-
-        Ok(stream::MessageStream::new(self))
+        self.query(Command::new("HGETALL").arg(&key)).await
     }
 
-    ///Exactly like [`subscribe`](struct.Connection.html#method.subscribe), but subscribe to channels
-    ///matching patterns instead.
-    pub async fn psubscribe<K>(mut self, patterns: &[K]) -> Result<stream::PMessageStream>
+    ///Get the value of every field in `fields` in the hash set stored at `key`, or `None` if it doesn't exist.
+    pub async fn hmget<K, F>(&mut self, key: K, fields: &[F]) -> Result<Vec<Option<Vec<u8>>>>
     where
         K: AsRef<[u8]>,
+        F: AsRef<[u8]>,
     {
-        let command = Command::new("PSUBSCRIBE").args(patterns);
-
-        //TODO: Find out if we care about the values given here
-        let _ = self.run_command(command).await?;
-        {
-            let mut stream = self.stream.lock().await;
-            for _ in 0..patterns.len() - 1 {
-                let response = Self::read_value(&mut stream).await?;
-                assert_eq!(
-                    response.unwrap_array()[0],
-                    Value::String("psubscribe".into())
-                );
-            }
-        }
-
-        Ok(stream::PMessageStream::new(self))
+        check_slice_not_empty!(fields);
+        self.query(Command::new("HMGET").arg(&key).args(fields))
+            .await
     }
 
-    ///Publish `message` to `channel`.
-    ///# Return Value
-    ///Returns how many clients received the message.
-    pub async fn publish<C, M>(&mut self, channel: C, message: M) -> Result<isize>
+    ///Get a random field from the hash set stored at `key`, or `None` if it doesn't exist.
+    pub async fn hrandfield<K>(&mut self, key: K) -> Result<Option<Vec<u8>>>
     where
-        C: AsRef<[u8]>,
-        M: AsRef<[u8]>,
+        K: AsRef<[u8]>,
     {
-        let command = Command::new("PUBLISH").arg(&channel).arg(&message);
-        self.run_command(command).await.map(|i| i.unwrap_integer())
+        self.query(Command::new("HRANDFIELD").arg(&key)).await
     }
 
-    ///Set `key` to `value`.
-    pub async fn set<K, D>(&mut self, key: K, value: D) -> Result<()>
+    ///Get up to `count` distinct random fields from the hash set stored at `key`. If `count` is
+    ///negative, get exactly `-count` fields which may contain duplicates.
+    pub async fn hrandfield_count<K>(&mut self, key: K, count: isize) -> Result<Vec<Vec<u8>>>
     where
         K: AsRef<[u8]>,
-        D: AsRef<[u8]>,
     {
-        let command = Command::new("SET").arg(&key).arg(&value);
-
-        self.run_command(command).await.map(|_| ())
+        self.query(Command::new("HRANDFIELD").arg(&key).arg(count))
+            .await
     }
 
-    ///Set the key `key` to `data`, and set it to expire after `seconds` seconds.
-    pub async fn set_and_expire_seconds<K, D>(
+    ///Like [`hrandfield_count`](struct.Connection.html#method.hrandfield_count), but also get the
+    ///value of each field.
+    pub async fn hrandfield_withvalues<K>(
         &mut self,
         key: K,
-        data: D,
-        seconds: u32,
-    ) -> Result<()>
+        count: isize,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>>
     where
         K: AsRef<[u8]>,
-        D: AsRef<[u8]>,
     {
-        let seconds = seconds.to_string();
-        let command = Command::new("SET")
+        let command = Command::new("HRANDFIELD")
             .arg(&key)
-            .arg(&data)
-            .arg(b"EX")
-            .arg(&seconds);
-
-        self.run_command(command).await.map(|_| ())
+            .arg(count)
+            .arg("WITHVALUES");
+        self.query(command).await
     }
 
-    ///Set the key `key` to `data`, and set it to expire after `milliseconds` ms.
-    pub async fn set_and_expire_ms<K, D>(
+    ///Set every field in `fields` in the hash set stored at `key` to expire after `seconds`
+    ///seconds, if `condition` is met. Requires Redis 7.4 or newer.
+    ///# Return value
+    ///The result for each field, in the same order as `fields`.
+    pub async fn hexpire<K, F>(
         &mut self,
         key: K,
-        data: D,
-        milliseconds: u32,
-    ) -> Result<()>
+        seconds: u64,
+        fields: &[F],
+        condition: Option<ExpireCondition>,
+    ) -> Result<Vec<HashExpireResult>>
     where
         K: AsRef<[u8]>,
-        D: AsRef<[u8]>,
+        F: AsRef<[u8]>,
     {
-        let milliseconds = milliseconds.to_string();
-        let command = Command::new("SET")
-            .arg(&key)
-            .arg(&data)
-            .arg(b"PX")
-            .arg(&milliseconds);
-
-        self.run_command(command).await.map(|_| ())
+        check_slice_not_empty!(fields);
+        let mut command = Command::new("HEXPIRE").arg(&key).arg(seconds);
+        if let Some(condition) = condition {
+            command.append_arg(condition.as_arg());
+        }
+        self.query(Self::append_hash_fields(command, fields)).await
     }
 
-    ///Set `key` to expire `seconds` seconds from now.
-    pub async fn expire_seconds<K>(&mut self, key: K, seconds: u32) -> Result<isize>
+    ///Like [`hexpire`](struct.Connection.html#method.hexpire), but the expiry is given in milliseconds.
+    pub async fn hpexpire<K, F>(
+        &mut self,
+        key: K,
+        milliseconds: u64,
+        fields: &[F],
+        condition: Option<ExpireCondition>,
+    ) -> Result<Vec<HashExpireResult>>
     where
         K: AsRef<[u8]>,
+        F: AsRef<[u8]>,
     {
-        let seconds = seconds.to_string();
-        let command = Command::new("EXPIRE").arg(&key).arg(&seconds);
-
-        self.run_command(command).await.map(|i| i.unwrap_integer())
+        check_slice_not_empty!(fields);
+        let mut command = Command::new("HPEXPIRE").arg(&key).arg(milliseconds);
+        if let Some(condition) = condition {
+            command.append_arg(condition.as_arg());
+        }
+        self.query(Self::append_hash_fields(command, fields)).await
     }
 
-    ///Set `key` to expire `milliseconds` ms from now.
-    pub async fn expire_ms<K>(&mut self, key: K, seconds: u32) -> Result<isize>
+    ///Get the remaining time to live in seconds of every field in `fields` in the hash set stored
+    ///at `key`. Requires Redis 7.4 or newer.
+    pub async fn httl<K, F>(&mut self, key: K, fields: &[F]) -> Result<Vec<Ttl>>
     where
         K: AsRef<[u8]>,
+        F: AsRef<[u8]>,
     {
-        let seconds = seconds.to_string();
-        let command = Command::new("PEXPIRE").arg(&key).arg(&seconds);
-
-        self.run_command(command).await.map(|i| i.unwrap_integer())
+        check_slice_not_empty!(fields);
+        let command = Command::new("HTTL").arg(&key);
+        self.query(Self::append_hash_fields(command, fields)).await
     }
 
-    ///Set `key` to expire at Unix timestamp `timestamp`, measured in seconds.
-    pub async fn expire_at_seconds<K>(&mut self, key: K, timestamp: u64) -> Result<isize>
+    ///Like [`httl`](struct.Connection.html#method.httl), but the time to live is in milliseconds.
+    pub async fn hpttl<K, F>(&mut self, key: K, fields: &[F]) -> Result<Vec<Ttl>>
     where
         K: AsRef<[u8]>,
+        F: AsRef<[u8]>,
     {
-        let timestamp = timestamp.to_string();
-        let command = Command::new("EXPIREAT").arg(&key).arg(&timestamp);
-
-        self.run_command(command).await.map(|i| i.unwrap_integer())
+        check_slice_not_empty!(fields);
+        let command = Command::new("HPTTL").arg(&key);
+        self.query(Self::append_hash_fields(command, fields)).await
     }
 
-    ///Set `key` to expire at Unix timestamp `timestamp`, measured in milliseconds.
-    pub async fn expire_at_ms<K>(&mut self, key: K, timestamp: u64) -> Result<isize>
+    ///Remove the expiry of every field in `fields` in the hash set stored at `key`. Requires Redis
+    ///7.4 or newer.
+    ///# Return value
+    ///For each field, `true` if its expiry was removed, or `false` if it has no expiry or doesn't exist.
+    pub async fn hpersist<K, F>(&mut self, key: K, fields: &[F]) -> Result<Vec<bool>>
     where
         K: AsRef<[u8]>,
+        F: AsRef<[u8]>,
     {
-        let timestamp = timestamp.to_string();
-        let command = Command::new("PEXPIREAT").arg(&key).arg(&timestamp);
+        check_slice_not_empty!(fields);
+        let command = Command::new("HPERSIST").arg(&key);
+        let results: Vec<isize> = self
+            .query(Self::append_hash_fields(command, fields))
+            .await?;
+        Ok(results.into_iter().map(|result| result == 1).collect())
+    }
 
-        self.run_command(command).await.map(|i| i.unwrap_integer())
+    //Append the `FIELDS numfields field...` arguments used by the hash field expiry commands.
+    fn append_hash_fields<'a, F>(command: Command<'a>, fields: &'a [F]) -> Command<'a>
+    where
+        F: AsRef<[u8]>,
+    {
+        command.arg("FIELDS").arg(fields.len()).args(fields)
     }
 
-    ///Delete `key`.
+    ///Switch the protocol used by this connection to `protocol` using the `HELLO` command.
     ///# Return value
-    ///The number of deleted keys.
-    pub async fn del<K>(&mut self, key: K) -> Result<bool>
+    ///Information about the Redis server, which is a [`Value::Map`](enum.Value.html#Map.v) when using RESP3.
+    pub async fn hello(&mut self, protocol: ProtocolVersion) -> Result<Value> {
+        let version = match protocol {
+            ProtocolVersion::Resp2 => "2",
+            ProtocolVersion::Resp3 => "3",
+        };
+        self.run_command(Command::new("HELLO").arg(version)).await
+    }
+
+    ///Load the Lua script `code` into the script cache without running it. See
+    ///[`Script`](struct.Script.html) for a more convenient way of running scripts.
+    ///# Return value
+    ///The SHA1 hash of the script, used to run it with `EVALSHA`.
+    pub async fn script_load<C>(&mut self, code: C) -> Result<String>
     where
-        K: AsRef<[u8]>,
+        C: AsRef<[u8]>,
     {
-        let command = Command::new("DEL").arg(&key);
-        self.run_command(command).await.map(|i| i.unwrap_bool())
+        self.query(Command::new("SCRIPT").arg("LOAD").arg(&code))
+            .await
     }
 
-    ///Delete every element in `keys`.
+    ///Check if the scripts with the SHA1 hashes in `hashes` exist in the script cache.
     ///# Return value
-    ///The number of deleted keys.
-    pub async fn del_slice<K>(&mut self, keys: &[K]) -> Result<isize>
+    ///Whether each script exists, in the same order as `hashes`.
+    pub async fn script_exists<H>(&mut self, hashes: &[H]) -> Result<Vec<bool>>
     where
-        K: AsRef<[u8]>,
+        H: AsRef<[u8]>,
     {
-        check_slice_not_empty!(keys);
-        let command = Command::new("DEL").args(&keys);
-        self.run_command(command).await.map(|i| i.unwrap_integer())
+        check_slice_not_empty!(hashes);
+
+        self.query(Command::new("SCRIPT").arg("EXISTS").args(hashes))
+            .await
     }
 
-    ///Get the value of `key`.
-    pub async fn get<K>(&mut self, key: K) -> Result<Option<Vec<u8>>>
+    ///Remove every script from the script cache.
+    pub async fn script_flush(&mut self) -> Result<()> {
+        self.query(Command::new("SCRIPT").arg("FLUSH")).await
+    }
+
+    ///Load the function library `code` using [`FUNCTION LOAD`](https://redis.io/commands/function-load).
+    ///If `replace` is true, an existing library with the same name is replaced instead of returning an error.
+    ///# Return value
+    ///The name of the loaded library.
+    pub async fn function_load<C>(&mut self, code: C, replace: bool) -> Result<String>
     where
-        K: AsRef<[u8]>,
+        C: AsRef<[u8]>,
     {
-        let command = Command::new("GET").arg(&key);
+        let mut command = Command::new("FUNCTION").arg("LOAD");
+        if replace {
+            command.append_arg("REPLACE");
+        }
 
-        Ok(self.run_command(command).await?.optional_string())
+        self.query(command.arg(&code)).await
     }
 
-    ///Push a value to `list` from the left.
+    ///Read the function library at `path` and load it using
+    ///[`function_load`](struct.Connection.html#method.function_load), for deploying libraries at
+    ///startup.
     ///# Return value
-    ///The number of elements in `list`
-    pub async fn lpush<K, V>(&mut self, list: K, value: V) -> Result<isize>
+    ///The name of the loaded library.
+    pub async fn function_load_file<P>(&mut self, path: P, replace: bool) -> Result<String>
     where
-        K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
+        P: AsRef<std::path::Path>,
     {
-        let command = Command::new("LPUSH").arg(&list).arg(&value);
+        #[cfg(feature = "runtime_tokio")]
+        let code = tokio::fs::read(path.as_ref()).await?;
+        #[cfg(feature = "runtime_async_std")]
+        let code = async_std::fs::read(path.as_ref()).await?;
 
-        Ok(self.run_command(command).await?.unwrap_integer())
+        self.function_load(code, replace).await
     }
 
-    ///Like [`lpush`](struct.Connection.html#method.lpush), but push multiple values.
-    pub async fn lpush_slice<K, V>(&mut self, key: K, values: &[V]) -> Result<isize>
+    ///List the loaded function libraries, optionally only those with names matching `pattern`. If
+    ///`with_code` is true, the source code of each library is included.
+    pub async fn function_list<P>(
+        &mut self,
+        pattern: Option<P>,
+        with_code: bool,
+    ) -> Result<Vec<LibraryInfo>>
     where
-        K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
+        P: AsRef<[u8]>,
     {
-        check_slice_not_empty!(values);
-        let command = Command::new("LPUSH").arg(&key).args(values);
+        let mut command = Command::new("FUNCTION").arg("LIST");
+        if let Some(ref pattern) = pattern {
+            command.append_arg("LIBRARYNAME");
+            command.append_arg(pattern);
+        }
+        if with_code {
+            command.append_arg("WITHCODE");
+        }
 
-        Ok(self.run_command(command).await?.unwrap_integer())
+        self.query(command).await
     }
 
-    ///Push a value to `list` from the right.
-    ///# Return value
-    ///The number of elements in `list`
-    pub async fn rpush<K, V>(&mut self, list: K, value: V) -> Result<isize>
+    ///Delete the function library named `library`, returning an error if it doesn't exist.
+    pub async fn function_delete<L>(&mut self, library: L) -> Result<()>
     where
-        K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
+        L: AsRef<[u8]>,
     {
-        let command = Command::new("RPUSH").arg(&list).arg(&value);
+        self.query(Command::new("FUNCTION").arg("DELETE").arg(&library))
+            .await
+    }
 
-        Ok(self.run_command(command).await?.unwrap_integer())
+    ///Delete every function library.
+    pub async fn function_flush(&mut self) -> Result<()> {
+        self.query(Command::new("FUNCTION").arg("FLUSH")).await
     }
 
-    ///Like [`rpush`](struct.Connection.html#method.rpush), but push multiple values through a slice.
-    pub async fn rpush_slice<K, V>(&mut self, key: K, values: &[V]) -> Result<isize>
+    ///Serialize every loaded function library, for use with
+    ///[`function_restore`](struct.Connection.html#method.function_restore).
+    pub async fn function_dump(&mut self) -> Result<Vec<u8>> {
+        self.query(Command::new("FUNCTION").arg("DUMP")).await
+    }
+
+    ///Restore function libraries from `payload`, as returned by
+    ///[`function_dump`](struct.Connection.html#method.function_dump). `policy` decides what
+    ///happens to existing libraries, defaulting to
+    ///[`FunctionRestorePolicy::Append`](enum.FunctionRestorePolicy.html#variant.Append).
+    pub async fn function_restore<D>(
+        &mut self,
+        payload: D,
+        policy: Option<FunctionRestorePolicy>,
+    ) -> Result<()>
     where
-        K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
+        D: AsRef<[u8]>,
     {
-        check_slice_not_empty!(values);
-        let command = Command::new("RPUSH").arg(&key).args(values);
+        let mut command = Command::new("FUNCTION").arg("RESTORE").arg(&payload);
+        if let Some(policy) = policy {
+            command.append_arg(policy.as_arg());
+        }
 
-        Ok(self.run_command(command).await?.unwrap_integer())
+        self.query(command).await
     }
 
-    ///Pop a value from a list from the left side.
-    ///# Return value
-    ///The value popped from `list`
-    pub async fn lpop<K>(&mut self, list: K) -> Result<Option<Vec<u8>>>
+    ///Call the function `function` from a loaded library with `keys` and `args`, converting the
+    ///result into `T`.
+    ///# Example
+    ///```
+    ///use darkredis::Connection;
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///let library = "#!lua name=fcall_example\n\
+    ///    redis.register_function('fcall_example', function(keys, args) return args[1] end)";
+    ///connection.function_load(library, true).await.unwrap();
+    ///
+    ///let empty: &[&str] = &[];
+    ///let result: String = connection.fcall("fcall_example", empty, &["hello"]).await.unwrap();
+    ///assert_eq!(result, "hello");
+    ///# connection.function_delete("fcall_example").await.unwrap();
+    ///# }
+    ///```
+    pub async fn fcall<F, K, A, T>(&mut self, function: F, keys: &[K], args: &[A]) -> Result<T>
     where
+        F: AsRef<[u8]>,
         K: AsRef<[u8]>,
+        A: AsRef<[u8]>,
+        T: FromValue,
     {
-        let command = Command::new("LPOP").arg(&list);
-
-        Ok(self.run_command(command).await?.optional_string())
+        self.query(Self::fcall_command("FCALL", &function, keys, args))
+            .await
     }
 
-    ///Pop a value from a list from the right side.
-    ///# Return value
-    ///The value popped from `list`
-    pub async fn rpop<K>(&mut self, list: K) -> Result<Option<Vec<u8>>>
+    ///Like [`fcall`](struct.Connection.html#method.fcall), but using `FCALL_RO`, which only allows
+    ///calling functions with the `no-writes` flag and can be run on read-only replicas.
+    pub async fn fcall_ro<F, K, A, T>(&mut self, function: F, keys: &[K], args: &[A]) -> Result<T>
     where
+        F: AsRef<[u8]>,
         K: AsRef<[u8]>,
+        A: AsRef<[u8]>,
+        T: FromValue,
     {
-        let command = Command::new("RPOP").arg(&list);
-
-        Ok(self.run_command(command).await?.optional_string())
+        self.query(Self::fcall_command("FCALL_RO", &function, keys, args))
+            .await
     }
 
-    ///Pop a value from one of the lists from the left side.
-    ///Block timeout seconds when there are no values to pop. A zero-value with block infinitely.
-    ///# Return value
-    ///* `Ok(Some((list,value)))`: name of the list and corresponding value
-    ///* `Ok(None)`: timeout (no values)
-    ///* `Err(err)`: there was an error
-    pub async fn blpop<K>(
-        &mut self,
-        lists: &[K],
-        timeout: u32,
-    ) -> Result<Option<(Vec<u8>, Vec<u8>)>>
+    //Build an FCALL or FCALL_RO command, which take the function and then the number of keys.
+    fn fcall_command<'a, F, K, A>(
+        command: &'static str,
+        function: &'a F,
+        keys: &'a [K],
+        args: &'a [A],
+    ) -> Command<'a>
     where
+        F: AsRef<[u8]>,
         K: AsRef<[u8]>,
+        A: AsRef<[u8]>,
     {
-        self.blpop_brpop(lists, timeout, "BLPOP").await
+        Command::new(command)
+            .arg(function)
+            .arg(keys.len())
+            .args(keys)
+            .args(args)
     }
 
-    ///Pop a value from one of the lists from the right side.
-    ///Block timeout seconds when there are no values to pop. A zero-value will block infinitely.
-    ///# Return value
-    ///* `Ok(Some((list,value)))`: name of the list and corresponding value
-    ///* `Ok(None)`: timeout (no values)
-    ///* `Err(err)`: there was an error
-    pub async fn brpop<K>(
-        &mut self,
-        lists: &[K],
-        timeout: u32,
-    ) -> Result<Option<(Vec<u8>, Vec<u8>)>>
+    ///Send a `PING` to the server, returning Ok(()) on success.
+    pub async fn ping(&mut self) -> Result<()> {
+        match self.run_command(Command::new("PING")).await? {
+            Value::Status(ref s) if s == "PONG" => Ok(()),
+            other => Err(Error::UnexpectedResponse(format!("{:?}", other))),
+        }
+    }
+
+    ///Consume `self`, and subscribe to `channels`, returning a stream of [`Message's`](struct.Message.html). There's no way to get the connection back, nor change the subscribed topics.
+    ///Use [`into_pubsub`](struct.Connection.html#method.into_pubsub) for that instead.
+    pub async fn subscribe<K>(self, channels: &[K]) -> Result<stream::MessageStream>
     where
         K: AsRef<[u8]>,
     {
-        self.blpop_brpop(lists, timeout, "BRPOP").await
+        check_slice_not_empty!(channels);
+        let mut pubsub = self.into_pubsub();
+        pubsub.subscribe(channels).await?;
+        Self::confirm_subscriptions(&mut pubsub, channels.len()).await?;
+
+        Ok(stream::MessageStream::new(pubsub))
     }
 
-    ///blpop and brpop common code
-    async fn blpop_brpop<K>(
-        &mut self,
-        lists: &[K],
-        timeout: u32,
-        redis_cmd: &str,
-    ) -> Result<Option<(Vec<u8>, Vec<u8>)>>
+    ///Exactly like [`subscribe`](struct.Connection.html#method.subscribe), but subscribe to channels
+    ///matching patterns instead.
+    pub async fn psubscribe<K>(self, patterns: &[K]) -> Result<stream::PMessageStream>
     where
         K: AsRef<[u8]>,
     {
-        let timeout = timeout.to_string();
-        let command = Command::new(redis_cmd).args(&lists).arg(&timeout);
-        match self.run_command(command).await? {
-            Value::Array(values) => {
-                let vlen = values.len();
-                if vlen == 2 {
-                    let mut v = values.into_iter().map(|s| s.unwrap_string());
-                    return Ok(Some(
-                        (v.next().unwrap(), v.next().unwrap()), // values.into_iter().map(|s| s.unwrap_string()).collect(),
-                    ));
-                }
-                Err(Error::UnexpectedResponse(format!(
-                    "{}: wrong number of elements received: {}",
-                    redis_cmd, vlen
-                )))
+        check_slice_not_empty!(patterns);
+        let mut pubsub = self.into_pubsub();
+        pubsub.psubscribe(patterns).await?;
+        Self::confirm_subscriptions(&mut pubsub, patterns.len()).await?;
+
+        Ok(stream::PMessageStream::new(pubsub))
+    }
+
+    //Wait until `count` subscriptions have been confirmed, which are always received before any messages.
+    async fn confirm_subscriptions(pubsub: &mut PubSub, count: usize) -> Result<()> {
+        for _ in 0..count {
+            match pubsub.next().await {
+                Some(Ok(PubSubEvent::Subscribe { .. }))
+                | Some(Ok(PubSubEvent::PSubscribe { .. })) => (),
+                Some(Err(e)) => return Err(e),
+                other => return Err(Error::UnexpectedResponse(format!("{:?}", other))),
             }
-            Value::Nil => Ok(None),
-            other => Err(Error::UnexpectedResponse(format!(
-                "{}: {:?}",
-                redis_cmd, other
-            ))),
         }
+
+        Ok(())
     }
 
-    ///Get a series of elements from `list`, from index `from` to `to`. If they are negative, take the
-    ///index from the right side of the list.
-    pub async fn lrange<K>(&mut self, list: K, from: isize, to: isize) -> Result<Vec<Vec<u8>>>
+    ///Consume `self`, and turn it into a [`PubSub`](struct.PubSub.html) connection, which can change
+    ///its subscriptions while receiving messages and be turned back into a normal connection.
+    pub fn into_pubsub(self) -> PubSub {
+        PubSub::new(self)
+    }
+
+    ///Publish `message` to `channel`.
+    ///# Return Value
+    ///Returns how many clients received the message.
+    pub async fn publish<C, M>(&mut self, channel: C, message: M) -> Result<isize>
     where
-        K: AsRef<[u8]>,
+        C: AsRef<[u8]>,
+        M: AsRef<[u8]>,
     {
-        let from = from.to_string();
-        let to = to.to_string();
-        let command = Command::new("LRANGE").arg(&list).arg(&from).arg(&to);
-
-        Ok(self
-            .run_command(command)
-            .await?
-            .unwrap_array()
-            .into_iter()
-            .map(|s| s.unwrap_string())
-            .collect())
+        let command = Command::new("PUBLISH").arg(&channel).arg(&message);
+        self.query(command).await
     }
 
-    ///Get the number of elements in `list`, or `None` if the list doesn't exist.
-    pub async fn llen<K>(&mut self, list: K) -> Result<Option<isize>>
+    ///Set `key` to `value`.
+    pub async fn set<K, D>(&mut self, key: K, value: D) -> Result<()>
     where
         K: AsRef<[u8]>,
+        D: AsRef<[u8]>,
     {
-        let command = Command::new("LLEN").arg(&list);
-        Ok(self.run_command(command).await?.optional_integer())
+        let command = Command::new("SET").arg(&key).arg(&value);
+
+        self.run_command(command).await.map(|_| ())
     }
 
-    ///Set the value of the element at `index` in `list` to `value`.
-    pub async fn lset<K, V>(&mut self, list: K, index: usize, value: V) -> Result<()>
+    ///Set the key `key` to `data`, and set it to expire after `seconds` seconds.
+    pub async fn set_and_expire_seconds<K, D>(
+        &mut self,
+        key: K,
+        data: D,
+        seconds: u32,
+    ) -> Result<()>
     where
         K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
+        D: AsRef<[u8]>,
     {
-        let index = index.to_string();
-        let command = Command::new("LSET").arg(&list).arg(&index).arg(&value);
+        let command = Command::new("SET")
+            .arg(&key)
+            .arg(&data)
+            .arg(b"EX")
+            .arg(seconds);
 
-        self.run_command(command).await?;
-        Ok(())
+        self.run_command(command).await.map(|_| ())
     }
 
-    ///Trim `list` from `start` to `stop`.
-    pub async fn ltrim<K>(&mut self, list: K, start: usize, stop: usize) -> Result<()>
+    ///Set the key `key` to `data`, and set it to expire after `milliseconds` ms.
+    pub async fn set_and_expire_ms<K, D>(
+        &mut self,
+        key: K,
+        data: D,
+        milliseconds: u32,
+    ) -> Result<()>
     where
         K: AsRef<[u8]>,
+        D: AsRef<[u8]>,
     {
-        let start = start.to_string();
-        let stop = stop.to_string();
-        let command = Command::new("LTRIM").arg(&list).arg(&start).arg(&stop);
-        self.run_command(command).await?;
+        let command = Command::new("SET")
+            .arg(&key)
+            .arg(&data)
+            .arg(b"PX")
+            .arg(milliseconds);
 
-        Ok(())
+        self.run_command(command).await.map(|_| ())
     }
 
-    ///Increment `key` by one.
+    ///Set `key` to `value` using `options`.
     ///# Return value
-    ///The new value of `key`.
-    pub async fn incr<K>(&mut self, key: K) -> Result<isize>
+    ///`true` if the key was set, `false` if it wasn't because of the
+    ///[`nx`](struct.SetOptions.html#method.nx) or [`xx`](struct.SetOptions.html#method.xx) condition.
+    ///# Example
+    ///```
+    ///use darkredis::{Connection, Expiry, SetOptions};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///let options = SetOptions::new().nx().expire(Expiry::Seconds(60));
+    ///assert!(connection.set_with_options("lock", "me", options).await.unwrap());
+    ///assert!(!connection.set_with_options("lock", "you", options).await.unwrap());
+    ///# connection.del("lock").await.unwrap();
+    ///# }
+    ///```
+    pub async fn set_with_options<K, D>(
+        &mut self,
+        key: K,
+        value: D,
+        options: SetOptions,
+    ) -> Result<bool>
     where
         K: AsRef<[u8]>,
+        D: AsRef<[u8]>,
     {
-        let command = Command::new("INCR").arg(&key);
-        Ok(self.run_command(command).await?.unwrap_integer())
+        let mut command = Command::new("SET").arg(&key).arg(&value);
+        options.build(&mut command);
+        Ok(self.query::<Option<()>>(command).await?.is_some())
     }
 
-    ///Increment `key` by `val`.
+    ///Like [`set_with_options`](struct.Connection.html#method.set_with_options), but get the old
+    ///value of `key` using the `GET` option.
     ///# Return value
-    ///The new value of `key`
-    pub async fn incrby<K>(&mut self, key: K, val: isize) -> Result<isize>
+    ///The old value of `key`, or `None` if it didn't exist. When using the
+    ///[`nx`](struct.SetOptions.html#method.nx) condition, the key was only set if this is `None`.
+    pub async fn set_and_get<K, D>(
+        &mut self,
+        key: K,
+        value: D,
+        options: SetOptions,
+    ) -> Result<Option<Vec<u8>>>
     where
         K: AsRef<[u8]>,
+        D: AsRef<[u8]>,
     {
-        let val = val.to_string();
-        let command = Command::new("INCRBY").arg(&key).arg(&val);
-        Ok(self.run_command(command).await?.unwrap_integer())
+        let mut command = Command::new("SET").arg(&key).arg(&value);
+        options.build(&mut command);
+        command.append_arg("GET");
+        self.query(command).await
     }
 
-    ///Increment `key` by a floating point value `val`.
+    ///Set `key` to `value` only if it doesn't already exist.
     ///# Return value
-    ///The new value of `key`
-    pub async fn incrbyfloat<K>(&mut self, key: K, val: f64) -> Result<f64>
+    ///`true` if the key was set.
+    pub async fn setnx<K, D>(&mut self, key: K, value: D) -> Result<bool>
     where
         K: AsRef<[u8]>,
+        D: AsRef<[u8]>,
     {
-        let val = val.to_string();
-        let command = Command::new("INCRBYFLOAT").arg(&key).arg(&val);
-        let result = self.run_command(command).await?.unwrap_string();
-        Ok(String::from_utf8_lossy(&result).parse::<f64>().unwrap())
+        let command = Command::new("SETNX").arg(&key).arg(&value);
+        self.query(command).await
     }
 
-    ///Decrement `key` by a floating point value `val`.
-    ///# Return value
-    ///The new value of `key`
-    pub async fn decr<K>(&mut self, key: K) -> Result<isize>
+    ///Set `key` to `value`, returning its old value.
+    pub async fn getset<K, D>(&mut self, key: K, value: D) -> Result<Option<Vec<u8>>>
     where
         K: AsRef<[u8]>,
+        D: AsRef<[u8]>,
     {
-        let command = Command::new("DECR").arg(&key);
-        Ok(self.run_command(command).await?.unwrap_integer())
+        let command = Command::new("GETSET").arg(&key).arg(&value);
+        self.query(command).await
     }
 
-    ///Decrement `key` by `val`.
-    ///# Return value
-    ///The new value of `key`
-    pub async fn decrby<K>(&mut self, key: K, val: isize) -> Result<isize>
+    ///Set `key` to expire `seconds` seconds from now.
+    pub async fn expire_seconds<K>(&mut self, key: K, seconds: u32) -> Result<isize>
     where
         K: AsRef<[u8]>,
     {
-        let val = val.to_string();
-        let command = Command::new("DECRBY").arg(&key).arg(&val);
-        Ok(self.run_command(command).await?.unwrap_integer())
+        let command = Command::new("EXPIRE").arg(&key).arg(seconds);
+
+        self.query(command).await
     }
 
-    ///Append a string `val` to `key`.
-    ///# Return value
-    ///The new size of `key`
-    pub async fn append<K, V>(&mut self, key: K, val: V) -> Result<isize>
+    ///Set `key` to expire `milliseconds` ms from now.
+    pub async fn expire_ms<K>(&mut self, key: K, seconds: u32) -> Result<isize>
     where
         K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
     {
-        let command = Command::new("APPEND").arg(&key).arg(&val);
-        Ok(self.run_command(command).await?.unwrap_integer())
+        let command = Command::new("PEXPIRE").arg(&key).arg(seconds);
+
+        self.query(command).await
     }
 
-    ///Get the string value for every `key`, or `None`` if it doesn't exist
-    pub async fn mget<K>(&mut self, keys: &[K]) -> Result<Vec<Option<Vec<u8>>>>
+    ///Set `key` to expire at Unix timestamp `timestamp`, measured in seconds.
+    pub async fn expire_at_seconds<K>(&mut self, key: K, timestamp: u64) -> Result<isize>
     where
         K: AsRef<[u8]>,
     {
-        let command = Command::new("MGET").args(&keys);
-        let result = self.run_command(command).await?.unwrap_array();
-        let output: Vec<Option<Vec<u8>>> =
-            result.into_iter().map(|r| r.optional_string()).collect();
-        Ok(output)
-    }
-
-    ///Set every key in `builder` to their respective values.
-    ///# Example
-    ///```
-    /// use darkredis::{Connection, MSetBuilder};
-    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
-    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
-    /// # async fn main() {
-    /// let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
-    /// let builder = MSetBuilder::new()
-    ///     .set(b"multi-key-1", b"foo")
-    ///     .set(b"multi-key-2", b"bar")
-    ///     .set(b"multi-key-3", b"baz");
-    /// connection.mset(builder).await.unwrap();
-    /// let keys = &[b"multi-key-1", b"multi-key-2", b"multi-key-3"];
-    /// let results = connection.mget(keys).await.unwrap();
-    /// assert_eq!(
-    ///     results,
-    ///     vec![
-    ///         Some(b"foo".to_vec()),
-    ///         Some(b"bar".to_vec()),
-    ///         Some(b"baz".to_vec())
-    ///     ]
-    /// );
-    /// # connection.del_slice(keys).await.unwrap();
-    /// # }
-    ///```
+        let command = Command::new("EXPIREAT").arg(&key).arg(timestamp);
 
-    pub async fn mset(&mut self, builder: MSetBuilder<'_>) -> Result<()>
-where {
-        let mut command = Command::new("MSET");
-        command.append_msetbuilder(&builder);
-        self.run_command(command).await?;
-        Ok(())
+        self.query(command).await
     }
 
-    ///Returns true if a key has been previously set.
-    pub async fn exists<K>(&mut self, key: K) -> Result<bool>
+    ///Set `key` to expire at Unix timestamp `timestamp`, measured in milliseconds.
+    pub async fn expire_at_ms<K>(&mut self, key: K, timestamp: u64) -> Result<isize>
     where
         K: AsRef<[u8]>,
     {
-        let command = Command::new("EXISTS").arg(&key);
-        Ok(self.run_command(command).await? == Value::Integer(1))
+        let command = Command::new("PEXPIREAT").arg(&key).arg(timestamp);
+
+        self.query(command).await
     }
 
-    ///Adds new `value` to set specified by `key`.
-    pub async fn sadd<K, V>(&mut self, key: K, value: V) -> Result<bool>
+    ///Get the remaining time to live of `key` in seconds.
+    pub async fn ttl<K>(&mut self, key: K) -> Result<Ttl>
     where
         K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
     {
-        let command = Command::new("SADD").arg(&key).arg(&value);
+        let command = Command::new("TTL").arg(&key);
 
-        Ok(self.run_command(command).await?.unwrap_bool())
+        self.query(command).await
     }
 
-    ///Like [`sadd`](struct.Connection.html#method.sadd), but push multiple values.
-    pub async fn sadd_slice<K, V>(&mut self, key: K, values: &[V]) -> Result<isize>
+    ///Get the remaining time to live of `key` in milliseconds.
+    pub async fn pttl<K>(&mut self, key: K) -> Result<Ttl>
     where
         K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
     {
-        let command = Command::new("SADD").arg(&key).args(&values);
+        let command = Command::new("PTTL").arg(&key);
 
-        Ok(self.run_command(command).await?.unwrap_integer())
+        self.query(command).await
     }
 
-    /// Return the members of a set specified by `key`.
-    pub async fn smembers<K>(&mut self, key: K) -> Result<Vec<Vec<u8>>>
+    ///Get the Unix timestamp at which `key` expires, measured in seconds.
+    pub async fn expiretime<K>(&mut self, key: K) -> Result<Ttl>
     where
         K: AsRef<[u8]>,
     {
-        let command = Command::new("SMEMBERS").arg(&key);
+        let command = Command::new("EXPIRETIME").arg(&key);
 
-        Ok(self
-            .run_command(command)
-            .await?
-            .unwrap_array()
-            .into_iter()
-            .map(|s| s.unwrap_string())
-            .collect())
+        self.query(command).await
     }
 
-    /// Returns `true` if `value` belongs to a set specified by `key`.
-    pub async fn sismember<K, V>(&mut self, key: K, value: V) -> Result<bool>
+    ///Get the Unix timestamp at which `key` expires, measured in milliseconds.
+    pub async fn pexpiretime<K>(&mut self, key: K) -> Result<Ttl>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("PEXPIRETIME").arg(&key);
+
+        self.query(command).await
+    }
+
+    ///Remove the expiry of `key`.
+    ///# Return value
+    ///`true` if the expiry was removed, `false` if `key` doesn't exist or has no expiry.
+    pub async fn persist<K>(&mut self, key: K) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("PERSIST").arg(&key);
+
+        self.query(command).await
+    }
+
+    ///Delete `key`.
+    ///# Return value
+    ///The number of deleted keys.
+    pub async fn del<K>(&mut self, key: K) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("DEL").arg(&key);
+        self.query(command).await
+    }
+
+    ///Delete every element in `keys`.
+    ///# Return value
+    ///The number of deleted keys.
+    pub async fn del_slice<K>(&mut self, keys: &[K]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(keys);
+        let command = Command::new("DEL").args(keys);
+        self.query(command).await
+    }
+
+    ///Like [`del`](struct.Connection.html#method.del), but reclaim the memory used by `key` in
+    ///the background.
+    pub async fn unlink<K>(&mut self, key: K) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("UNLINK").arg(&key);
+        self.query(command).await
+    }
+
+    ///Like [`del_slice`](struct.Connection.html#method.del_slice), but reclaim the memory used by
+    ///the keys in the background.
+    pub async fn unlink_slice<K>(&mut self, keys: &[K]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(keys);
+        let command = Command::new("UNLINK").args(keys);
+        self.query(command).await
+    }
+
+    ///Rename `key` to `new_key`, overwriting `new_key` if it exists. Returns an error if `key`
+    ///doesn't exist.
+    pub async fn rename<K, N>(&mut self, key: K, new_key: N) -> Result<()>
+    where
+        K: AsRef<[u8]>,
+        N: AsRef<[u8]>,
+    {
+        let command = Command::new("RENAME").arg(&key).arg(&new_key);
+        self.query(command).await
+    }
+
+    ///Rename `key` to `new_key` only if `new_key` doesn't exist. Returns an error if `key`
+    ///doesn't exist.
+    ///# Return value
+    ///`true` if the key was renamed.
+    pub async fn renamenx<K, N>(&mut self, key: K, new_key: N) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+        N: AsRef<[u8]>,
+    {
+        let command = Command::new("RENAMENX").arg(&key).arg(&new_key);
+        self.query(command).await
+    }
+
+    ///Copy the value of `source` to `destination`, overwriting `destination` only if `replace` is true.
+    ///# Return value
+    ///`true` if the value was copied.
+    pub async fn copy<S, D>(&mut self, source: S, destination: D, replace: bool) -> Result<bool>
+    where
+        S: AsRef<[u8]>,
+        D: AsRef<[u8]>,
+    {
+        let mut command = Command::new("COPY").arg(&source).arg(&destination);
+        if replace {
+            command.append_arg("REPLACE");
+        }
+        self.query(command).await
+    }
+
+    ///Update the last access time of `key`.
+    ///# Return value
+    ///`true` if `key` exists.
+    pub async fn touch<K>(&mut self, key: K) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("TOUCH").arg(&key);
+        self.query(command).await
+    }
+
+    ///Like [`touch`](struct.Connection.html#method.touch), but touch every key in `keys`.
+    ///# Return value
+    ///The number of keys which exist.
+    pub async fn touch_slice<K>(&mut self, keys: &[K]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(keys);
+        let command = Command::new("TOUCH").args(keys);
+        self.query(command).await
+    }
+
+    ///Get the value of `key`.
+    pub async fn get<K>(&mut self, key: K) -> Result<Option<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("GET").arg(&key);
+
+        self.query(command).await
+    }
+
+    ///Get the value of `key` and delete it.
+    pub async fn getdel<K>(&mut self, key: K) -> Result<Option<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("GETDEL").arg(&key);
+        self.query(command).await
+    }
+
+    ///Get the value of `key`, changing its expiry according to `option`. The expiry is left as it is
+    ///if `option` is `None`.
+    pub async fn getex<K>(&mut self, key: K, option: Option<GetExOption>) -> Result<Option<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("GETEX").arg(&key);
+        if let Some(option) = option {
+            option.build(&mut command);
+        }
+        self.query(command).await
+    }
+
+    ///Push a value to `list` from the left.
+    ///# Return value
+    ///The number of elements in `list`
+    pub async fn lpush<K, V>(&mut self, list: K, value: V) -> Result<isize>
     where
         K: AsRef<[u8]>,
         V: AsRef<[u8]>,
     {
-        let command = Command::new("SISMEMBER").arg(&key).arg(&value);
+        let command = Command::new("LPUSH").arg(&list).arg(&value);
 
-        Ok(self.run_command(command).await?.unwrap_bool())
+        self.query(command).await
     }
 
-    ///Scan for elements in a set.
+    ///Like [`lpush`](struct.Connection.html#method.lpush), but push multiple values.
+    pub async fn lpush_slice<K, V>(&mut self, key: K, values: &[V]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(values);
+        let command = Command::new("LPUSH").arg(&key).args(values);
+
+        self.query(command).await
+    }
+
+    ///Push a value to `list` from the right.
     ///# Return value
-    ///Returns a list of the elements which matched in the set.
+    ///The number of elements in `list`
+    pub async fn rpush<K, V>(&mut self, list: K, value: V) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let command = Command::new("RPUSH").arg(&list).arg(&value);
+
+        self.query(command).await
+    }
+
+    ///Like [`rpush`](struct.Connection.html#method.rpush), but push multiple values through a slice.
+    pub async fn rpush_slice<K, V>(&mut self, key: K, values: &[V]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(values);
+        let command = Command::new("RPUSH").arg(&key).args(values);
+
+        self.query(command).await
+    }
+
+    ///Like [`lpush`](struct.Connection.html#method.lpush), but only push if `list` already exists.
+    ///# Return value
+    ///The number of elements in `list`, or 0 if it doesn't exist.
+    pub async fn lpushx<K, V>(&mut self, list: K, value: V) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let command = Command::new("LPUSHX").arg(&list).arg(&value);
+        self.query(command).await
+    }
+
+    ///Like [`rpush`](struct.Connection.html#method.rpush), but only push if `list` already exists.
+    ///# Return value
+    ///The number of elements in `list`, or 0 if it doesn't exist.
+    pub async fn rpushx<K, V>(&mut self, list: K, value: V) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let command = Command::new("RPUSHX").arg(&list).arg(&value);
+        self.query(command).await
+    }
+
+    ///Pop a value from a list from the left side.
+    ///# Return value
+    ///The value popped from `list`
+    pub async fn lpop<K>(&mut self, list: K) -> Result<Option<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("LPOP").arg(&list);
+
+        self.query(command).await
+    }
+
+    ///Pop a value from a list from the right side.
+    ///# Return value
+    ///The value popped from `list`
+    pub async fn rpop<K>(&mut self, list: K) -> Result<Option<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("RPOP").arg(&list);
+
+        self.query(command).await
+    }
+
+    ///Like [`lpop`](struct.Connection.html#method.lpop), but pop up to `count` values.
+    ///# Return value
+    ///The values popped from `list`, which is empty if it doesn't exist.
+    pub async fn lpop_count<K>(&mut self, list: K, count: usize) -> Result<Vec<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("LPOP").arg(&list).arg(count);
+        self.query(command).await
+    }
+
+    ///Like [`rpop`](struct.Connection.html#method.rpop), but pop up to `count` values.
+    ///# Return value
+    ///The values popped from `list`, which is empty if it doesn't exist.
+    pub async fn rpop_count<K>(&mut self, list: K, count: usize) -> Result<Vec<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("RPOP").arg(&list).arg(count);
+        self.query(command).await
+    }
+
+    ///Pop a value from one of the lists from the left side.
+    ///Block timeout seconds when there are no values to pop. A zero-value with block infinitely.
+    ///# Return value
+    ///* `Ok(Some((list,value)))`: name of the list and corresponding value
+    ///* `Ok(None)`: timeout (no values)
+    ///* `Err(err)`: there was an error
+    pub async fn blpop<K>(
+        &mut self,
+        lists: &[K],
+        timeout: u32,
+    ) -> Result<Option<(Vec<u8>, Vec<u8>)>>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.blpop_brpop(lists, timeout, "BLPOP").await
+    }
+
+    ///Pop a value from one of the lists from the right side.
+    ///Block timeout seconds when there are no values to pop. A zero-value will block infinitely.
+    ///# Return value
+    ///* `Ok(Some((list,value)))`: name of the list and corresponding value
+    ///* `Ok(None)`: timeout (no values)
+    ///* `Err(err)`: there was an error
+    pub async fn brpop<K>(
+        &mut self,
+        lists: &[K],
+        timeout: u32,
+    ) -> Result<Option<(Vec<u8>, Vec<u8>)>>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.blpop_brpop(lists, timeout, "BRPOP").await
+    }
+
+    ///blpop and brpop common code
+    async fn blpop_brpop<K>(
+        &mut self,
+        lists: &[K],
+        timeout: u32,
+        redis_cmd: &str,
+    ) -> Result<Option<(Vec<u8>, Vec<u8>)>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new(redis_cmd).args(lists).arg(timeout);
+        self.query(command).await
+    }
+
+    ///Atomically pop a value from the `from` side of `source` and push it to the `to` side of
+    ///`destination`. `source` and `destination` may be the same list, rotating it.
+    ///# Return value
+    ///The moved value, or `None` if `source` is empty.
+    ///# Example
+    ///```
+    ///use darkredis::{Connection, ListSide};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    /////A reliable queue: jobs stay in the processing list until they are done
+    ///connection.lpush("lmove-queue", "job").await.unwrap();
+    ///let job = connection
+    ///    .lmove("lmove-queue", "lmove-processing", ListSide::Right, ListSide::Left)
+    ///    .await
+    ///    .unwrap();
+    ///assert_eq!(job, Some(b"job".to_vec()));
+    ///
+    /////Process the job, then remove it from the processing list
+    ///connection.lrem("lmove-processing", 1, "job").await.unwrap();
+    ///# }
+    ///```
+    pub async fn lmove<S, D>(
+        &mut self,
+        source: S,
+        destination: D,
+        from: ListSide,
+        to: ListSide,
+    ) -> Result<Option<Vec<u8>>>
+    where
+        S: AsRef<[u8]>,
+        D: AsRef<[u8]>,
+    {
+        let command = Command::new("LMOVE")
+            .arg(&source)
+            .arg(&destination)
+            .arg(from.as_arg())
+            .arg(to.as_arg());
+        self.query(command).await
+    }
+
+    ///Like [`lmove`](struct.Connection.html#method.lmove), but block for up to `timeout` seconds
+    ///if `source` is empty. A zero-value will block infinitely.
+    ///# Return value
+    ///The moved value, or `None` if the timeout was reached.
+    pub async fn blmove<S, D>(
+        &mut self,
+        source: S,
+        destination: D,
+        from: ListSide,
+        to: ListSide,
+        timeout: u32,
+    ) -> Result<Option<Vec<u8>>>
+    where
+        S: AsRef<[u8]>,
+        D: AsRef<[u8]>,
+    {
+        let command = Command::new("BLMOVE")
+            .arg(&source)
+            .arg(&destination)
+            .arg(from.as_arg())
+            .arg(to.as_arg())
+            .arg(timeout);
+        self.query(command).await
+    }
+
+    ///Atomically pop a value from the right side of `source` and push it to the left side of
+    ///`destination`. Deprecated in Redis 6.2 in favour of [`lmove`](struct.Connection.html#method.lmove).
+    ///# Return value
+    ///The moved value, or `None` if `source` is empty.
+    pub async fn rpoplpush<S, D>(&mut self, source: S, destination: D) -> Result<Option<Vec<u8>>>
+    where
+        S: AsRef<[u8]>,
+        D: AsRef<[u8]>,
+    {
+        let command = Command::new("RPOPLPUSH").arg(&source).arg(&destination);
+        self.query(command).await
+    }
+
+    ///Like [`rpoplpush`](struct.Connection.html#method.rpoplpush), but block for up to `timeout`
+    ///seconds if `source` is empty. A zero-value will block infinitely.
+    ///# Return value
+    ///The moved value, or `None` if the timeout was reached.
+    pub async fn brpoplpush<S, D>(
+        &mut self,
+        source: S,
+        destination: D,
+        timeout: u32,
+    ) -> Result<Option<Vec<u8>>>
+    where
+        S: AsRef<[u8]>,
+        D: AsRef<[u8]>,
+    {
+        let command = Command::new("BRPOPLPUSH")
+            .arg(&source)
+            .arg(&destination)
+            .arg(timeout);
+        self.query(command).await
+    }
+
+    ///Pop up to `count` values from the `side` of the first non-empty list in `lists`, or one
+    ///value if `count` is `None`.
+    ///# Return value
+    ///* `Ok(Some((list, values)))`: name of the list and the values popped from it
+    ///* `Ok(None)`: every list is empty
+    pub async fn lmpop<K>(
+        &mut self,
+        lists: &[K],
+        side: ListSide,
+        count: Option<usize>,
+    ) -> Result<Option<(Vec<u8>, Vec<Vec<u8>>)>>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(lists);
+        let command = Command::new("LMPOP");
+        self.lmpop_blmpop(command, lists, side, count).await
+    }
+
+    ///Like [`lmpop`](struct.Connection.html#method.lmpop), but block for up to `timeout` seconds
+    ///if every list is empty. A zero-value will block infinitely.
+    ///# Return value
+    ///* `Ok(Some((list, values)))`: name of the list and the values popped from it
+    ///* `Ok(None)`: timeout (no values)
+    pub async fn blmpop<K>(
+        &mut self,
+        lists: &[K],
+        side: ListSide,
+        count: Option<usize>,
+        timeout: u32,
+    ) -> Result<Option<(Vec<u8>, Vec<Vec<u8>>)>>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(lists);
+        let command = Command::new("BLMPOP").arg(timeout);
+        self.lmpop_blmpop(command, lists, side, count).await
+    }
+
+    ///lmpop and blmpop common code
+    async fn lmpop_blmpop<K>(
+        &mut self,
+        command: Command<'_>,
+        lists: &[K],
+        side: ListSide,
+        count: Option<usize>,
+    ) -> Result<Option<(Vec<u8>, Vec<Vec<u8>>)>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = command.arg(lists.len()).args(lists).arg(side.as_arg());
+        if let Some(count) = count {
+            command.append_arg("COUNT");
+            command.append_arg(count);
+        }
+        self.query(command).await
+    }
+
+    ///Get a series of elements from `list`, from index `from` to `to`. If they are negative, take the
+    ///index from the right side of the list.
+    pub async fn lrange<K>(&mut self, list: K, from: isize, to: isize) -> Result<Vec<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("LRANGE").arg(&list).arg(from).arg(to);
+
+        self.query(command).await
+    }
+
+    ///Get the element at `index` in `list`. If it is negative, take the index from the right side
+    ///of the list.
+    ///# Return value
+    ///The element, or `None` if `index` is out of range.
+    pub async fn lindex<K>(&mut self, list: K, index: isize) -> Result<Option<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("LINDEX").arg(&list).arg(index);
+        self.query(command).await
+    }
+
+    ///Insert `element` before or after the first occurrence of `pivot` in `list`.
+    ///# Return value
+    ///The number of elements in `list` after the insert, 0 if `list` doesn't exist or -1 if
+    ///`pivot` wasn't found.
+    pub async fn linsert<K, P, E>(
+        &mut self,
+        list: K,
+        position: InsertPosition,
+        pivot: P,
+        element: E,
+    ) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        P: AsRef<[u8]>,
+        E: AsRef<[u8]>,
+    {
+        let command = Command::new("LINSERT")
+            .arg(&list)
+            .arg(position.as_arg())
+            .arg(&pivot)
+            .arg(&element);
+        self.query(command).await
+    }
+
+    ///Remove the first `count` occurrences of `element` from `list`. If `count` is negative,
+    ///remove the last occurrences instead, and if it is zero remove every occurrence.
+    ///# Return value
+    ///The number of removed elements.
+    pub async fn lrem<K, E>(&mut self, list: K, count: isize, element: E) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        E: AsRef<[u8]>,
+    {
+        let command = Command::new("LREM").arg(&list).arg(count).arg(&element);
+        self.query(command).await
+    }
+
+    ///Find the index of `element` in `list`. If `rank` is given, find the `rank`th occurrence
+    ///instead of the first one, searching from the right side of the list if it is negative. If
+    ///`max_len` is given, only search that many elements.
+    ///# Return value
+    ///The index of the element, or `None` if it wasn't found.
+    pub async fn lpos<K, E>(
+        &mut self,
+        list: K,
+        element: E,
+        rank: Option<isize>,
+        max_len: Option<usize>,
+    ) -> Result<Option<isize>>
+    where
+        K: AsRef<[u8]>,
+        E: AsRef<[u8]>,
+    {
+        let command = Self::lpos_command(&list, &element, rank, max_len);
+        self.query(command).await
+    }
+
+    ///Like [`lpos`](struct.Connection.html#method.lpos), but find the indexes of up to `count`
+    ///occurrences, or every occurrence if `count` is zero.
+    pub async fn lpos_count<K, E>(
+        &mut self,
+        list: K,
+        element: E,
+        count: usize,
+        rank: Option<isize>,
+        max_len: Option<usize>,
+    ) -> Result<Vec<isize>>
+    where
+        K: AsRef<[u8]>,
+        E: AsRef<[u8]>,
+    {
+        let mut command = Self::lpos_command(&list, &element, rank, max_len);
+        command.append_arg("COUNT");
+        command.append_arg(count);
+        self.query(command).await
+    }
+
+    ///lpos and lpos_count common code
+    fn lpos_command<'a, K, E>(
+        list: &'a K,
+        element: &'a E,
+        rank: Option<isize>,
+        max_len: Option<usize>,
+    ) -> Command<'a>
+    where
+        K: AsRef<[u8]>,
+        E: AsRef<[u8]>,
+    {
+        let mut command = Command::new("LPOS").arg(list).arg(element);
+        if let Some(rank) = rank {
+            command.append_arg("RANK");
+            command.append_arg(rank);
+        }
+        if let Some(max_len) = max_len {
+            command.append_arg("MAXLEN");
+            command.append_arg(max_len);
+        }
+        command
+    }
+
+    ///Get the number of elements in `list`, or `None` if the list doesn't exist.
+    pub async fn llen<K>(&mut self, list: K) -> Result<Option<isize>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("LLEN").arg(&list);
+        self.query(command).await
+    }
+
+    ///Set the value of the element at `index` in `list` to `value`.
+    pub async fn lset<K, V>(&mut self, list: K, index: usize, value: V) -> Result<()>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let command = Command::new("LSET").arg(&list).arg(index).arg(&value);
+
+        self.run_command(command).await?;
+        Ok(())
+    }
+
+    ///Trim `list` from `start` to `stop`.
+    pub async fn ltrim<K>(&mut self, list: K, start: usize, stop: usize) -> Result<()>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("LTRIM").arg(&list).arg(start).arg(stop);
+        self.run_command(command).await?;
+
+        Ok(())
+    }
+
+    ///Increment `key` by one.
+    ///# Return value
+    ///The new value of `key`.
+    pub async fn incr<K>(&mut self, key: K) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("INCR").arg(&key);
+        self.query(command).await
+    }
+
+    ///Increment `key` by `val`.
+    ///# Return value
+    ///The new value of `key`
+    pub async fn incrby<K>(&mut self, key: K, val: isize) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("INCRBY").arg(&key).arg(val);
+        self.query(command).await
+    }
+
+    ///Increment `key` by a floating point value `val`.
+    ///# Return value
+    ///The new value of `key`
+    pub async fn incrbyfloat<K>(&mut self, key: K, val: f64) -> Result<f64>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("INCRBYFLOAT").arg(&key).arg(val);
+        self.query(command).await
+    }
+
+    ///Decrement `key` by a floating point value `val`.
+    ///# Return value
+    ///The new value of `key`
+    pub async fn decr<K>(&mut self, key: K) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("DECR").arg(&key);
+        self.query(command).await
+    }
+
+    ///Decrement `key` by `val`.
+    ///# Return value
+    ///The new value of `key`
+    pub async fn decrby<K>(&mut self, key: K, val: isize) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("DECRBY").arg(&key).arg(val);
+        self.query(command).await
+    }
+
+    ///Decrement `key` by a floating point value `val`, using `INCRBYFLOAT` with `-val`.
+    ///# Return value
+    ///The new value of `key`
+    pub async fn decrbyfloat<K>(&mut self, key: K, val: f64) -> Result<f64>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.incrbyfloat(key, -val).await
+    }
+
+    ///Append a string `val` to `key`.
+    ///# Return value
+    ///The new size of `key`
+    pub async fn append<K, V>(&mut self, key: K, val: V) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let command = Command::new("APPEND").arg(&key).arg(&val);
+        self.query(command).await
+    }
+
+    ///Overwrite part of the string `key` with `val`, starting at `offset`. The string is padded
+    ///with zero bytes if it's shorter than `offset`.
+    ///# Return value
+    ///The new size of `key`
+    pub async fn setrange<K, V>(&mut self, key: K, offset: usize, val: V) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let command = Command::new("SETRANGE").arg(&key).arg(offset).arg(&val);
+        self.query(command).await
+    }
+
+    ///Get the part of the string `key` from `start` to `end`, both inclusive. Negative indexes
+    ///count from the end of the string.
+    pub async fn getrange<K>(&mut self, key: K, start: isize, end: isize) -> Result<Vec<u8>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("GETRANGE").arg(&key).arg(start).arg(end);
+        self.query(command).await
+    }
+
+    ///Get the length of the string `key`, or 0 if it doesn't exist.
+    pub async fn strlen<K>(&mut self, key: K) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("STRLEN").arg(&key);
+        self.query(command).await
+    }
+
+    ///Get the longest common subsequence of the strings `key1` and `key2`.
+    pub async fn lcs<K1, K2>(&mut self, key1: K1, key2: K2) -> Result<Vec<u8>>
+    where
+        K1: AsRef<[u8]>,
+        K2: AsRef<[u8]>,
+    {
+        let command = Command::new("LCS").arg(&key1).arg(&key2);
+        self.query(command).await
+    }
+
+    ///Like [`lcs`](struct.Connection.html#method.lcs), but only get the length of the longest
+    ///common subsequence.
+    pub async fn lcs_len<K1, K2>(&mut self, key1: K1, key2: K2) -> Result<isize>
+    where
+        K1: AsRef<[u8]>,
+        K2: AsRef<[u8]>,
+    {
+        let command = Command::new("LCS").arg(&key1).arg(&key2).arg("LEN");
+        self.query(command).await
+    }
+
+    ///Get the string value for every `key`, or `None`` if it doesn't exist
+    pub async fn mget<K>(&mut self, keys: &[K]) -> Result<Vec<Option<Vec<u8>>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("MGET").args(keys);
+        self.query(command).await
+    }
+
+    ///Set every key in `builder` to their respective values.
+    ///# Example
+    ///```
+    /// use darkredis::{Connection, MSetBuilder};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    /// # async fn main() {
+    /// let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    /// let builder = MSetBuilder::new()
+    ///     .set(b"multi-key-1", b"foo")
+    ///     .set(b"multi-key-2", b"bar")
+    ///     .set(b"multi-key-3", b"baz");
+    /// connection.mset(builder).await.unwrap();
+    /// let keys = &[b"multi-key-1", b"multi-key-2", b"multi-key-3"];
+    /// let results = connection.mget(keys).await.unwrap();
+    /// assert_eq!(
+    ///     results,
+    ///     vec![
+    ///         Some(b"foo".to_vec()),
+    ///         Some(b"bar".to_vec()),
+    ///         Some(b"baz".to_vec())
+    ///     ]
+    /// );
+    /// # connection.del_slice(keys).await.unwrap();
+    /// # }
+    ///```
+    pub async fn mset(&mut self, builder: MSetBuilder<'_>) -> Result<()> {
+        let mut command = Command::new("MSET");
+        command.append_msetbuilder(&builder);
+        self.run_command(command).await?;
+        Ok(())
+    }
+
+    ///Like [`mset`](struct.Connection.html#method.mset), but only set the keys if none of them
+    ///already exist.
+    ///# Return value
+    ///`true` if the keys were set.
+    pub async fn msetnx(&mut self, builder: MSetBuilder<'_>) -> Result<bool> {
+        let mut command = Command::new("MSETNX");
+        command.append_msetbuilder(&builder);
+        self.query(command).await
+    }
+
+    ///Returns true if a key has been previously set.
+    pub async fn exists<K>(&mut self, key: K) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("EXISTS").arg(&key);
+        Ok(self.run_command(command).await? == Value::Integer(1))
+    }
+
+    ///Count how many of `keys` exist. Keys mentioned multiple times are counted multiple times.
+    pub async fn exists_slice<K>(&mut self, keys: &[K]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(keys);
+        let command = Command::new("EXISTS").args(keys);
+        self.query(command).await
+    }
+
+    ///Adds new `value` to set specified by `key`.
+    pub async fn sadd<K, V>(&mut self, key: K, value: V) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let command = Command::new("SADD").arg(&key).arg(&value);
+
+        self.query(command).await
+    }
+
+    ///Like [`sadd`](struct.Connection.html#method.sadd), but push multiple values.
+    pub async fn sadd_slice<K, V>(&mut self, key: K, values: &[V]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let command = Command::new("SADD").arg(&key).args(values);
+
+        self.query(command).await
+    }
+
+    /// Return the members of a set specified by `key`.
+    pub async fn smembers<K>(&mut self, key: K) -> Result<Vec<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("SMEMBERS").arg(&key);
+
+        self.query(command).await
+    }
+
+    /// Returns `true` if `value` belongs to a set specified by `key`.
+    pub async fn sismember<K, V>(&mut self, key: K, value: V) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let command = Command::new("SISMEMBER").arg(&key).arg(&value);
+
+        self.query(command).await
+    }
+
+    ///Append an entry with the fields and values in `fields` to the stream at `key`, creating the
+    ///stream if it doesn't exist. If `id` is `None`, Redis generates an ID from the current time.
+    ///The stream is trimmed afterwards according to `trim` if given.
+    ///# Return value
+    ///The ID of the added entry.
+    ///# Example
+    ///```
+    ///use darkredis::{Connection, MSetBuilder, StreamTrim};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///# connection.del("xadd-example").await.unwrap();
+    ///let fields = MSetBuilder::new().set(b"sensor", b"1").set(b"temperature", b"19.8");
+    ///let trim = StreamTrim::max_len(1000).approximate();
+    ///let id = connection.xadd("xadd-example", None, fields, Some(trim)).await.unwrap();
+    ///
+    ///let entries = connection.xrange("xadd-example", "-", "+", None).await.unwrap();
+    ///assert_eq!(entries[0].id, id);
+    ///assert_eq!(entries[0].get("temperature"), Some(&b"19.8"[..]));
+    ///# connection.del("xadd-example").await.unwrap();
+    ///# }
+    ///```
+    pub async fn xadd<K>(
+        &mut self,
+        key: K,
+        id: Option<StreamId>,
+        fields: MSetBuilder<'_>,
+        trim: Option<StreamTrim>,
+    ) -> Result<StreamId>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("XADD").arg(&key);
+        if let Some(trim) = trim {
+            trim.build(&mut command);
+        }
+        match id {
+            Some(id) => command.append_arg(id),
+            None => command.append_arg("*"),
+        }
+        command.append_args(fields.build());
+
+        self.query(command).await
+    }
+
+    ///Get the entries of the stream at `key` with IDs between `start` and `end`, returning at most
+    ///`count` entries if given. The bounds are inclusive unless prefixed with `(`, and can be `-`
+    ///and `+` for the lowest and highest possible IDs.
+    pub async fn xrange<'a, K, S, E>(
+        &mut self,
+        key: K,
+        start: S,
+        end: E,
+        count: Option<usize>,
+    ) -> Result<Vec<StreamEntry>>
+    where
+        K: AsRef<[u8]>,
+        S: ToArg<'a>,
+        E: ToArg<'a>,
+    {
+        self.xrange_xrevrange("XRANGE", key, start.to_arg(), end.to_arg(), count)
+            .await
+    }
+
+    ///Like [`xrange`](struct.Connection.html#method.xrange), but return the entries in reverse
+    ///order, starting with `end`.
+    pub async fn xrevrange<'a, K, E, S>(
+        &mut self,
+        key: K,
+        end: E,
+        start: S,
+        count: Option<usize>,
+    ) -> Result<Vec<StreamEntry>>
+    where
+        K: AsRef<[u8]>,
+        E: ToArg<'a>,
+        S: ToArg<'a>,
+    {
+        self.xrange_xrevrange("XREVRANGE", key, end.to_arg(), start.to_arg(), count)
+            .await
+    }
+
+    ///xrange and xrevrange common code
+    async fn xrange_xrevrange<K>(
+        &mut self,
+        redis_cmd: &str,
+        key: K,
+        first: Cow<'_, [u8]>,
+        second: Cow<'_, [u8]>,
+        count: Option<usize>,
+    ) -> Result<Vec<StreamEntry>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new(redis_cmd).arg(&key).arg(&first).arg(&second);
+        if let Some(count) = count {
+            command.append_arg("COUNT");
+            command.append_arg(count);
+        }
+
+        self.query(command).await
+    }
+
+    ///Get the number of entries in the stream at `key`.
+    pub async fn xlen<K>(&mut self, key: K) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("XLEN").arg(&key);
+        self.query(command).await
+    }
+
+    ///Delete the entries with the IDs in `ids` from the stream at `key`.
+    ///# Return value
+    ///The number of deleted entries.
+    pub async fn xdel<K>(&mut self, key: K, ids: &[StreamId]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(ids);
+        let command = Command::new("XDEL").arg(&key).args(ids.iter().copied());
+        self.query(command).await
+    }
+
+    ///Trim the stream at `key` according to `trim`.
+    ///# Return value
+    ///The number of deleted entries.
+    pub async fn xtrim<K>(&mut self, key: K, trim: StreamTrim) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("XTRIM").arg(&key);
+        trim.build(&mut command);
+        self.query(command).await
+    }
+
+    ///Read the entries with an ID higher than the given ID from each stream in `streams`, returning at
+    ///most `count` entries per stream if given. If `block_ms` is given and none of the streams have
+    ///new entries, block for that many milliseconds waiting for one, where zero blocks forever.
+    ///# Return value
+    ///The key of every stream with new entries, together with the entries. Empty if the command timed out.
+    ///# See also
+    ///[`xread_stream`](struct.Connection.html#method.xread_stream)
+    pub async fn xread<K>(
+        &mut self,
+        streams: &[(K, StreamId)],
+        count: Option<usize>,
+        block_ms: Option<u32>,
+    ) -> Result<Vec<(Vec<u8>, Vec<StreamEntry>)>>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(streams);
+        let command = streams::xread_command(streams, count, block_ms);
+        self.query(command).await
+    }
+
+    ///Consume `self`, and continuously tail each stream in `streams` using blocking `XREAD`s,
+    ///returning a stream of new entries. Each stream is read starting after the given ID, or after
+    ///its current last entry if the ID is `None`. At most `count` entries per stream are requested
+    ///from Redis at a time if given.
+    ///# Example
+    ///```
+    ///use darkredis::{Connection, MSetBuilder, StreamId};
+    ///use futures::StreamExt;
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///# connection.del("xread-stream-example").await.unwrap();
+    ///let first = StreamId::new(1, 0);
+    ///let fields = MSetBuilder::new().set(b"field", b"value");
+    ///connection.xadd("xread-stream-example", Some(first), fields, None).await.unwrap();
+    ///
+    ///let tail = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///let mut entries = tail
+    ///    .xread_stream(&[("xread-stream-example", Some(StreamId::default()))], None)
+    ///    .await
+    ///    .unwrap();
+    ///let (key, entry) = entries.next().await.unwrap().unwrap();
+    ///assert_eq!(key, b"xread-stream-example");
+    ///assert_eq!(entry.id, first);
+    ///# connection.del("xread-stream-example").await.unwrap();
+    ///# }
+    ///```
+    pub async fn xread_stream<K>(
+        mut self,
+        streams: &[(K, Option<StreamId>)],
+        count: Option<usize>,
+    ) -> Result<XReadStream>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(streams);
+        let mut positions = Vec::with_capacity(streams.len());
+        for (key, id) in streams {
+            let id = match id {
+                Some(id) => *id,
+                //Look up the last entry instead of using `$`, so that no entries are missed between reads.
+                None => self
+                    .xrevrange(key, "+", "-", Some(1))
+                    .await?
+                    .pop()
+                    .map(|e| e.id)
+                    .unwrap_or_default(),
+            };
+            positions.push((key.as_ref().to_vec(), id));
+        }
+
+        Ok(XReadStream::new(self, positions, count, None))
+    }
+
+    ///Create the consumer group `group` for the stream at `key`, which starts reading after `id`, or
+    ///after the current last entry if `id` is `None`. If `mkstream` is true, an empty stream is
+    ///created if it doesn't exist, otherwise an error is returned.
+    pub async fn xgroup_create<K, G>(
+        &mut self,
+        key: K,
+        group: G,
+        id: Option<StreamId>,
+        mkstream: bool,
+    ) -> Result<()>
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+    {
+        let mut command = Command::new("XGROUP").arg("CREATE").arg(&key).arg(&group);
+        match id {
+            Some(id) => command.append_arg(id),
+            None => command.append_arg("$"),
+        }
+        if mkstream {
+            command.append_arg("MKSTREAM");
+        }
+
+        self.query(command).await
+    }
+
+    ///Destroy the consumer group `group` of the stream at `key`, including its pending entries.
+    ///# Return value
+    ///`true` if the group existed.
+    pub async fn xgroup_destroy<K, G>(&mut self, key: K, group: G) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+    {
+        let command = Command::new("XGROUP").arg("DESTROY").arg(&key).arg(&group);
+        self.query(command).await
+    }
+
+    ///Make the consumer group `group` continue reading after `id`, or after the current last entry
+    ///if `id` is `None`.
+    pub async fn xgroup_setid<K, G>(&mut self, key: K, group: G, id: Option<StreamId>) -> Result<()>
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+    {
+        let mut command = Command::new("XGROUP").arg("SETID").arg(&key).arg(&group);
+        match id {
+            Some(id) => command.append_arg(id),
+            None => command.append_arg("$"),
+        }
+
+        self.query(command).await
+    }
+
+    ///Create the consumer `consumer` in the consumer group `group`. Consumers are also created
+    ///automatically the first time they read from the group.
+    ///# Return value
+    ///`true` if the consumer was created, `false` if it already existed.
+    pub async fn xgroup_createconsumer<K, G, C>(
+        &mut self,
+        key: K,
+        group: G,
+        consumer: C,
+    ) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+        C: AsRef<[u8]>,
+    {
+        let command = Command::new("XGROUP")
+            .arg("CREATECONSUMER")
+            .arg(&key)
+            .arg(&group)
+            .arg(&consumer);
+        self.query(command).await
+    }
+
+    ///Delete the consumer `consumer` from the consumer group `group`, along with its pending entries.
+    ///# Return value
+    ///The number of pending entries the consumer had.
+    pub async fn xgroup_delconsumer<K, G, C>(
+        &mut self,
+        key: K,
+        group: G,
+        consumer: C,
+    ) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+        C: AsRef<[u8]>,
+    {
+        let command = Command::new("XGROUP")
+            .arg("DELCONSUMER")
+            .arg(&key)
+            .arg(&group)
+            .arg(&consumer);
+        self.query(command).await
+    }
+
+    ///Read entries from each stream in `streams` as `consumer` in the consumer group `group`. If the
+    ///ID of a stream is `None`, entries which were never delivered to any consumer are returned,
+    ///otherwise the entries pending on `consumer` with a higher ID are returned. `count` and
+    ///`block_ms` work like in [`xread`](struct.Connection.html#method.xread). If `noack` is true,
+    ///the entries don't have to be acknowledged.
+    ///# Return value
+    ///The key of every stream with entries, together with the entries. Empty if the command timed out.
+    pub async fn xreadgroup<G, C, K>(
+        &mut self,
+        group: G,
+        consumer: C,
+        streams: &[(K, Option<StreamId>)],
+        count: Option<usize>,
+        block_ms: Option<u32>,
+        noack: bool,
+    ) -> Result<Vec<(Vec<u8>, Vec<StreamEntry>)>>
+    where
+        G: AsRef<[u8]>,
+        C: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(streams);
+        let command = streams::xreadgroup_command(
+            group.as_ref(),
+            consumer.as_ref(),
+            streams,
+            count,
+            block_ms,
+            noack,
+        );
+        self.query(command).await
+    }
+
+    ///Consume `self`, and continuously read new entries from each stream in `keys` as `consumer` in
+    ///the consumer group `group` using blocking `XREADGROUP`s. See
+    ///[`xreadgroup`](struct.Connection.html#method.xreadgroup) for the meaning of `count` and `noack`.
+    ///# See also
+    ///[`group_consumer`](struct.Connection.html#method.group_consumer), which also recovers entries
+    ///left pending by crashed consumers.
+    pub async fn xreadgroup_stream<G, C, K>(
+        self,
+        group: G,
+        consumer: C,
+        keys: &[K],
+        count: Option<usize>,
+        noack: bool,
+    ) -> Result<XReadStream>
+    where
+        G: AsRef<[u8]>,
+        C: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(keys);
+        let streams = keys
+            .iter()
+            .map(|k| (k.as_ref().to_vec(), StreamId::default()))
+            .collect();
+        let group = GroupRead {
+            group: group.as_ref().to_vec(),
+            consumer: consumer.as_ref().to_vec(),
+            noack,
+        };
+
+        Ok(XReadStream::new(self, streams, count, Some(group)))
+    }
+
+    ///Consume `self`, and create a [`GroupConsumer`](struct.GroupConsumer.html) reading from the
+    ///stream at `key` as `consumer` in the consumer group `group`, which also recovers entries left
+    ///pending by crashed consumers.
+    pub fn group_consumer<K, G, C>(self, key: K, group: G, consumer: C) -> GroupConsumerBuilder
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+        C: AsRef<[u8]>,
+    {
+        GroupConsumerBuilder::new(self, key.as_ref(), group.as_ref(), consumer.as_ref())
+    }
+
+    ///Acknowledge the entries with the IDs in `ids` in the consumer group `group`, removing them
+    ///from its pending entries.
+    ///# Return value
+    ///The number of entries which were acknowledged.
+    pub async fn xack<K, G>(&mut self, key: K, group: G, ids: &[StreamId]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(ids);
+        let command = Command::new("XACK")
+            .arg(&key)
+            .arg(&group)
+            .args(ids.iter().copied());
+        self.query(command).await
+    }
+
+    ///Get a summary of the pending entries of the consumer group `group`.
+    pub async fn xpending<K, G>(&mut self, key: K, group: G) -> Result<PendingSummary>
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+    {
+        let command = Command::new("XPENDING").arg(&key).arg(&group);
+        self.query(command).await
+    }
+
+    ///Get the pending entries of the consumer group `group` described by `range`.
+    pub async fn xpending_range<K, G>(
+        &mut self,
+        key: K,
+        group: G,
+        range: XPendingBuilder<'_>,
+    ) -> Result<Vec<PendingEntry>>
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+    {
+        let mut command = Command::new("XPENDING").arg(&key).arg(&group);
+        range.build(&mut command);
+        self.query(command).await
+    }
+
+    ///Transfer the entries with the IDs in `ids` which have been pending for at least
+    ///`min_idle_ms` milliseconds to `consumer`.
+    ///# Return value
+    ///The entries which were claimed.
+    pub async fn xclaim<K, G, C>(
+        &mut self,
+        key: K,
+        group: G,
+        consumer: C,
+        min_idle_ms: u64,
+        ids: &[StreamId],
+    ) -> Result<Vec<StreamEntry>>
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+        C: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(ids);
+        let command = Command::new("XCLAIM")
+            .arg(&key)
+            .arg(&group)
+            .arg(&consumer)
+            .arg(min_idle_ms)
+            .args(ids.iter().copied());
+        //Entries which were deleted from the stream are nil before Redis 7.
+        let entries: Vec<Option<StreamEntry>> = self.query(command).await?;
+        Ok(entries.into_iter().flatten().collect())
+    }
+
+    ///Transfer up to `count` entries which have been pending for at least `min_idle_ms`
+    ///milliseconds to `consumer`, starting at the ID `start`. Continue by calling it again with
+    ///[`AutoClaim::next`](struct.AutoClaim.html#structfield.next) until it is `0-0`.
+    pub async fn xautoclaim<K, G, C>(
+        &mut self,
+        key: K,
+        group: G,
+        consumer: C,
+        min_idle_ms: u64,
+        start: StreamId,
+        count: Option<usize>,
+    ) -> Result<AutoClaim>
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+        C: AsRef<[u8]>,
+    {
+        let mut command = Command::new("XAUTOCLAIM")
+            .arg(&key)
+            .arg(&group)
+            .arg(&consumer)
+            .arg(min_idle_ms)
+            .arg(start);
+        if let Some(count) = count {
+            command.append_arg("COUNT");
+            command.append_arg(count);
+        }
+
+        self.query(command).await
+    }
+
+    ///Get information about the stream at `key`.
+    pub async fn xinfo_stream<K>(&mut self, key: K) -> Result<StreamInfo>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("XINFO").arg("STREAM").arg(&key);
+        self.query(command).await
+    }
+
+    ///Get information about every consumer group of the stream at `key`.
+    pub async fn xinfo_groups<K>(&mut self, key: K) -> Result<Vec<GroupInfo>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("XINFO").arg("GROUPS").arg(&key);
+        self.query(command).await
+    }
+
+    ///Get information about every consumer in the consumer group `group`.
+    pub async fn xinfo_consumers<K, G>(&mut self, key: K, group: G) -> Result<Vec<ConsumerInfo>>
+    where
+        K: AsRef<[u8]>,
+        G: AsRef<[u8]>,
+    {
+        let command = Command::new("XINFO").arg("CONSUMERS").arg(&key).arg(&group);
+        self.query(command).await
+    }
+
+    ///Scan for elements in a set.
+    ///# Return value
+    ///Returns a list of the elements which matched in the set.
+    ///# Example
+    ///```
+    /// use darkredis::Connection;
+    /// use futures::TryStreamExt;
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    /// # async fn main() {
+    ///
+    /// let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    /// let key = b"test-set".to_vec();
+    /// # connection.del(&key).await.unwrap();
+    /// connection.sadd(&key, "foo").await.unwrap();
+    /// connection.sadd(&key, "bar").await.unwrap();
+    ///
+    /// let results = connection.sscan(&key).run()
+    ///   .try_collect::<Vec<Vec<u8>>>().await.unwrap();
+    ///
+    /// assert!(results.contains(&b"foo".to_vec()));
+    /// assert!(results.contains(&b"bar".to_vec()));
+    /// # connection.del(&key).await.unwrap();
+    /// # }
+    ///```
+    pub fn sscan<'a, K>(&'a mut self, key: &'a K) -> ScanBuilder<'a>
+    where
+        K: AsRef<[u8]>,
+    {
+        ScanBuilder::new("SSCAN", Some(key.as_ref()), self)
+    }
+
+    ///Scan for keys in the database.
+    ///# Return Value
+    /// A stream of the matching keys.
+    ///# Example
+    ///```no_run
+    /// use darkredis::Connection;
+    /// use futures::TryStreamExt;
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    /// # async fn main() {
+    ///
+    /// let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    /// let key = b"locate-me".to_vec();
+    /// connection.set(&key, "dummy-value").await.unwrap();
+    /// let results = connection.scan().pattern(b"locate*").run()
+    ///   .try_collect::<Vec<Vec<u8>>>().await.unwrap();
+    ///
+    /// assert!(results.contains(&key));
+    /// # connection.del(&key).await.unwrap();
+    /// # }
+    ///```
+    pub fn scan(&mut self) -> ScanBuilder<'_> {
+        ScanBuilder::new("SCAN", None, self)
+    }
+
+    ///Scan for fields in the hash set at `key`.
+    ///# Example
+    ///```
+    /// use darkredis::Connection;
+    /// use futures::TryStreamExt;
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    /// # async fn main() {
+    ///
+    /// let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    /// let key = b"hscan_test".to_vec();
+    /// connection.hset(&key, "one", "1").await.unwrap();
+    /// connection.hset(&key, "two", "2").await.unwrap();
+    /// connection.hset(&key, "three", "3").await.unwrap();
+    /// let results = connection.hscan(&key).run()
+    ///   .try_collect::<Vec<(Vec<u8>, Vec<u8>)>>().await.unwrap();
+    ///
+    /// assert_eq!(results.len(), 3);
+    /// assert!(results.contains(&(b"one".to_vec(), b"1".to_vec())));
+    /// assert!(results.contains(&(b"two".to_vec(), b"2".to_vec())));
+    /// assert!(results.contains(&(b"three".to_vec(), b"3".to_vec())));
+    /// # connection.del(&key).await.unwrap();
+    /// # }
+    pub fn hscan<'a, K>(&'a mut self, key: &'a K) -> HScanBuilder<'a>
+    where
+        K: AsRef<[u8]>,
+    {
+        HScanBuilder::new(key.as_ref(), self)
+    }
+
+    ///Scan for members and their scores in the sorted set at `key`.
+    ///# Example
+    ///```
+    /// use darkredis::Connection;
+    /// use futures::TryStreamExt;
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    /// # async fn main() {
+    ///
+    /// let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    /// let key = b"zscan_test".to_vec();
+    /// connection.zadd(&key, 1.0, "one").await.unwrap();
+    /// connection.zadd(&key, 2.5, "two").await.unwrap();
+    /// let results = connection.zscan(&key).run()
+    ///   .try_collect::<Vec<(Vec<u8>, f64)>>().await.unwrap();
+    ///
+    /// assert_eq!(results.len(), 2);
+    /// assert!(results.contains(&(b"one".to_vec(), 1.0)));
+    /// assert!(results.contains(&(b"two".to_vec(), 2.5)));
+    /// # connection.del(&key).await.unwrap();
+    /// # }
+    ///```
+    pub fn zscan<'a, K>(&'a mut self, key: &'a K) -> ZScanBuilder<'a>
+    where
+        K: AsRef<[u8]>,
+    {
+        ZScanBuilder::new(key.as_ref(), self)
+    }
+
+    ///Get the Type of `key` using the `TYPE` command.
+    pub async fn key_type<K>(&mut self, key: K) -> Result<Option<DataType>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("TYPE").arg(&key);
+        let result = match self.run_command(command).await? {
+            Value::Status(result) => result,
+            other => return Err(Error::UnexpectedResponse(format!("{:?}", other))),
+        };
+        match result.as_str() {
+            "string" => Ok(Some(DataType::String)),
+            "list" => Ok(Some(DataType::List)),
+            "set" => Ok(Some(DataType::Set)),
+            "zset" => Ok(Some(DataType::ZSet)),
+            "hash" => Ok(Some(DataType::Hash)),
+            "stream" => Ok(Some(DataType::Stream)),
+            "none" => Ok(None),
+            _ => Err(Error::UnexpectedResponse(result)),
+        }
+    }
+
+    ///Get a random key, or `None` if the database is empty.
+    pub async fn randomkey(&mut self) -> Result<Option<Vec<u8>>> {
+        self.query(Command::new("RANDOMKEY")).await
+    }
+
+    ///Get the internal encoding used to store the value of `key`, such as `listpack` or `int`, or
+    ///`None` if it doesn't exist.
+    pub async fn object_encoding<K>(&mut self, key: K) -> Result<Option<String>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("OBJECT").arg(b"ENCODING").arg(&key);
+        self.query(command).await
+    }
+
+    ///Get the logarithmic access frequency counter of `key`, or `None` if it doesn't exist. Returns
+    ///an error unless the server uses an LFU eviction policy.
+    pub async fn object_freq<K>(&mut self, key: K) -> Result<Option<isize>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("OBJECT").arg(b"FREQ").arg(&key);
+        self.query(command).await
+    }
+
+    ///Get the number of seconds since `key` was last accessed, or `None` if it doesn't exist.
+    pub async fn object_idletime<K>(&mut self, key: K) -> Result<Option<isize>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("OBJECT").arg(b"IDLETIME").arg(&key);
+        self.query(command).await
+    }
+
+    ///Get the number of bytes used to store `key` and its value, or `None` if it doesn't exist.
+    ///For nested types, `samples` elements are sampled to estimate the size, defaulting to 5.
+    ///Use 0 to sample every element.
+    pub async fn memory_usage<K>(&mut self, key: K, samples: Option<usize>) -> Result<Option<isize>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("MEMORY").arg(b"USAGE").arg(&key);
+        if let Some(samples) = samples {
+            command.append_arg("SAMPLES");
+            command.append_arg(samples);
+        }
+        self.query(command).await
+    }
+
+    ///Sort the elements of the list, set or sorted set `key` as described by `builder`.
+    ///# Return value
+    ///The sorted elements. When using [`SortBuilder::get`](struct.SortBuilder.html#method.get),
+    ///values of missing keys are `None`.
+    ///# Example
+    ///```
+    ///use darkredis::{Connection, SortBuilder};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///connection.rpush_slice("sort-example", &["3", "10", "2"]).await.unwrap();
+    ///
+    ///let sorted = connection.sort("sort-example", SortBuilder::new()).await.unwrap();
+    ///assert_eq!(sorted, vec![Some(b"2".to_vec()), Some(b"3".to_vec()), Some(b"10".to_vec())]);
+    ///let sorted = connection.sort("sort-example", SortBuilder::new().alpha().desc()).await.unwrap();
+    ///assert_eq!(sorted, vec![Some(b"3".to_vec()), Some(b"2".to_vec()), Some(b"10".to_vec())]);
+    ///# connection.del("sort-example").await.unwrap();
+    ///# }
+    ///```
+    pub async fn sort<K>(
+        &mut self,
+        key: K,
+        builder: SortBuilder<'_>,
+    ) -> Result<Vec<Option<Vec<u8>>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("SORT").arg(&key);
+        builder.build(&mut command);
+        self.query(command).await
+    }
+
+    ///Like [`sort`](struct.Connection.html#method.sort), but using the read-only `SORT_RO`
+    ///variant which can run on replicas.
+    pub async fn sort_ro<K>(
+        &mut self,
+        key: K,
+        builder: SortBuilder<'_>,
+    ) -> Result<Vec<Option<Vec<u8>>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("SORT_RO").arg(&key);
+        builder.build(&mut command);
+        self.query(command).await
+    }
+
+    ///Like [`sort`](struct.Connection.html#method.sort), but store the result as a list in
+    ///`destination`, replacing it if it exists.
+    ///# Return value
+    ///The number of elements in `destination`.
+    pub async fn sort_store<K, D>(
+        &mut self,
+        key: K,
+        destination: D,
+        builder: SortBuilder<'_>,
+    ) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        D: AsRef<[u8]>,
+    {
+        let mut command = Command::new("SORT").arg(&key);
+        builder.build(&mut command);
+        command.append_arg("STORE");
+        command.append_arg(&destination);
+        self.query(command).await
+    }
+
+    ///Serialize the value of `key` in the Redis-specific format used by
+    ///[`restore`](struct.Connection.html#method.restore). The payload does not contain the expiry
+    ///of the key.
+    ///# Return value
+    ///The serialized value, or `None` if `key` does not exist.
+    pub async fn dump<K>(&mut self, key: K) -> Result<Option<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("DUMP").arg(&key);
+        self.query(command).await
+    }
+
+    ///Create `key` from `payload`, as returned by [`dump`](struct.Connection.html#method.dump),
+    ///expiring after `ttl_ms` milliseconds. A TTL of 0 creates the key without an expiry.
+    ///# Example
+    ///```
+    ///use darkredis::{Connection, RestoreOptions};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///connection.rpush("restore-example", "value").await.unwrap();
+    ///
+    ///let payload = connection.dump("restore-example").await.unwrap().unwrap();
+    ///let options = RestoreOptions::new().replace();
+    ///connection.restore("restore-example-copy", 0, &payload, options).await.unwrap();
+    ///assert_eq!(connection.lrange("restore-example-copy", 0, -1).await.unwrap(), vec![b"value".to_vec()]);
+    ///# connection.del_slice(&["restore-example", "restore-example-copy"]).await.unwrap();
+    ///# }
+    ///```
+    pub async fn restore<K, P>(
+        &mut self,
+        key: K,
+        ttl_ms: u64,
+        payload: P,
+        options: RestoreOptions,
+    ) -> Result<()>
+    where
+        K: AsRef<[u8]>,
+        P: AsRef<[u8]>,
+    {
+        let mut command = Command::new("RESTORE").arg(&key).arg(ttl_ms).arg(&payload);
+        options.build(&mut command);
+        self.query(command).await
+    }
+
+    ///Atomically transfer `keys` to database `destination_db` of the Redis instance at `host`:`port`,
+    ///failing if the transfer takes longer than `timeout_ms` milliseconds. To copy keys between
+    ///instances which can't reach each other, see
+    ///[`copy_keys_to`](struct.Connection.html#method.copy_keys_to).
+    ///# Return value
+    ///`true` if any keys were transferred, `false` if none of `keys` exist.
+    pub async fn migrate<K>(
+        &mut self,
+        host: &str,
+        port: u16,
+        keys: &[K],
+        destination_db: u32,
+        timeout_ms: u64,
+        options: MigrateOptions<'_>,
+    ) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(keys);
+        let mut command = Command::new("MIGRATE")
+            .arg(host)
+            .arg(port)
+            .arg("")
+            .arg(destination_db)
+            .arg(timeout_ms);
+        options.build(&mut command);
+        command.append_arg("KEYS");
+        command.append_args(keys);
+
+        match self.run_command(command).await? {
+            Value::Ok => Ok(true),
+            Value::Status(ref status) if status == "NOKEY" => Ok(false),
+            other => Err(Error::UnexpectedResponse(format!("{:?}", other))),
+        }
+    }
+
+    ///Copy every key matching `pattern` to `destination` together with its expiry, using
+    ///[`SCAN`](https://redis.io/commands/scan), [`DUMP`](https://redis.io/commands/dump) and
+    ///[`RESTORE`](https://redis.io/commands/restore) in pipelined batches of at most `batch_size`
+    ///keys. `batch_size` is also used as the `COUNT` hint for `SCAN`. Existing keys on `destination`
+    ///are overwritten if `replace` is true, otherwise they are reported as failed with a `BUSYKEY`
+    ///error. Unlike [`migrate`](struct.Connection.html#method.migrate), the two instances don't have
+    ///to be able to reach each other, and the keys are kept on the source instance.
+    ///# Example
+    ///```
+    ///use darkredis::Connection;
+    ///use futures::StreamExt;
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///let mut source = Connection::connect("127.0.0.1:6379").await.unwrap();
+    /////Usually a different instance
+    ///let mut destination = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///source.set("copy-example.a", "1").await.unwrap();
+    ///source.set("copy-example.b", "2").await.unwrap();
+    ///
+    ///let mut progress = source.copy_keys_to(&"copy-example.*", &mut destination, 100, true);
+    ///let mut copied = 0;
+    ///while let Some(batch) = progress.next().await {
+    ///    copied = batch.unwrap().copied;
+    ///}
+    ///assert_eq!(copied, 2);
+    ///# drop(progress);
+    ///# source.del_slice(&["copy-example.a", "copy-example.b"]).await.unwrap();
+    ///# }
+    ///```
+    pub fn copy_keys_to<'a, P>(
+        &'a mut self,
+        pattern: &'a P,
+        destination: &'a mut Connection,
+        batch_size: usize,
+        replace: bool,
+    ) -> CopyKeysStream<'a>
+    where
+        P: AsRef<[u8]>,
+    {
+        let source = self.clone();
+        let keys = self.scan().pattern(pattern).count(batch_size.max(1)).run();
+        CopyKeysStream::new(keys, source, destination, batch_size, replace)
+    }
+
+    ///Get the number of members in the set at `key`.
+    pub async fn scard<K>(&mut self, key: &K) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("SCARD").arg(key);
+        self.query(command).await
+    }
+
+    ///Move set member `member` from `source` to `destination`.
+    ///# Return value
+    ///`true` if the member was moved.
+    pub async fn smove<S, D, M>(&mut self, source: S, destination: D, member: M) -> Result<bool>
+    where
+        S: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+        D: AsRef<[u8]>,
+    {
+        let command = Command::new("SMOVE")
+            .arg(&source)
+            .arg(&destination)
+            .arg(&member);
+        self.query(command).await
+    }
+
+    ///Remove set member `member`, from the set `key`.
+    ///# Return value
+    ///`true` if the member was removed.
+    pub async fn srem<K, M>(&mut self, key: K, member: M) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        let command = Command::new("SREM").arg(&key).arg(&member);
+        self.query(command).await
+    }
+
+    ///Remove every member in `members` from the set at `key`.
+    ///# Return value
+    ///The number of members which were removed.
+    pub async fn srem_slice<K, M>(&mut self, key: K, members: &[M]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(members);
+        let command = Command::new("SREM").arg(&key).args(members);
+        self.query(command).await
+    }
+
+    ///Return the difference in members between the first set and all the other sets.
+    pub async fn sdiff<S>(&mut self, sets: &[S]) -> Result<Vec<Vec<u8>>>
+    where
+        S: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(sets);
+        let command = Command::new("SDIFF").args(sets);
+        self.query(command).await
+    }
+
+    ///Place the difference in members between the sets into `destination`.
+    ///# Return value
+    ///The number of elements in `destination` after the operation.
+    pub async fn sdiffstore<D, S>(&mut self, destination: D, sets: &[S]) -> Result<isize>
+    where
+        D: AsRef<[u8]>,
+        S: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(sets);
+        let command = Command::new("SDIFFSTORE").arg(&destination).args(sets);
+        self.query(command).await
+    }
+
+    ///Return the members which are in every set.
+    pub async fn sinter<S>(&mut self, sets: &[S]) -> Result<Vec<Vec<u8>>>
+    where
+        S: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(sets);
+        let command = Command::new("SINTER").args(sets);
+        self.query(command).await
+    }
+
+    ///Create a new set at `destination` containing the members which are part of all sets.
+    ///# Return value
+    ///The number of elements in `destination` after the operation.
+    pub async fn sinterstore<D, S>(&mut self, destination: D, sets: &[S]) -> Result<isize>
+    where
+        D: AsRef<[u8]>,
+        S: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(sets);
+        let command = Command::new("SINTERSTORE").arg(&destination).args(sets);
+        self.query(command).await
+    }
+
+    ///Return a `count` random members of `set`. If `count` is negative, the same element can show up
+    ///multiple times. See the [Redis documentation](https://redis.io/commands/srandmember) for more info.
+    pub async fn srandmember<S>(&mut self, set: S, count: isize) -> Result<Vec<Vec<u8>>>
+    where
+        S: AsRef<[u8]>,
+    {
+        let command = Command::new("SRANDMEMBER").arg(&set).arg(count);
+        self.query(command).await
+    }
+
+    ///Pop `count` random elements out of `set`.
+    pub async fn spop<S>(&mut self, set: S, count: isize) -> Result<Vec<Vec<u8>>>
+    where
+        S: AsRef<[u8]>,
+    {
+        let command = Command::new("SPOP").arg(&set).arg(count);
+        self.query(command).await
+    }
+
+    ///Return the union of every set in `sets`.
+    pub async fn sunion<S>(&mut self, sets: &[S]) -> Result<Vec<Vec<u8>>>
+    where
+        S: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(sets);
+        let command = Command::new("SUNION").args(sets);
+        self.query(command).await
+    }
+
+    ///Store the union of `sets` in `destination`.
+    ///# Return value
+    ///The number of elements in `destination` after the operation.
+    pub async fn sunionstore<D, S>(&mut self, destination: D, sets: &[S]) -> Result<isize>
+    where
+        D: AsRef<[u8]>,
+        S: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(sets);
+        let command = Command::new("SUNIONSTORE").arg(&destination).args(sets);
+        self.query(command).await
+    }
+
+    ///Add `member` to the sorted set `key` with `score`, or update its score if it already exists.
+    ///# Return value
+    ///`true` if the member was added, `false` if its score was updated.
+    pub async fn zadd<K, M>(&mut self, key: K, score: f64, member: M) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        let command = Command::new("ZADD").arg(&key).arg(score).arg(&member);
+        self.query(command).await
+    }
+
+    ///Add every member in `builder` to the sorted set `key`, using the options set in `builder`.
+    ///# Return value
+    ///The number of members which were added, or changed if
+    ///[`ZAddBuilder::ch`](struct.ZAddBuilder.html#method.ch) is used.
+    pub async fn zadd_many<K>(&mut self, key: K, builder: ZAddBuilder<'_>) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("ZADD").arg(&key);
+        builder.build(&mut command);
+        self.query(command).await
+    }
+
+    ///Increment the score of the single member in `builder` by its score, like
+    ///[`zincrby`](struct.Connection.html#method.zincrby) but using the options set in `builder`.
+    ///# Return value
+    ///The new score of the member, or `None` if it wasn't updated because of the options.
+    pub async fn zadd_incr<K>(&mut self, key: K, builder: ZAddBuilder<'_>) -> Result<Option<f64>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("ZADD").arg(&key);
+        command.append_arg("INCR");
+        builder.build(&mut command);
+        self.query(command).await
+    }
+
+    ///Remove `member` from the sorted set `key`.
+    ///# Return value
+    ///`true` if the member was removed.
+    pub async fn zrem<K, M>(&mut self, key: K, member: M) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        let command = Command::new("ZREM").arg(&key).arg(&member);
+        self.query(command).await
+    }
+
+    ///Remove every member in `members` from the sorted set `key`.
+    ///# Return value
+    ///The number of members which were removed.
+    pub async fn zrem_slice<K, M>(&mut self, key: K, members: &[M]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(members);
+        let command = Command::new("ZREM").arg(&key).args(members);
+        self.query(command).await
+    }
+
+    ///Get the score of `member` in the sorted set `key`, or `None` if it isn't a member.
+    pub async fn zscore<K, M>(&mut self, key: K, member: M) -> Result<Option<f64>>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        let command = Command::new("ZSCORE").arg(&key).arg(&member);
+        self.query(command).await
+    }
+
+    ///Get the scores of every member in `members`, in the same order.
+    pub async fn zmscore<K, M>(&mut self, key: K, members: &[M]) -> Result<Vec<Option<f64>>>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(members);
+        let command = Command::new("ZMSCORE").arg(&key).args(members);
+        self.query(command).await
+    }
+
+    ///Increment the score of `member` in the sorted set `key` by `increment`, adding it if it
+    ///doesn't exist.
+    ///# Return value
+    ///The new score of the member.
+    pub async fn zincrby<K, M>(&mut self, key: K, increment: f64, member: M) -> Result<f64>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        let command = Command::new("ZINCRBY")
+            .arg(&key)
+            .arg(increment)
+            .arg(&member);
+        self.query(command).await
+    }
+
+    ///Get the index of `member` in the sorted set `key` ordered from the lowest to the highest
+    ///score, or `None` if it isn't a member.
+    pub async fn zrank<K, M>(&mut self, key: K, member: M) -> Result<Option<isize>>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        let command = Command::new("ZRANK").arg(&key).arg(&member);
+        self.query(command).await
+    }
+
+    ///Like [`zrank`](struct.Connection.html#method.zrank), but ordered from the highest to the
+    ///lowest score.
+    pub async fn zrevrank<K, M>(&mut self, key: K, member: M) -> Result<Option<isize>>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        let command = Command::new("ZREVRANK").arg(&key).arg(&member);
+        self.query(command).await
+    }
+
+    ///Get the number of members in the sorted set `key`.
+    pub async fn zcard<K>(&mut self, key: K) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("ZCARD").arg(&key);
+        self.query(command).await
+    }
+
+    ///Count the members of the sorted set `key` with a score between `min` and `max`. The bounds
+    ///are inclusive unless prefixed with `(`, and can be `-inf` or `+inf`.
+    pub async fn zcount<'a, K, Mi, Ma>(&mut self, key: K, min: Mi, max: Ma) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+        Mi: ToArg<'a>,
+        Ma: ToArg<'a>,
+    {
+        let (min, max) = (min.to_arg(), max.to_arg());
+        let command = Command::new("ZCOUNT").arg(&key).arg(&min).arg(&max);
+        self.query(command).await
+    }
+
+    ///Get the members of the sorted set `key` in the range described by `range`.
     ///# Example
     ///```
-    /// use darkredis::Connection;
-    /// use futures::StreamExt;
+    ///use darkredis::{Connection, ZRangeBuilder};
     ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
     ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
-    /// # async fn main() {
-    ///
-    /// let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
-    /// let key = b"test-set".to_vec();
-    /// # connection.del(&key).await.unwrap();
-    /// connection.sadd(&key, "foo").await.unwrap();
-    /// connection.sadd(&key, "bar").await.unwrap();
+    ///# async fn main() {
+    ///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///connection.zadd("zrange-example", 1.0, "one").await.unwrap();
+    ///connection.zadd("zrange-example", 2.0, "two").await.unwrap();
     ///
-    /// let results = connection.sscan(&key).run()
-    ///   .collect::<Vec<Vec<u8>>>().await;
+    ///let members = connection.zrange("zrange-example", ZRangeBuilder::new(0, -1)).await.unwrap();
+    ///assert_eq!(members, vec![b"one".to_vec(), b"two".to_vec()]);
     ///
-    /// assert!(results.contains(&b"foo".to_vec()));
-    /// assert!(results.contains(&b"bar".to_vec()));
-    /// # connection.del(&key).await.unwrap();
-    /// # }
+    ///let range = ZRangeBuilder::new("+inf", "(1").by_score().rev();
+    ///let scores = connection.zrange_withscores("zrange-example", range).await.unwrap();
+    ///assert_eq!(scores, vec![(b"two".to_vec(), 2.0)]);
+    ///# connection.del("zrange-example").await.unwrap();
+    ///# }
     ///```
-    pub fn sscan<'a, K>(&'a mut self, key: &'a K) -> ScanBuilder
+    pub async fn zrange<K>(&mut self, key: K, range: ZRangeBuilder<'_>) -> Result<Vec<Vec<u8>>>
     where
         K: AsRef<[u8]>,
     {
-        ScanBuilder::new("SSCAN", Some(key.as_ref()), self)
+        let mut command = Command::new("ZRANGE").arg(&key);
+        range.build(&mut command);
+        self.query(command).await
     }
 
-    ///Scan for keys in the database.
-    ///# Return Value
-    /// A stream of the matching keys.
-    ///# Example
-    ///```no_run
-    /// use darkredis::Connection;
-    /// use futures::StreamExt;
-    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
-    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
-    /// # async fn main() {
-    ///
-    /// let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
-    /// let key = b"locate-me".to_vec();
-    /// connection.set(&key, "dummy-value").await.unwrap();
-    /// let results = connection.scan().pattern(b"locate*").run()
-    ///   .collect::<Vec<Vec<u8>>>().await;
-    ///
-    /// assert!(results.contains(&key));
-    /// # connection.del(&key).await.unwrap();
-    /// # }
-    ///```
-    pub fn scan(&mut self) -> ScanBuilder {
-        ScanBuilder::new("SCAN", None, self)
+    ///Like [`zrange`](struct.Connection.html#method.zrange), but also return the score of each member.
+    pub async fn zrange_withscores<K>(
+        &mut self,
+        key: K,
+        range: ZRangeBuilder<'_>,
+    ) -> Result<Vec<(Vec<u8>, f64)>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("ZRANGE").arg(&key);
+        range.build(&mut command);
+        command.append_arg("WITHSCORES");
+        self.query(command).await
     }
 
-    ///Scan for fields in the hash set at `key`.
-    ///# Example
-    ///```
-    /// use darkredis::Connection;
-    /// use futures::StreamExt;
-    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
-    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
-    /// # async fn main() {
-    ///
-    /// let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
-    /// let key = b"hscan_test".to_vec();
-    /// connection.hset(&key, "one", "1").await.unwrap();
-    /// connection.hset(&key, "two", "2").await.unwrap();
-    /// connection.hset(&key, "three", "3").await.unwrap();
-    /// let results = connection.hscan(&key).run()
-    ///   .collect::<Vec<(Vec<u8>, Vec<u8>)>>().await;
-    ///
-    /// assert_eq!(results.len(), 3);
-    /// assert!(results.contains(&(b"one".to_vec(), b"1".to_vec())));
-    /// assert!(results.contains(&(b"two".to_vec(), b"2".to_vec())));
-    /// assert!(results.contains(&(b"three".to_vec(), b"3".to_vec())));
-    /// # connection.del(&key).await.unwrap();
-    /// # }
-    pub fn hscan<'a, K>(&'a mut self, key: &'a K) -> HScanBuilder<'a>
+    ///Pop up to `count` members with the lowest scores out of the sorted set `key`.
+    pub async fn zpopmin<K>(&mut self, key: K, count: usize) -> Result<Vec<(Vec<u8>, f64)>>
     where
         K: AsRef<[u8]>,
     {
-        HScanBuilder::new(key.as_ref(), self)
+        let command = Command::new("ZPOPMIN").arg(&key).arg(count);
+        self.query(command).await
     }
 
-    ///Get the Type of `key` using the `TYPE` command.
-    pub async fn key_type<K>(&mut self, key: K) -> Result<Option<DataType>>
+    ///Pop up to `count` members with the highest scores out of the sorted set `key`.
+    pub async fn zpopmax<K>(&mut self, key: K, count: usize) -> Result<Vec<(Vec<u8>, f64)>>
     where
         K: AsRef<[u8]>,
     {
-        let command = Command::new("TYPE").arg(&key);
-        let result = self.run_command(command).await?.unwrap_string();
-        match result.as_slice() {
-            b"string\r\n" => Ok(Some(DataType::String)),
-            b"list\r\n" => Ok(Some(DataType::List)),
-            b"set\r\n" => Ok(Some(DataType::Set)),
-            b"zset\r\n" => Ok(Some(DataType::ZSet)),
-            b"hash\r\n" => Ok(Some(DataType::Hash)),
-            b"stream\r\n" => Ok(Some(DataType::Stream)),
-            b"none\r\n" => Ok(None),
-            _ => Err(Error::UnexpectedResponse(
-                String::from_utf8_lossy(&result).to_string(),
-            )),
-        }
+        let command = Command::new("ZPOPMAX").arg(&key).arg(count);
+        self.query(command).await
     }
 
-    ///Get the number of members in the set at `key`.
-    pub async fn scard<K>(&mut self, key: &K) -> Result<isize>
+    ///Pop the member with the lowest score out of the first non-empty sorted set in `keys`.
+    ///Block timeout seconds when all of them are empty. A zero-value will block infinitely.
+    ///# Return value
+    ///* `Ok(Some((key, member, score)))`: name of the sorted set and the popped member
+    ///* `Ok(None)`: timeout (no members)
+    ///* `Err(err)`: there was an error
+    pub async fn bzpopmin<K>(
+        &mut self,
+        keys: &[K],
+        timeout: u32,
+    ) -> Result<Option<(Vec<u8>, Vec<u8>, f64)>>
     where
         K: AsRef<[u8]>,
     {
-        let command = Command::new("SCARD").arg(&key);
-        Ok(self.run_command(command).await?.unwrap_integer())
+        check_slice_not_empty!(keys);
+        let command = Command::new("BZPOPMIN").args(keys).arg(timeout);
+        self.query(command).await
     }
 
-    ///Move set member `member` from `source` to `destination`.
+    ///Like [`bzpopmin`](struct.Connection.html#method.bzpopmin), but pop the member with the
+    ///highest score.
+    pub async fn bzpopmax<K>(
+        &mut self,
+        keys: &[K],
+        timeout: u32,
+    ) -> Result<Option<(Vec<u8>, Vec<u8>, f64)>>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(keys);
+        let command = Command::new("BZPOPMAX").args(keys).arg(timeout);
+        self.query(command).await
+    }
+
+    ///Store the union of the sorted sets in `keys` in `destination`. The scores of each set are
+    ///multiplied by the matching weight in `weights` if given, and combined using `aggregate`.
     ///# Return value
-    ///`true` if the member was moved.
-    pub async fn smove<S, D, M>(&mut self, source: S, destination: D, member: M) -> Result<bool>
+    ///The number of members in `destination` after the operation.
+    pub async fn zunionstore<D, K>(
+        &mut self,
+        destination: D,
+        keys: &[K],
+        weights: Option<&[f64]>,
+        aggregate: Option<Aggregate>,
+    ) -> Result<isize>
     where
-        S: AsRef<[u8]>,
-        M: AsRef<[u8]>,
         D: AsRef<[u8]>,
+        K: AsRef<[u8]>,
     {
-        let command = Command::new("SMOVE")
-            .arg(&source)
+        self.zstore("ZUNIONSTORE", destination, keys, weights, aggregate)
+            .await
+    }
+
+    ///Like [`zunionstore`](struct.Connection.html#method.zunionstore), but only store the members
+    ///which are in every sorted set.
+    pub async fn zinterstore<D, K>(
+        &mut self,
+        destination: D,
+        keys: &[K],
+        weights: Option<&[f64]>,
+        aggregate: Option<Aggregate>,
+    ) -> Result<isize>
+    where
+        D: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+    {
+        self.zstore("ZINTERSTORE", destination, keys, weights, aggregate)
+            .await
+    }
+
+    ///zunionstore and zinterstore common code
+    async fn zstore<D, K>(
+        &mut self,
+        redis_cmd: &str,
+        destination: D,
+        keys: &[K],
+        weights: Option<&[f64]>,
+        aggregate: Option<Aggregate>,
+    ) -> Result<isize>
+    where
+        D: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(keys);
+        let mut command = Command::new(redis_cmd)
             .arg(&destination)
+            .arg(keys.len())
+            .args(keys);
+        if let Some(weights) = weights {
+            command.append_arg("WEIGHTS");
+            command.append_args(weights.iter().copied());
+        }
+        if let Some(aggregate) = aggregate {
+            command.append_arg("AGGREGATE");
+            command.append_arg(aggregate.as_arg());
+        }
+
+        self.query(command).await
+    }
+
+    ///Add `member` at `position` to the geospatial index `key`, or update its position if it
+    ///already exists.
+    ///# Return value
+    ///`true` if the member was added, `false` if its position was updated.
+    pub async fn geoadd<K, M>(&mut self, key: K, position: GeoPosition, member: M) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        let command = Command::new("GEOADD")
+            .arg(&key)
+            .arg(position.longitude)
+            .arg(position.latitude)
             .arg(&member);
-        Ok(self.run_command(command).await?.unwrap_bool())
+        self.query(command).await
     }
 
-    ///Remove set member `member`, from the set `key`.
+    ///Like [`geoadd`](struct.Connection.html#method.geoadd), but add multiple members.
     ///# Return value
-    ///`true` if the member was removed.
-    pub async fn srem<K, M>(&mut self, key: K, member: M) -> Result<bool>
+    ///The number of members which were added.
+    pub async fn geoadd_slice<K, M>(
+        &mut self,
+        key: K,
+        members: &[(GeoPosition, M)],
+    ) -> Result<isize>
     where
         K: AsRef<[u8]>,
         M: AsRef<[u8]>,
     {
-        let command = Command::new("SREM").arg(&key).arg(&member);
-        Ok(self.run_command(command).await?.unwrap_bool())
+        check_slice_not_empty!(members);
+        let mut command = Command::new("GEOADD").arg(&key);
+        for (position, member) in members {
+            command.append_arg(position.longitude);
+            command.append_arg(position.latitude);
+            command.append_arg(member);
+        }
+        self.query(command).await
     }
 
-    ///Remove every member in `members` from the set at `key`.
+    ///Get the position of each of `members` in the geospatial index `key`, or `None` for members
+    ///which don't exist.
+    pub async fn geopos<K, M>(&mut self, key: K, members: &[M]) -> Result<Vec<Option<GeoPosition>>>
+    where
+        K: AsRef<[u8]>,
+        M: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(members);
+        let command = Command::new("GEOPOS").arg(&key).args(members);
+        self.query(command).await
+    }
+
+    ///Get the distance between `member1` and `member2` in the geospatial index `key`, in `unit`.
     ///# Return value
-    ///The number of members which were removed.
-    pub async fn srem_slice<K, M>(&mut self, key: K, members: &[M]) -> Result<isize>
+    ///The distance, or `None` if either of the members don't exist.
+    pub async fn geodist<K, M1, M2>(
+        &mut self,
+        key: K,
+        member1: M1,
+        member2: M2,
+        unit: GeoUnit,
+    ) -> Result<Option<f64>>
+    where
+        K: AsRef<[u8]>,
+        M1: AsRef<[u8]>,
+        M2: AsRef<[u8]>,
+    {
+        let command = Command::new("GEODIST")
+            .arg(&key)
+            .arg(&member1)
+            .arg(&member2)
+            .arg(unit.as_arg());
+        self.query(command).await
+    }
+
+    ///Get the standard 11 character geohash string of each of `members` in the geospatial index
+    ///`key`, or `None` for members which don't exist.
+    pub async fn geohash<K, M>(&mut self, key: K, members: &[M]) -> Result<Vec<Option<String>>>
     where
         K: AsRef<[u8]>,
         M: AsRef<[u8]>,
     {
         check_slice_not_empty!(members);
-        let command = Command::new("SREM").arg(&key).args(members);
-        Ok(self.run_command(command).await?.unwrap_integer())
+        let command = Command::new("GEOHASH").arg(&key).args(members);
+        self.query(command).await
     }
 
-    ///Return the difference in members between the first set and all the other sets.
-    pub async fn sdiff<S>(&mut self, sets: &[S]) -> Result<Vec<Vec<u8>>>
+    ///Search the geospatial index `key` for members inside the area described by `search`.
+    ///# Example
+    ///```
+    ///use darkredis::{Connection, GeoPosition, GeoSearchBuilder, GeoShape, GeoUnit};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///let key = "geosearch-example";
+    ///let sicily = [
+    ///    (GeoPosition::new(13.361389, 38.115556), "Palermo"),
+    ///    (GeoPosition::new(15.087269, 37.502669), "Catania"),
+    ///];
+    ///connection.geoadd_slice(key, &sicily).await.unwrap();
+    ///
+    ///let shape = GeoShape::Radius { radius: 100.0, unit: GeoUnit::Kilometers };
+    ///let search = GeoSearchBuilder::from_member("Catania", shape).with_dist();
+    ///let results = connection.geosearch(key, search).await.unwrap();
+    ///assert_eq!(results.len(), 1);
+    ///assert_eq!(results[0].member, b"Catania");
+    ///assert_eq!(results[0].distance, Some(0.0));
+    ///# connection.del(key).await.unwrap();
+    ///# }
+    ///```
+    pub async fn geosearch<K>(
+        &mut self,
+        key: K,
+        search: GeoSearchBuilder<'_>,
+    ) -> Result<Vec<GeoSearchResult>>
     where
-        S: AsRef<[u8]>,
+        K: AsRef<[u8]>,
     {
-        check_slice_not_empty!(sets);
-        let command = Command::new("SDIFF").args(sets);
-        Ok(self.run_command(command).await?.unwrap_string_array())
+        let mut command = Command::new("GEOSEARCH").arg(&key);
+        search.build(&mut command, true);
+        let value = self.query(command).await?;
+        search.parse(value)
     }
 
-    ///Place the difference in members between the sets into `destination`.
+    ///Like [`geosearch`](struct.Connection.html#method.geosearch), but store the found members
+    ///from `source` in the geospatial index `destination`. If `store_dist` is true, the members
+    ///are stored in a regular sorted set with their distance as their score instead.
     ///# Return value
-    ///The number of elements in `destination` after the operation.
-    pub async fn sdiffstore<D, S>(&mut self, destination: D, sets: &[S]) -> Result<isize>
+    ///The number of members in `destination` after the operation.
+    pub async fn geosearchstore<D, S>(
+        &mut self,
+        destination: D,
+        source: S,
+        search: GeoSearchBuilder<'_>,
+        store_dist: bool,
+    ) -> Result<isize>
     where
         D: AsRef<[u8]>,
         S: AsRef<[u8]>,
     {
-        check_slice_not_empty!(sets);
-        let command = Command::new("SDIFFSTORE").arg(&destination).args(sets);
-        Ok(self.run_command(command).await?.unwrap_integer())
+        let mut command = Command::new("GEOSEARCHSTORE")
+            .arg(&destination)
+            .arg(&source);
+        search.build(&mut command, false);
+        if store_dist {
+            command.append_arg("STOREDIST");
+        }
+        self.query(command).await
     }
 
-    ///Return the members which are in every set.
-    pub async fn sinter<S>(&mut self, sets: &[S]) -> Result<Vec<Vec<u8>>>
+    ///Add `element` to the HyperLogLog `key`, creating it if it doesn't exist.
+    ///# Return value
+    ///`true` if the estimated cardinality of the HyperLogLog changed.
+    pub async fn pfadd<K, E>(&mut self, key: K, element: E) -> Result<bool>
     where
-        S: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+        E: AsRef<[u8]>,
     {
-        check_slice_not_empty!(sets);
-        let command = Command::new("SINTER").args(sets);
-        Ok(self.run_command(command).await?.unwrap_string_array())
+        let command = Command::new("PFADD").arg(&key).arg(&element);
+        self.query(command).await
     }
 
-    ///Create a new set at `destination` containing the members which are part of all sets.
-    ///# Return value
-    ///The number of elements in `destination` after the operation.
-    pub async fn sinterstore<D, S>(&mut self, destination: D, sets: &[S]) -> Result<isize>
+    ///Like [`pfadd`](struct.Connection.html#method.pfadd), but add multiple elements.
+    pub async fn pfadd_slice<K, E>(&mut self, key: K, elements: &[E]) -> Result<bool>
+    where
+        K: AsRef<[u8]>,
+        E: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(elements);
+        let command = Command::new("PFADD").arg(&key).args(elements);
+        self.query(command).await
+    }
+
+    ///Get the approximate number of unique elements added to the HyperLogLog `key`.
+    pub async fn pfcount<K>(&mut self, key: K) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let command = Command::new("PFCOUNT").arg(&key);
+        self.query(command).await
+    }
+
+    ///Like [`pfcount`](struct.Connection.html#method.pfcount), but get the approximate number of
+    ///unique elements in the union of the HyperLogLogs in `keys`.
+    pub async fn pfcount_slice<K>(&mut self, keys: &[K]) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(keys);
+        let command = Command::new("PFCOUNT").args(keys);
+        self.query(command).await
+    }
+
+    ///Merge the HyperLogLogs in `sources` into `destination`, including the existing contents of
+    ///`destination` if it exists.
+    pub async fn pfmerge<D, S>(&mut self, destination: D, sources: &[S]) -> Result<()>
     where
         D: AsRef<[u8]>,
         S: AsRef<[u8]>,
     {
-        check_slice_not_empty!(sets);
-        let command = Command::new("SINTERSTORE").arg(&destination).args(sets);
-        Ok(self.run_command(command).await?.unwrap_integer())
+        check_slice_not_empty!(sources);
+        let command = Command::new("PFMERGE").arg(&destination).args(sources);
+        self.query(command).await
     }
 
-    ///Return a `count` random members of `set`. If `count` is negative, the same element can show up
-    ///multiple times. See the [Redis documentation](https://redis.io/commands/srandmember) for more info.
-    pub async fn srandmember<S>(&mut self, set: S, count: isize) -> Result<Vec<Vec<u8>>>
+    ///Set the bit at `offset` in the string `key` to `value`, growing the string if needed.
+    ///# Return value
+    ///The previous value of the bit.
+    pub async fn setbit<K>(&mut self, key: K, offset: usize, value: bool) -> Result<bool>
     where
-        S: AsRef<[u8]>,
+        K: AsRef<[u8]>,
     {
-        let count = count.to_string();
-        let command = Command::new("SRANDMEMBER").arg(&set).arg(&count);
-        Ok(self.run_command(command).await?.unwrap_string_array())
+        let command = Command::new("SETBIT").arg(&key).arg(offset).arg(value);
+        self.query(command).await
     }
 
-    ///Pop `count` random elements out of `set`.
-    pub async fn spop<S>(&mut self, set: S, count: isize) -> Result<Vec<Vec<u8>>>
+    ///Get the bit at `offset` in the string `key`. Bits past the end of the string are `false`.
+    pub async fn getbit<K>(&mut self, key: K, offset: usize) -> Result<bool>
     where
-        S: AsRef<[u8]>,
+        K: AsRef<[u8]>,
     {
-        let count = count.to_string();
-        let command = Command::new("SPOP").arg(&set).arg(&count);
-        Ok(self.run_command(command).await?.unwrap_string_array())
+        let command = Command::new("GETBIT").arg(&key).arg(offset);
+        self.query(command).await
     }
 
-    ///Return the union of every set in `sets`.
-    pub async fn sunion<S>(&mut self, sets: &[S]) -> Result<Vec<Vec<u8>>>
+    ///Count the number of set bits in the string `key`, or only in `range` if given.
+    pub async fn bitcount<K>(&mut self, key: K, range: Option<BitRange>) -> Result<isize>
     where
-        S: AsRef<[u8]>,
+        K: AsRef<[u8]>,
     {
-        check_slice_not_empty!(sets);
-        let command = Command::new("SUNION").args(sets);
-        Ok(self.run_command(command).await?.unwrap_string_array())
+        let mut command = Command::new("BITCOUNT").arg(&key);
+        if let Some(range) = range {
+            range.build(&mut command);
+        }
+        self.query(command).await
     }
 
-    ///Store the union of `sets` in `destination`.
+    ///Find the position of the first bit set to `bit` in the string `key`, or only in `range` if given.
     ///# Return value
-    ///The number of elements in `destination` after the operation.
-    pub async fn sunionstore<D, S>(&mut self, destination: D, sets: &[S]) -> Result<isize>
+    ///The position of the bit counted from the start of the string, or -1 if it wasn't found. When
+    ///looking for a clear bit without a `range` in a string with every bit set, the string is
+    ///considered padded with clear bits and the position after the end of the string is returned.
+    pub async fn bitpos<K>(&mut self, key: K, bit: bool, range: Option<BitRange>) -> Result<isize>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("BITPOS").arg(&key).arg(bit);
+        if let Some(range) = range {
+            range.build(&mut command);
+        }
+        self.query(command).await
+    }
+
+    ///Perform the bitwise operation `op` on the strings in `keys`, storing the result in
+    ///`destination`. [`BitOp::Not`](enum.BitOp.html#variant.Not) takes exactly one key.
+    ///# Return value
+    ///The length of the string stored in `destination`, which is the length of the longest source string.
+    pub async fn bitop<D, K>(&mut self, op: BitOp, destination: D, keys: &[K]) -> Result<isize>
     where
         D: AsRef<[u8]>,
-        S: AsRef<[u8]>,
+        K: AsRef<[u8]>,
     {
-        check_slice_not_empty!(sets);
-        let command = Command::new("SUNIONSTORE").arg(&destination).args(sets);
-        Ok(self.run_command(command).await?.unwrap_integer())
+        check_slice_not_empty!(keys);
+        let command = Command::new("BITOP")
+            .arg(op.as_arg())
+            .arg(&destination)
+            .args(keys);
+        self.query(command).await
+    }
+
+    ///Perform the operations in `builder` on the integer fields of the string `key`.
+    ///# Return value
+    ///The result of each `get`, `set` and `incrby` operation, in order. Operations which failed due
+    ///to [`BitFieldOverflow::Fail`](enum.BitFieldOverflow.html#variant.Fail) return `None`.
+    ///# Example
+    ///```
+    ///use darkredis::{BitFieldBuilder, BitFieldOverflow, BitFieldType, Connection};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///let key = "bitfield-example";
+    ///let counter = BitFieldType::Unsigned(4);
+    ///let builder = BitFieldBuilder::new()
+    ///    .set(counter, 0, 14)
+    ///    .incrby(counter, 0, 1)
+    ///    .overflow(BitFieldOverflow::Fail)
+    ///    .incrby(counter, 0, 1);
+    ///let results = connection.bitfield(key, builder).await.unwrap();
+    ///assert_eq!(results, vec![Some(0), Some(15), None]);
+    ///# connection.del(key).await.unwrap();
+    ///# }
+    ///```
+    pub async fn bitfield<K>(
+        &mut self,
+        key: K,
+        builder: BitFieldBuilder,
+    ) -> Result<Vec<Option<i64>>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("BITFIELD").arg(&key);
+        builder.build(&mut command);
+        self.query(command).await
+    }
+
+    ///Get the values of `fields`, given as their type and offset in bits, using the read-only
+    ///[`BITFIELD_RO`](https://redis.io/commands/bitfield_ro) variant of
+    ///[`bitfield`](struct.Connection.html#method.bitfield) which can run on replicas.
+    ///# Example
+    ///```
+    ///use darkredis::{BitFieldType, Connection};
+    ///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+    ///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+    ///# async fn main() {
+    ///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+    ///let key = "bitfield-ro-example";
+    ///connection.set(key, b"\x01\xff").await.unwrap();
+    ///let fields = [(BitFieldType::Unsigned(8), 0), (BitFieldType::Signed(8), 8)];
+    ///let results = connection.bitfield_ro(key, &fields).await.unwrap();
+    ///assert_eq!(results, vec![1, -1]);
+    ///# connection.del(key).await.unwrap();
+    ///# }
+    ///```
+    pub async fn bitfield_ro<K>(
+        &mut self,
+        key: K,
+        fields: &[(BitFieldType, usize)],
+    ) -> Result<Vec<i64>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let mut command = Command::new("BITFIELD_RO").arg(&key);
+        for (ty, offset) in fields {
+            command.append_arg("GET");
+            command.append_arg(ty.as_arg());
+            command.append_arg(*offset);
+        }
+        self.query(command).await
     }
 }
@@ -0,0 +1,152 @@
+use super::parse::{Parser, Scanner};
+use crate::{Error, Result, Value};
+
+#[cfg(feature = "runtime_async_std")]
+use async_std::{io, net::TcpStream};
+#[cfg(feature = "runtime_async_std")]
+use futures::{AsyncReadExt, AsyncWriteExt};
+
+#[cfg(feature = "runtime_tokio")]
+use tokio::{
+    io::{self, AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+//The size of the read buffer of a new connection.
+const INITIAL_BUFFER_SIZE: usize = 8 * 1024;
+
+///A TCP stream with a growable read buffer. Replies are parsed directly from the buffer, so that
+///reading a value only requires a system call whenever the buffer doesn't contain a complete value.
+#[derive(Debug)]
+pub(crate) struct BufferedStream {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    //The received bytes which are yet to be parsed are buffer[start..end].
+    start: usize,
+    end: usize,
+    //Progress towards receiving the complete value at the start of the buffer.
+    scanner: Scanner,
+    #[cfg(test)]
+    parse_attempts: usize,
+}
+
+impl BufferedStream {
+    pub(crate) fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            buffer: vec![0; INITIAL_BUFFER_SIZE],
+            start: 0,
+            end: 0,
+            scanner: Scanner::new(),
+            #[cfg(test)]
+            parse_attempts: 0,
+        }
+    }
+
+    ///Write all of `data` to the underlying stream.
+    #[inline]
+    pub(crate) async fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        self.stream.write_all(data).await?;
+        Ok(())
+    }
+
+    ///Read a single value, only reading from the socket if the buffer doesn't contain a complete value.
+    ///Error replies are returned as an [`Error::RedisError`](enum.Error.html#variant.RedisError).
+    ///If the connection is closed before a complete value is received, an IO error of the kind
+    ///`UnexpectedEof` is returned.
+    pub(crate) async fn read_value(&mut self) -> Result<Value> {
+        loop {
+            //Only build the value once all of it has been received.
+            if self.start != self.end && self.scanner.scan(&self.buffer[self.start..self.end]) {
+                #[cfg(test)]
+                {
+                    self.parse_attempts += 1;
+                }
+                let mut parser = Parser::new(&self.buffer[self.start..self.end]);
+                match parser.parse() {
+                    Ok(Some(value)) => {
+                        self.consume(parser.position());
+                        self.scanner.reset();
+                        return value.into_result();
+                    }
+                    Ok(None) => (),
+                    Err(e) => {
+                        //There's no way to know where the next value starts, so discard everything.
+                        self.consume(self.end - self.start);
+                        self.scanner.reset();
+                        return Err(e);
+                    }
+                }
+            }
+
+            self.fill_buffer().await?;
+        }
+    }
+
+    fn consume(&mut self, count: usize) {
+        self.start += count;
+        if self.start == self.end {
+            self.start = 0;
+            self.end = 0;
+        }
+    }
+
+    //Read more data from the socket into the buffer, making room for it first.
+    async fn fill_buffer(&mut self) -> Result<()> {
+        if self.start > 0 {
+            self.buffer.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+
+        //Grow the buffer exponentially so that large replies need few reads and parsing attempts.
+        if self.buffer.len() - self.end < self.buffer.len() / 2 {
+            let new_len = self.buffer.len() * 2;
+            self.buffer.resize(new_len, 0);
+        }
+
+        let read = self.stream.read(&mut self.buffer[self.end..]).await?;
+        if read == 0 {
+            return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+        self.end += read;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    #[cfg_attr(feature = "runtime_tokio", tokio::test)]
+    #[cfg_attr(feature = "runtime_async_std", async_std::test)]
+    async fn parse_once() {
+        let mut reply = b"*1000\r\n".to_vec();
+        for i in 0..1000 {
+            reply.extend_from_slice(format!("$4\r\n{:04}\r\n", i).as_bytes());
+        }
+        reply.extend_from_slice(b":1\r\n");
+
+        //Send the reply in many small pieces
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            socket.set_nodelay(true).unwrap();
+            for chunk in reply.chunks(64) {
+                socket.write_all(chunk).unwrap();
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+        });
+
+        let mut stream = BufferedStream::new(TcpStream::connect(address).await.unwrap());
+        let value = stream.read_value().await.unwrap();
+        assert_eq!(value.unwrap_array().len(), 1000);
+        assert_eq!(stream.parse_attempts, 1);
+        assert_eq!(stream.read_value().await.unwrap(), Value::Integer(1));
+        assert_eq!(stream.parse_attempts, 2);
+        server.join().unwrap();
+    }
+}
@@ -1,3 +1,6 @@
+use crate::{Command, ToArg};
+use std::borrow::Cow;
+
 ///A builder struct for commands where you set multiple values at once. It utilizes
 ///references to ensure that it does not copy any of the data given to it. It supports
 ///the classic builder-pattern, as well as a mutable pattern.
@@ -48,3 +51,844 @@ impl<'a> MSetBuilder<'a> {
         self
     }
 }
+
+///A builder for the [`ZADD`](https://redis.io/commands/zadd) command, holding the members to
+///add together with their scores, as well as the options controlling how existing members are
+///updated. Used with [`Connection::zadd_many`](struct.Connection.html#method.zadd_many) and
+///[`Connection::zadd_incr`](struct.Connection.html#method.zadd_incr).
+///# Example
+///```
+///# use darkredis::ZAddBuilder;
+/// //Builder-style
+///let mut builder = ZAddBuilder::new().add(1.0, b"first").add(2.5, b"second").xx().gt();
+///
+/// // Mutable style
+///builder.append(3.0, b"third");
+///```
+#[derive(Debug, Default)]
+pub struct ZAddBuilder<'a> {
+    condition: Option<ZAddCondition>,
+    ch: bool,
+    members: Vec<(f64, &'a [u8])>,
+}
+
+//The combinations of NX, XX, GT and LT accepted by ZADD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ZAddCondition {
+    Nx,
+    Xx,
+    Gt,
+    Lt,
+    XxGt,
+    XxLt,
+}
+
+impl ZAddCondition {
+    fn as_args(self) -> &'static [&'static str] {
+        match self {
+            ZAddCondition::Nx => &["NX"],
+            ZAddCondition::Xx => &["XX"],
+            ZAddCondition::Gt => &["GT"],
+            ZAddCondition::Lt => &["LT"],
+            ZAddCondition::XxGt => &["XX", "GT"],
+            ZAddCondition::XxLt => &["XX", "LT"],
+        }
+    }
+
+    fn is_xx(self) -> bool {
+        matches!(
+            self,
+            ZAddCondition::Xx | ZAddCondition::XxGt | ZAddCondition::XxLt
+        )
+    }
+}
+
+impl<'a> ZAddBuilder<'a> {
+    ///Create a new instance.
+    pub fn new() -> Self {
+        Self {
+            condition: None,
+            ch: false,
+            members: Vec::new(),
+        }
+    }
+
+    pub(crate) fn build(&self, command: &mut Command<'a>) {
+        if let Some(condition) = self.condition {
+            for arg in condition.as_args() {
+                command.append_arg(*arg);
+            }
+        }
+        if self.ch {
+            command.append_arg("CH");
+        }
+        for (score, member) in &self.members {
+            command.append_arg(*score);
+            command.append_arg(*member);
+        }
+    }
+
+    ///Add `member` with `score`, mutable style.
+    #[inline]
+    pub fn append<M>(&mut self, score: f64, member: &'a M)
+    where
+        M: AsRef<[u8]> + ?Sized,
+    {
+        self.members.push((score, member.as_ref()));
+    }
+
+    ///Add `member` with `score`, builder-style.
+    #[inline]
+    pub fn add<M>(mut self, score: f64, member: &'a M) -> Self
+    where
+        M: AsRef<[u8]> + ?Sized,
+    {
+        self.append(score, member);
+        self
+    }
+
+    ///Only add new members, never update existing ones. Replaces any previous call to
+    ///[`xx`](struct.ZAddBuilder.html#method.xx), [`gt`](struct.ZAddBuilder.html#method.gt) or
+    ///[`lt`](struct.ZAddBuilder.html#method.lt).
+    pub fn nx(mut self) -> Self {
+        self.condition = Some(ZAddCondition::Nx);
+        self
+    }
+
+    ///Only update existing members, never add new ones. Replaces any previous call to
+    ///[`nx`](struct.ZAddBuilder.html#method.nx), and can be combined with
+    ///[`gt`](struct.ZAddBuilder.html#method.gt) or [`lt`](struct.ZAddBuilder.html#method.lt).
+    pub fn xx(mut self) -> Self {
+        self.condition = match self.condition {
+            Some(ZAddCondition::Gt) => Some(ZAddCondition::XxGt),
+            Some(ZAddCondition::Lt) => Some(ZAddCondition::XxLt),
+            Some(condition) if condition.is_xx() => Some(condition),
+            _ => Some(ZAddCondition::Xx),
+        };
+        self
+    }
+
+    ///Only update existing members if the new score is greater than the current one. Replaces any
+    ///previous call to [`nx`](struct.ZAddBuilder.html#method.nx) or
+    ///[`lt`](struct.ZAddBuilder.html#method.lt).
+    pub fn gt(mut self) -> Self {
+        self.condition = match self.condition {
+            Some(condition) if condition.is_xx() => Some(ZAddCondition::XxGt),
+            _ => Some(ZAddCondition::Gt),
+        };
+        self
+    }
+
+    ///Only update existing members if the new score is less than the current one. Replaces any
+    ///previous call to [`nx`](struct.ZAddBuilder.html#method.nx) or
+    ///[`gt`](struct.ZAddBuilder.html#method.gt).
+    pub fn lt(mut self) -> Self {
+        self.condition = match self.condition {
+            Some(condition) if condition.is_xx() => Some(ZAddCondition::XxLt),
+            _ => Some(ZAddCondition::Lt),
+        };
+        self
+    }
+
+    ///Count the members whose score was changed, not only the ones which were added.
+    pub fn ch(mut self) -> Self {
+        self.ch = true;
+        self
+    }
+}
+
+///A builder for the [`ZRANGE`](https://redis.io/commands/zrange) command, used with
+///[`Connection::zrange`](struct.Connection.html#method.zrange) and
+///[`Connection::zrange_withscores`](struct.Connection.html#method.zrange_withscores). By default
+///`start` and `stop` are indexes, but they can be changed to be scores or lexicographical ranges
+///using [`by_score`](struct.ZRangeBuilder.html#method.by_score) and
+///[`by_lex`](struct.ZRangeBuilder.html#method.by_lex).
+///# Example
+///```
+///# use darkredis::ZRangeBuilder;
+/// //The first 10 members with a score above 5, highest scores first
+///let builder = ZRangeBuilder::new("+inf", "(5").by_score().rev().limit(0, 10);
+///```
+#[derive(Debug, Clone)]
+pub struct ZRangeBuilder<'a> {
+    start: Cow<'a, [u8]>,
+    stop: Cow<'a, [u8]>,
+    by: Option<&'static str>,
+    rev: bool,
+    limit: Option<(isize, isize)>,
+}
+
+impl<'a> ZRangeBuilder<'a> {
+    ///Create a new instance, returning the members from `start` to `stop`. When used together with
+    ///[`rev`](struct.ZRangeBuilder.html#method.rev), `start` is the highest score or member.
+    pub fn new<S, E>(start: S, stop: E) -> Self
+    where
+        S: ToArg<'a>,
+        E: ToArg<'a>,
+    {
+        Self {
+            start: start.to_arg(),
+            stop: stop.to_arg(),
+            by: None,
+            rev: false,
+            limit: None,
+        }
+    }
+
+    pub(crate) fn build(&self, command: &mut Command<'a>) {
+        command.append_arg(self.start.clone());
+        command.append_arg(self.stop.clone());
+        if let Some(by) = self.by {
+            command.append_arg(by);
+        }
+        if self.rev {
+            command.append_arg("REV");
+        }
+        if let Some((offset, count)) = self.limit {
+            command.append_arg("LIMIT");
+            command.append_arg(offset);
+            command.append_arg(count);
+        }
+    }
+
+    ///Treat `start` and `stop` as scores, such as `1`, `(1` for an exclusive bound or `-inf`.
+    pub fn by_score(mut self) -> Self {
+        self.by = Some("BYSCORE");
+        self
+    }
+
+    ///Treat `start` and `stop` as lexicographical ranges, such as `[a`, `(a`, `-` or `+`.
+    pub fn by_lex(mut self) -> Self {
+        self.by = Some("BYLEX");
+        self
+    }
+
+    ///Return the members from the highest to the lowest score.
+    pub fn rev(mut self) -> Self {
+        self.rev = true;
+        self
+    }
+
+    ///Skip `offset` members and return at most `count` members, or all of them if `count` is
+    ///negative. Only valid together with [`by_score`](struct.ZRangeBuilder.html#method.by_score)
+    ///or [`by_lex`](struct.ZRangeBuilder.html#method.by_lex).
+    pub fn limit(mut self, offset: isize, count: isize) -> Self {
+        self.limit = Some((offset, count));
+        self
+    }
+}
+
+///How the scores of members present in multiple sorted sets are combined by
+///[`Connection::zunionstore`](struct.Connection.html#method.zunionstore) and
+///[`Connection::zinterstore`](struct.Connection.html#method.zinterstore).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    ///Add the scores together. This is the default in Redis.
+    Sum,
+    ///Use the lowest score.
+    Min,
+    ///Use the highest score.
+    Max,
+}
+
+impl Aggregate {
+    pub(crate) fn as_arg(self) -> &'static str {
+        match self {
+            Aggregate::Sum => "SUM",
+            Aggregate::Min => "MIN",
+            Aggregate::Max => "MAX",
+        }
+    }
+}
+
+///A range of a string used by [`Connection::bitcount`](struct.Connection.html#method.bitcount)
+///and [`Connection::bitpos`](struct.Connection.html#method.bitpos). Both ends are inclusive, and
+///negative indexes count from the end of the string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitRange {
+    ///A range of bytes, from `start` to `end`.
+    Bytes(isize, isize),
+    ///A range of bits, from `start` to `end`. Requires Redis 7.0 or newer.
+    Bits(isize, isize),
+}
+
+impl BitRange {
+    pub(crate) fn build(self, command: &mut Command) {
+        let (start, end, unit) = match self {
+            BitRange::Bytes(start, end) => (start, end, "BYTE"),
+            BitRange::Bits(start, end) => (start, end, "BIT"),
+        };
+        command.append_arg(start);
+        command.append_arg(end);
+        command.append_arg(unit);
+    }
+}
+
+///The operation performed by [`Connection::bitop`](struct.Connection.html#method.bitop).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOp {
+    ///Bitwise AND of every source string.
+    And,
+    ///Bitwise OR of every source string.
+    Or,
+    ///Bitwise XOR of every source string.
+    Xor,
+    ///Bitwise NOT of a single source string.
+    Not,
+}
+
+impl BitOp {
+    pub(crate) fn as_arg(self) -> &'static str {
+        match self {
+            BitOp::And => "AND",
+            BitOp::Or => "OR",
+            BitOp::Xor => "XOR",
+            BitOp::Not => "NOT",
+        }
+    }
+}
+
+///The type of an integer field used by [`BitFieldBuilder`](struct.BitFieldBuilder.html). Signed
+///fields can be up to 64 bits wide, and unsigned fields up to 63 bits wide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitFieldType {
+    ///A signed integer with the given width in bits, such as `i8`.
+    Signed(u8),
+    ///An unsigned integer with the given width in bits, such as `u8`.
+    Unsigned(u8),
+}
+
+impl BitFieldType {
+    pub(crate) fn as_arg(self) -> String {
+        match self {
+            BitFieldType::Signed(bits) => format!("i{}", bits),
+            BitFieldType::Unsigned(bits) => format!("u{}", bits),
+        }
+    }
+}
+
+///How [`BitFieldBuilder`](struct.BitFieldBuilder.html) handles `SET` and `INCRBY` operations
+///which overflow the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitFieldOverflow {
+    ///Wrap around, like integer overflow in most languages. This is the default in Redis.
+    Wrap,
+    ///Saturate at the minimum or maximum value of the field.
+    Sat,
+    ///Don't perform the operation, returning `None` as its result.
+    Fail,
+}
+
+impl BitFieldOverflow {
+    pub(crate) fn as_arg(self) -> &'static str {
+        match self {
+            BitFieldOverflow::Wrap => "WRAP",
+            BitFieldOverflow::Sat => "SAT",
+            BitFieldOverflow::Fail => "FAIL",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BitFieldOperation {
+    Get(BitFieldType, usize),
+    Set(BitFieldType, usize, i64),
+    IncrBy(BitFieldType, usize, i64),
+    Overflow(BitFieldOverflow),
+}
+
+///A builder for the [`BITFIELD`](https://redis.io/commands/bitfield) command, used with
+///[`Connection::bitfield`](struct.Connection.html#method.bitfield). The operations are performed
+///in the order they are added, and offsets are given in bits.
+///# Example
+///```
+///# use darkredis::{BitFieldBuilder, BitFieldOverflow, BitFieldType};
+/// //Increment an unsigned 8-bit counter at bit 0 without overflowing, and read the next one
+///let builder = BitFieldBuilder::new()
+///    .overflow(BitFieldOverflow::Sat)
+///    .incrby(BitFieldType::Unsigned(8), 0, 1)
+///    .get(BitFieldType::Unsigned(8), 8);
+///```
+#[derive(Debug, Clone, Default)]
+pub struct BitFieldBuilder {
+    operations: Vec<BitFieldOperation>,
+}
+
+impl BitFieldBuilder {
+    ///Create a new instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Get the value of the field of type `ty` at `offset`.
+    pub fn get(mut self, ty: BitFieldType, offset: usize) -> Self {
+        self.operations.push(BitFieldOperation::Get(ty, offset));
+        self
+    }
+
+    ///Set the field of type `ty` at `offset` to `value`, returning its old value.
+    pub fn set(mut self, ty: BitFieldType, offset: usize, value: i64) -> Self {
+        self.operations
+            .push(BitFieldOperation::Set(ty, offset, value));
+        self
+    }
+
+    ///Increment the field of type `ty` at `offset` by `increment`, returning its new value.
+    pub fn incrby(mut self, ty: BitFieldType, offset: usize, increment: i64) -> Self {
+        self.operations
+            .push(BitFieldOperation::IncrBy(ty, offset, increment));
+        self
+    }
+
+    ///Change how the `set` and `incrby` operations added after this one handle overflows.
+    pub fn overflow(mut self, overflow: BitFieldOverflow) -> Self {
+        self.operations.push(BitFieldOperation::Overflow(overflow));
+        self
+    }
+
+    pub(crate) fn build(&self, command: &mut Command) {
+        for operation in &self.operations {
+            match *operation {
+                BitFieldOperation::Get(ty, offset) => {
+                    command.append_arg("GET");
+                    command.append_arg(ty.as_arg());
+                    command.append_arg(offset);
+                }
+                BitFieldOperation::Set(ty, offset, value) => {
+                    command.append_arg("SET");
+                    command.append_arg(ty.as_arg());
+                    command.append_arg(offset);
+                    command.append_arg(value);
+                }
+                BitFieldOperation::IncrBy(ty, offset, increment) => {
+                    command.append_arg("INCRBY");
+                    command.append_arg(ty.as_arg());
+                    command.append_arg(offset);
+                    command.append_arg(increment);
+                }
+                BitFieldOperation::Overflow(overflow) => {
+                    command.append_arg("OVERFLOW");
+                    command.append_arg(overflow.as_arg());
+                }
+            }
+        }
+    }
+}
+
+///The end of a list used by [`Connection::lmove`](struct.Connection.html#method.lmove),
+///[`Connection::lmpop`](struct.Connection.html#method.lmpop) and their blocking variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListSide {
+    ///The head of the list, like `LPUSH` and `LPOP`.
+    Left,
+    ///The tail of the list, like `RPUSH` and `RPOP`.
+    Right,
+}
+
+impl ListSide {
+    pub(crate) fn as_arg(self) -> &'static str {
+        match self {
+            ListSide::Left => "LEFT",
+            ListSide::Right => "RIGHT",
+        }
+    }
+}
+
+///Where [`Connection::linsert`](struct.Connection.html#method.linsert) inserts an element
+///relative to the pivot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertPosition {
+    ///Insert the element before the pivot.
+    Before,
+    ///Insert the element after the pivot.
+    After,
+}
+
+impl InsertPosition {
+    pub(crate) fn as_arg(self) -> &'static str {
+        match self {
+            InsertPosition::Before => "BEFORE",
+            InsertPosition::After => "AFTER",
+        }
+    }
+}
+
+///When a key expires, used by [`SetOptions`](struct.SetOptions.html) and
+///[`GetExOption`](enum.GetExOption.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    ///Expire after the given number of seconds, using `EX`.
+    Seconds(u32),
+    ///Expire after the given number of milliseconds, using `PX`.
+    Milliseconds(u32),
+    ///Expire at the given Unix timestamp in seconds, using `EXAT`.
+    AtSeconds(u64),
+    ///Expire at the given Unix timestamp in milliseconds, using `PXAT`.
+    AtMilliseconds(u64),
+}
+
+impl Expiry {
+    pub(crate) fn build(self, command: &mut Command) {
+        match self {
+            Expiry::Seconds(seconds) => {
+                command.append_arg("EX");
+                command.append_arg(seconds);
+            }
+            Expiry::Milliseconds(milliseconds) => {
+                command.append_arg("PX");
+                command.append_arg(milliseconds);
+            }
+            Expiry::AtSeconds(timestamp) => {
+                command.append_arg("EXAT");
+                command.append_arg(timestamp);
+            }
+            Expiry::AtMilliseconds(timestamp) => {
+                command.append_arg("PXAT");
+                command.append_arg(timestamp);
+            }
+        }
+    }
+}
+
+///How [`Connection::getex`](struct.Connection.html#method.getex) changes the expiry of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GetExOption {
+    ///Set the key to expire at the given time.
+    Expire(Expiry),
+    ///Remove the expiry of the key, using `PERSIST`.
+    Persist,
+}
+
+impl GetExOption {
+    pub(crate) fn build(self, command: &mut Command) {
+        match self {
+            GetExOption::Expire(expiry) => expiry.build(command),
+            GetExOption::Persist => command.append_arg("PERSIST"),
+        }
+    }
+}
+
+///Options for the [`SET`](https://redis.io/commands/set) command, used with
+///[`Connection::set_with_options`](struct.Connection.html#method.set_with_options) and
+///[`Connection::set_and_get`](struct.Connection.html#method.set_and_get). By default the key
+///is always set, and any existing expiry is removed.
+///# Example
+///```
+///# use darkredis::{Expiry, SetOptions};
+/// //Only set the key if it doesn't exist, expiring after one minute
+///let options = SetOptions::new().nx().expire(Expiry::Seconds(60));
+///```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SetOptions {
+    condition: Option<&'static str>,
+    expiry: Option<SetExpiry>,
+}
+
+//Setting an expiry and keeping the existing one are mutually exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SetExpiry {
+    Expiry(Expiry),
+    KeepTtl,
+}
+
+impl SetOptions {
+    ///Create a new instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Only set the key if it doesn't already exist.
+    pub fn nx(mut self) -> Self {
+        self.condition = Some("NX");
+        self
+    }
+
+    ///Only set the key if it already exists.
+    pub fn xx(mut self) -> Self {
+        self.condition = Some("XX");
+        self
+    }
+
+    ///Set the key to expire at `expiry`. Replaces any previous call to
+    ///[`keep_ttl`](struct.SetOptions.html#method.keep_ttl).
+    pub fn expire(mut self, expiry: Expiry) -> Self {
+        self.expiry = Some(SetExpiry::Expiry(expiry));
+        self
+    }
+
+    ///Keep the existing expiry of the key, instead of removing it. Replaces any previous call to
+    ///[`expire`](struct.SetOptions.html#method.expire).
+    pub fn keep_ttl(mut self) -> Self {
+        self.expiry = Some(SetExpiry::KeepTtl);
+        self
+    }
+
+    pub(crate) fn build(&self, command: &mut Command) {
+        if let Some(condition) = self.condition {
+            command.append_arg(condition);
+        }
+        match self.expiry {
+            Some(SetExpiry::Expiry(expiry)) => expiry.build(command),
+            Some(SetExpiry::KeepTtl) => command.append_arg("KEEPTTL"),
+            None => (),
+        }
+    }
+}
+
+///A condition for setting an expiry, used by
+///[`Connection::hexpire`](struct.Connection.html#method.hexpire) and
+///[`Connection::hpexpire`](struct.Connection.html#method.hpexpire).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpireCondition {
+    ///Only set the expiry if there is none.
+    Nx,
+    ///Only set the expiry if there already is one.
+    Xx,
+    ///Only set the expiry if it is later than the current one. No expiry counts as infinite.
+    Gt,
+    ///Only set the expiry if it is earlier than the current one. No expiry counts as infinite.
+    Lt,
+}
+
+impl ExpireCondition {
+    pub(crate) fn as_arg(self) -> &'static str {
+        match self {
+            ExpireCondition::Nx => "NX",
+            ExpireCondition::Xx => "XX",
+            ExpireCondition::Gt => "GT",
+            ExpireCondition::Lt => "LT",
+        }
+    }
+}
+
+///A builder for the [`SORT`](https://redis.io/commands/sort) command, used with
+///[`Connection::sort`](struct.Connection.html#method.sort),
+///[`Connection::sort_ro`](struct.Connection.html#method.sort_ro) and
+///[`Connection::sort_store`](struct.Connection.html#method.sort_store). By default the elements
+///are sorted numerically in ascending order.
+///# Example
+///```
+///# use darkredis::SortBuilder;
+/// //Sort user IDs by the name stored in each user's hash, returning the names
+///let builder = SortBuilder::new()
+///    .by("user:*->name")
+///    .get("user:*->name")
+///    .alpha()
+///    .limit(0, 10);
+///```
+#[derive(Debug, Clone, Default)]
+pub struct SortBuilder<'a> {
+    by: Option<&'a [u8]>,
+    limit: Option<(isize, isize)>,
+    get: Vec<&'a [u8]>,
+    desc: bool,
+    alpha: bool,
+}
+
+impl<'a> SortBuilder<'a> {
+    ///Create a new instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Sort by the values of the keys matching `pattern`, where the first `*` is replaced by each
+    ///element and `->field` refers to a hash field. A pattern without `*` skips sorting.
+    pub fn by<P>(mut self, pattern: &'a P) -> Self
+    where
+        P: AsRef<[u8]> + ?Sized,
+    {
+        self.by = Some(pattern.as_ref());
+        self
+    }
+
+    ///Skip `offset` elements and return at most `count` elements.
+    pub fn limit(mut self, offset: isize, count: isize) -> Self {
+        self.limit = Some((offset, count));
+        self
+    }
+
+    ///Return the value of the key matching `pattern` instead of each element, using the same
+    ///syntax as [`by`](struct.SortBuilder.html#method.by). `#` returns the element itself. When
+    ///called multiple times, every value is returned for each element, in order.
+    pub fn get<P>(mut self, pattern: &'a P) -> Self
+    where
+        P: AsRef<[u8]> + ?Sized,
+    {
+        self.get.push(pattern.as_ref());
+        self
+    }
+
+    ///Sort in descending order.
+    pub fn desc(mut self) -> Self {
+        self.desc = true;
+        self
+    }
+
+    ///Sort lexicographically instead of numerically.
+    pub fn alpha(mut self) -> Self {
+        self.alpha = true;
+        self
+    }
+
+    pub(crate) fn build(&self, command: &mut Command<'a>) {
+        if let Some(by) = self.by {
+            command.append_arg("BY");
+            command.append_arg(by);
+        }
+        if let Some((offset, count)) = self.limit {
+            command.append_arg("LIMIT");
+            command.append_arg(offset);
+            command.append_arg(count);
+        }
+        for pattern in &self.get {
+            command.append_arg("GET");
+            command.append_arg(*pattern);
+        }
+        if self.desc {
+            command.append_arg("DESC");
+        }
+        if self.alpha {
+            command.append_arg("ALPHA");
+        }
+    }
+}
+
+///Options for the [`RESTORE`](https://redis.io/commands/restore) command, used with
+///[`Connection::restore`](struct.Connection.html#method.restore). By default restoring fails if
+///the key already exists.
+///# Example
+///```
+///# use darkredis::RestoreOptions;
+/// //Overwrite the key, treating the TTL as a unix timestamp in milliseconds
+///let options = RestoreOptions::new().replace().absttl();
+///```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RestoreOptions {
+    replace: bool,
+    absttl: bool,
+    idletime: Option<u64>,
+    freq: Option<u64>,
+}
+
+impl RestoreOptions {
+    ///Create a new instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Replace the key if it already exists.
+    pub fn replace(mut self) -> Self {
+        self.replace = true;
+        self
+    }
+
+    ///Interpret the TTL as an absolute unix timestamp in milliseconds instead of a duration.
+    pub fn absttl(mut self) -> Self {
+        self.absttl = true;
+        self
+    }
+
+    ///Set the idle time of the restored key to `seconds`, used by the LRU eviction policies.
+    pub fn idletime(mut self, seconds: u64) -> Self {
+        self.idletime = Some(seconds);
+        self
+    }
+
+    ///Set the access frequency of the restored key, used by the LFU eviction policies.
+    pub fn freq(mut self, frequency: u64) -> Self {
+        self.freq = Some(frequency);
+        self
+    }
+
+    pub(crate) fn build(&self, command: &mut Command) {
+        if self.replace {
+            command.append_arg("REPLACE");
+        }
+        if self.absttl {
+            command.append_arg("ABSTTL");
+        }
+        if let Some(seconds) = self.idletime {
+            command.append_arg("IDLETIME");
+            command.append_arg(seconds);
+        }
+        if let Some(frequency) = self.freq {
+            command.append_arg("FREQ");
+            command.append_arg(frequency);
+        }
+    }
+}
+
+///Options for the [`MIGRATE`](https://redis.io/commands/migrate) command, used with
+///[`Connection::migrate`](struct.Connection.html#method.migrate). By default the keys are removed
+///from the source instance, and migrating fails if any of them exist on the destination.
+///# Example
+///```
+///# use darkredis::MigrateOptions;
+/// //Keep the keys on the source, authenticating as `admin` on the destination
+///let options = MigrateOptions::new().copy().auth2("admin", "hunter2");
+///```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MigrateOptions<'a> {
+    copy: bool,
+    replace: bool,
+    auth: Option<(Option<&'a [u8]>, &'a [u8])>,
+}
+
+impl<'a> MigrateOptions<'a> {
+    ///Create a new instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Keep the keys on the source instance.
+    pub fn copy(mut self) -> Self {
+        self.copy = true;
+        self
+    }
+
+    ///Replace keys which already exist on the destination instance.
+    pub fn replace(mut self) -> Self {
+        self.replace = true;
+        self
+    }
+
+    ///Authenticate to the destination instance using `password`.
+    pub fn auth<P>(mut self, password: &'a P) -> Self
+    where
+        P: AsRef<[u8]> + ?Sized,
+    {
+        self.auth = Some((None, password.as_ref()));
+        self
+    }
+
+    ///Authenticate to the destination instance as `username`, using ACLs.
+    pub fn auth2<U, P>(mut self, username: &'a U, password: &'a P) -> Self
+    where
+        U: AsRef<[u8]> + ?Sized,
+        P: AsRef<[u8]> + ?Sized,
+    {
+        self.auth = Some((Some(username.as_ref()), password.as_ref()));
+        self
+    }
+
+    pub(crate) fn build(&self, command: &mut Command<'a>) {
+        if self.copy {
+            command.append_arg("COPY");
+        }
+        if self.replace {
+            command.append_arg("REPLACE");
+        }
+        match self.auth {
+            Some((Some(username), password)) => {
+                command.append_arg("AUTH2");
+                command.append_arg(username);
+                command.append_arg(password);
+            }
+            Some((None, password)) => {
+                command.append_arg("AUTH");
+                command.append_arg(password);
+            }
+            None => (),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use super::{scan::ScanStream, Connection};
+use crate::{CommandList, Error, FromValue, Result, Ttl, Value};
+use futures::{
+    task::{Context, Poll},
+    Future, FutureExt, Stream, StreamExt,
+};
+use std::{collections::HashSet, pin::Pin};
+
+///The progress of copying keys using
+///[`Connection::copy_keys_to`](struct.Connection.html#method.copy_keys_to), returned once for every
+///batch.
+#[derive(Debug)]
+pub struct CopyProgress {
+    ///The keys copied in this batch.
+    pub keys: Vec<Vec<u8>>,
+    ///The keys in this batch which couldn't be copied, together with the error returned by Redis.
+    ///This includes keys which already exist on the destination if `replace` is false.
+    pub failed: Vec<(Vec<u8>, Error)>,
+    ///The total number of keys copied so far.
+    pub copied: usize,
+    ///The total number of keys skipped so far, because they were deleted or expired after being
+    ///scanned.
+    pub skipped: usize,
+}
+
+struct CopyState<'a> {
+    keys: ScanStream<'a>,
+    seen: HashSet<Vec<u8>>,
+    source: Connection,
+    destination: Connection,
+    batch_size: usize,
+    replace: bool,
+    copied: usize,
+    skipped: usize,
+}
+
+impl<'a> CopyState<'a> {
+    //Copy the next batch of keys, handing the state back for the next call.
+    async fn next_batch(mut self) -> (Self, Option<Result<CopyProgress>>) {
+        let result = self.copy_batch().await.transpose();
+        (self, result)
+    }
+
+    async fn copy_batch(&mut self) -> Result<Option<CopyProgress>> {
+        //SCAN may return the same key multiple times, which should only be copied once.
+        let mut batch = Vec::with_capacity(self.batch_size);
+        while batch.len() < self.batch_size {
+            match self.keys.next().await {
+                Some(key) => {
+                    let key = key?;
+                    if self.seen.insert(key.clone()) {
+                        batch.push(key);
+                    }
+                }
+                None => break,
+            }
+        }
+        if batch.is_empty() {
+            return Ok(None);
+        }
+
+        let mut commands = CommandList::new("PTTL");
+        for (i, key) in batch.iter().enumerate() {
+            if i > 0 {
+                commands.append_command("PTTL");
+            }
+            commands.append_arg(key);
+            commands.append_command("DUMP");
+            commands.append_arg(key);
+        }
+        //Every reply has to be read before returning, to leave the connection in a usable state.
+        let replies: Vec<_> = self.source.run_commands(commands).await?.collect().await;
+        let mut replies = replies.into_iter();
+
+        let mut dumped = Vec::with_capacity(batch.len());
+        let mut failed = Vec::new();
+        for key in batch {
+            let (ttl, payload) = match (replies.next(), replies.next()) {
+                (Some(ttl), Some(payload)) => (ttl, payload),
+                _ => unreachable!("one reply is received for every command"),
+            };
+            match dump_reply(ttl, payload) {
+                Ok(Some((ttl_ms, payload))) => dumped.push((key, ttl_ms, payload)),
+                Ok(None) => self.skipped += 1,
+                Err(e) => failed.push((key, e)),
+            }
+        }
+
+        let mut keys = Vec::with_capacity(dumped.len());
+        if !dumped.is_empty() {
+            let mut commands = CommandList::new("RESTORE");
+            for (i, (key, ttl_ms, payload)) in dumped.iter().enumerate() {
+                if i > 0 {
+                    commands.append_command("RESTORE");
+                }
+                commands.append_arg(key);
+                commands.append_arg(*ttl_ms);
+                commands.append_arg(payload);
+                if self.replace {
+                    commands.append_arg("REPLACE");
+                }
+            }
+            let results: Vec<_> = self
+                .destination
+                .run_commands(commands)
+                .await?
+                .collect()
+                .await;
+
+            for ((key, _, _), result) in dumped.into_iter().zip(results) {
+                match result {
+                    Ok(_) => keys.push(key),
+                    Err(e) => failed.push((key, e)),
+                }
+            }
+            self.copied += keys.len();
+        }
+
+        Ok(Some(CopyProgress {
+            keys,
+            failed,
+            copied: self.copied,
+            skipped: self.skipped,
+        }))
+    }
+}
+
+//Get the TTL to restore a key with and its serialized value, or `None` if it no longer exists.
+fn dump_reply(ttl: Result<Value>, payload: Result<Value>) -> Result<Option<(u64, Vec<u8>)>> {
+    let ttl_ms = match Ttl::from_value(ttl?)? {
+        Ttl::Persistent => 0,
+        //A TTL of 0 would make the restored key persistent.
+        Ttl::Expires(ms) if ms > 0 => ms,
+        _ => return Ok(None),
+    };
+    Ok(Option::<Vec<u8>>::from_value(payload?)?.map(|payload| (ttl_ms, payload)))
+}
+
+type CopyFuture<'a> =
+    Pin<Box<dyn Future<Output = (CopyState<'a>, Option<Result<CopyProgress>>)> + Send + 'a>>;
+
+///A stream copying keys to another connection, created using
+///[`Connection::copy_keys_to`](struct.Connection.html#method.copy_keys_to). Returns the progress
+///after every batch, and ends once every key has been scanned or after returning an error. Keys
+///which can't be copied are reported in
+///[`CopyProgress::failed`](struct.CopyProgress.html#structfield.failed) without ending the stream,
+///while errors such as a closed connection end it.
+#[must_use = "No keys will be copied if left unused"]
+#[allow(missing_debug_implementations)]
+pub struct CopyKeysStream<'a> {
+    poll_future: Option<CopyFuture<'a>>,
+}
+
+impl<'a> CopyKeysStream<'a> {
+    pub(crate) fn new(
+        keys: ScanStream<'a>,
+        source: Connection,
+        destination: &'a mut Connection,
+        batch_size: usize,
+        replace: bool,
+    ) -> Self {
+        let state = CopyState {
+            keys,
+            seen: HashSet::new(),
+            source,
+            destination: destination.clone(),
+            batch_size: batch_size.max(1),
+            replace,
+            copied: 0,
+            skipped: 0,
+        };
+        Self {
+            poll_future: Some(state.next_batch().boxed()),
+        }
+    }
+}
+
+impl<'a> Stream for CopyKeysStream<'a> {
+    type Item = Result<CopyProgress>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let (state, result) = match self.poll_future.as_mut() {
+            Some(future) => match future.as_mut().poll(cx) {
+                Poll::Ready(output) => output,
+                Poll::Pending => return Poll::Pending,
+            },
+            None => return Poll::Ready(None),
+        };
+        self.poll_future = match result {
+            Some(Ok(_)) => Some(state.next_batch().boxed()),
+            _ => None,
+        };
+
+        Poll::Ready(result)
+    }
+}
@@ -0,0 +1,708 @@
+use crate::{Error, Result, Value};
+
+//Unwrap the result of a parsing step, returning early if the buffer does not contain enough data yet.
+macro_rules! complete {
+    ($e:expr) => {
+        match $e? {
+            Some(v) => v,
+            None => return Ok(None),
+        }
+    };
+}
+
+//Maximum nesting depth of aggregate values, to prevent malicious or broken replies from overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+///Parser for values in the Redis protocol. Works on a buffer of bytes received from Redis and never
+///consumes anything unless a complete value is available. Invalid data results in an
+///[`Error::Protocol`](enum.Error.html#variant.Protocol) and never a panic.
+#[derive(Debug)]
+pub(crate) struct Parser<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    ///The number of bytes consumed by the parser so far.
+    #[inline]
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    ///Try to parse a single value from the buffer. Returns `Ok(None)` if the buffer does not contain
+    ///a complete value, in which case more data has to be read before trying again. Error replies
+    ///from Redis are returned as [`Value::Error`](enum.Value.html#Error.v), even at the top level.
+    ///Both RESP2 and RESP3 values are accepted.
+    pub(crate) fn parse(&mut self) -> Result<Option<Value>> {
+        self.parse_value(0)
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<Option<Value>> {
+        let start = self.pos;
+        let line = complete!(self.line());
+        if line.is_empty() {
+            return Err(Self::invalid(line, start));
+        }
+        //Aggregate types are the only ones which can nest
+        if b"*%~>|".contains(&line[0]) && depth >= MAX_DEPTH {
+            return Err(Self::invalid(line, start));
+        }
+
+        let value = match line[0] {
+            b'+' => {
+                if &line[1..] == b"OK" {
+                    Value::Ok
+                } else {
+                    Value::Status(String::from_utf8_lossy(&line[1..]).to_string())
+                }
+            }
+            b'-' => Value::Error(String::from_utf8_lossy(&line[1..]).to_string()),
+            b':' => Value::Integer(Self::parse_number(line, start)?),
+            b'$' => match Self::parse_length(line, start)? {
+                Some(len) => Value::String(complete!(self.bulk(len)).into()),
+                None => Value::Nil,
+            },
+            // result can be negative (blpop/brpop return '-1' on timeout)
+            b'*' => match Self::parse_length(line, start)? {
+                Some(len) => Value::Array(complete!(self.aggregate(len, depth))),
+                None => Value::Nil,
+            },
+            b'_' if line.len() == 1 => Value::Nil,
+            b'#' => match &line[1..] {
+                b"t" => Value::Boolean(true),
+                b"f" => Value::Boolean(false),
+                _ => return Err(Self::invalid(line, start)),
+            },
+            b',' => Value::Double(Self::parse_double(line, start)?),
+            b'(' => {
+                let digits = line[1..].strip_prefix(b"-").unwrap_or(&line[1..]);
+                if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+                    return Err(Self::invalid(line, start));
+                }
+                Value::BigNumber(String::from_utf8_lossy(&line[1..]).to_string())
+            }
+            b'!' => {
+                let len =
+                    Self::parse_length(line, start)?.ok_or_else(|| Self::invalid(line, start))?;
+                let message = complete!(self.bulk(len));
+                Value::Error(String::from_utf8_lossy(message).to_string())
+            }
+            b'=' => {
+                let len =
+                    Self::parse_length(line, start)?.ok_or_else(|| Self::invalid(line, start))?;
+                let data = complete!(self.bulk(len));
+                //Verbatim strings start with a three character format followed by a colon
+                if data.len() < 4 || data[3] != b':' {
+                    return Err(Self::invalid(data, start + line.len() + 2));
+                }
+                Value::Verbatim {
+                    format: String::from_utf8_lossy(&data[..3]).to_string(),
+                    text: data[4..].to_vec(),
+                }
+            }
+            b'%' => {
+                let len = Self::parse_pair_count(line, start)?;
+                Value::Map(Self::pairs(complete!(self.aggregate(len, depth))))
+            }
+            b'~' => {
+                let len =
+                    Self::parse_length(line, start)?.ok_or_else(|| Self::invalid(line, start))?;
+                Value::Set(complete!(self.aggregate(len, depth)))
+            }
+            b'>' => {
+                let len =
+                    Self::parse_length(line, start)?.ok_or_else(|| Self::invalid(line, start))?;
+                Value::Push(complete!(self.aggregate(len, depth)))
+            }
+            b'|' => {
+                //Attributes are sent ahead of the value they describe
+                let len = Self::parse_pair_count(line, start)?;
+                let attributes = Self::pairs(complete!(self.aggregate(len, depth)));
+                let data = complete!(self.parse_value(depth + 1));
+                Value::Attribute {
+                    data: Box::new(data),
+                    attributes,
+                }
+            }
+            _ => return Err(Self::invalid(line, start)),
+        };
+
+        Ok(Some(value))
+    }
+
+    //Parse `len` values of an aggregate type which is nested `depth` levels deep.
+    fn aggregate(&mut self, len: usize, depth: usize) -> Result<Option<Vec<Value>>> {
+        //Don't trust the length to preallocate, the elements still have to be received.
+        let mut values = Vec::with_capacity(len.min(self.buf.len() - self.pos));
+        for _ in 0..len {
+            values.push(complete!(self.parse_value(depth + 1)));
+        }
+
+        Ok(Some(values))
+    }
+
+    //Group a list of alternating keys and values into pairs.
+    fn pairs(values: Vec<Value>) -> Vec<(Value, Value)> {
+        let mut out = Vec::with_capacity(values.len() / 2);
+        let mut iter = values.into_iter();
+        while let (Some(key), Some(value)) = (iter.next(), iter.next()) {
+            out.push((key, value));
+        }
+        out
+    }
+
+    //Read a line terminated by \r\n, not including the terminator.
+    fn line(&mut self) -> Result<Option<&'a [u8]>> {
+        let rest = &self.buf[self.pos..];
+        match rest.iter().position(|b| *b == b'\n') {
+            Some(end) => match rest[..end].strip_suffix(b"\r") {
+                Some(line) => {
+                    self.pos += end + 1;
+                    Ok(Some(line))
+                }
+                None => Err(Self::invalid(&rest[..=end], self.pos)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    //Read `len` bytes of bulk data followed by \r\n.
+    fn bulk(&mut self, len: usize) -> Result<Option<&'a [u8]>> {
+        let rest = &self.buf[self.pos..];
+        match rest.get(len..len.saturating_add(2)) {
+            Some(b"\r\n") => {
+                self.pos += len + 2;
+                Ok(Some(&rest[..len]))
+            }
+            Some(terminator) => Err(Self::invalid(terminator, self.pos + len)),
+            None => Ok(None),
+        }
+    }
+
+    //Parse the number following the type byte of `line`, which starts at `position`.
+    fn parse_number(line: &[u8], position: usize) -> Result<isize> {
+        std::str::from_utf8(&line[1..])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| Self::invalid(line, position))
+    }
+
+    //Parse the floating point number following the type byte of `line`, which starts at `position`.
+    fn parse_double(line: &[u8], position: usize) -> Result<f64> {
+        std::str::from_utf8(&line[1..])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| Self::invalid(line, position))
+    }
+
+    //Parse the length of a bulk string or array, where -1 means that the value is nil.
+    fn parse_length(line: &[u8], position: usize) -> Result<Option<usize>> {
+        match Self::parse_number(line, position)? {
+            -1 => Ok(None),
+            len if len >= 0 => Ok(Some(len as usize)),
+            _ => Err(Self::invalid(line, position)),
+        }
+    }
+
+    //Parse the number of key-value pairs in a map or attribute, returning the number of values.
+    fn parse_pair_count(line: &[u8], position: usize) -> Result<usize> {
+        Self::parse_length(line, position)?
+            .and_then(|len| len.checked_mul(2))
+            .ok_or_else(|| Self::invalid(line, position))
+    }
+
+    fn invalid(data: &[u8], position: usize) -> Error {
+        Error::Protocol(data.to_vec(), position)
+    }
+}
+
+///Checks whether a buffer contains a complete value without building it, which is much cheaper
+///than parsing it. Keeps its progress between calls, so that a value received in many small reads
+///is only scanned once, and only parsed once it's complete.
+#[derive(Debug)]
+pub(crate) struct Scanner {
+    //The end of the last complete element, relative to the start of the value.
+    pos: usize,
+    //The number of elements still missing from each aggregate being scanned, innermost last.
+    missing: Vec<usize>,
+}
+
+//The result of scanning a single element, not including the elements nested in it.
+enum Element {
+    Incomplete,
+    Invalid,
+    Complete { len: usize, children: usize },
+}
+
+impl Scanner {
+    pub(crate) fn new() -> Self {
+        Self {
+            pos: 0,
+            missing: vec![1],
+        }
+    }
+
+    ///Start scanning a new value.
+    pub(crate) fn reset(&mut self) {
+        self.pos = 0;
+        self.missing.clear();
+        self.missing.push(1);
+    }
+
+    ///Scan `buf`, which starts with the value being scanned and contains at least the data given
+    ///to earlier calls. Returns `true` once the value is complete, or as soon as it turns out to be
+    ///invalid, in which case the [`Parser`](struct.Parser.html) reports the error.
+    pub(crate) fn scan(&mut self, buf: &[u8]) -> bool {
+        while let Some(missing) = self.missing.last_mut() {
+            if *missing == 0 {
+                self.missing.pop();
+                continue;
+            }
+
+            match Self::element(&buf[self.pos..]) {
+                Element::Incomplete => return false,
+                Element::Invalid => return true,
+                Element::Complete { len, children } => {
+                    *missing -= 1;
+                    self.pos += len;
+                    if children > 0 {
+                        if self.missing.len() > MAX_DEPTH {
+                            return true;
+                        }
+                        self.missing.push(children);
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    fn element(buf: &[u8]) -> Element {
+        let end = match buf.iter().position(|b| *b == b'\n') {
+            Some(end) => end,
+            None => return Element::Incomplete,
+        };
+        let header = end + 1;
+        let (kind, rest) = match buf[..end].strip_suffix(b"\r").and_then(<[u8]>::split_first) {
+            Some((kind, rest)) => (*kind, rest),
+            None => return Element::Invalid,
+        };
+        let len = std::str::from_utf8(rest)
+            .ok()
+            .and_then(|s| s.parse::<isize>().ok());
+
+        match (kind, len) {
+            (b'$', Some(-1)) | (b'*', Some(-1)) => Element::Complete {
+                len: header,
+                children: 0,
+            },
+            (b'$', Some(len)) | (b'!', Some(len)) | (b'=', Some(len)) if len >= 0 => {
+                match header.checked_add(len as usize + 2) {
+                    Some(total) if total <= buf.len() => Element::Complete {
+                        len: total,
+                        children: 0,
+                    },
+                    Some(_) => Element::Incomplete,
+                    None => Element::Invalid,
+                }
+            }
+            (b'*', Some(len)) | (b'~', Some(len)) | (b'>', Some(len)) if len >= 0 => {
+                Element::Complete {
+                    len: header,
+                    children: len as usize,
+                }
+            }
+            //Attributes are followed by the value they describe, which takes their place
+            (b'%', Some(len)) | (b'|', Some(len)) if len >= 0 => {
+                let extra = if kind == b'|' { 1 } else { 0 };
+                match (len as usize).checked_mul(2) {
+                    Some(children) => Element::Complete {
+                        len: header,
+                        children: children + extra,
+                    },
+                    None => Element::Invalid,
+                }
+            }
+            (b'$', _)
+            | (b'!', _)
+            | (b'=', _)
+            | (b'*', _)
+            | (b'~', _)
+            | (b'>', _)
+            | (b'%', _)
+            | (b'|', _) => Element::Invalid,
+            _ => Element::Complete {
+                len: header,
+                children: 0,
+            },
+        }
+    }
+}
+
+///Workaround for benchmarking the parser, parses a single complete value from `input`.
+#[cfg(feature = "bench")]
+pub fn parse_bench(input: &[u8]) -> Result<Value> {
+    Ok(Parser::new(input)
+        .parse()?
+        .expect("benchmark input should be a complete value"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn parse_complete(input: &[u8]) -> (Value, usize) {
+        let mut parser = Parser::new(input);
+        let value = parser.parse().unwrap().expect("incomplete value");
+        (value, parser.position())
+    }
+
+    #[test]
+    fn simple_values() {
+        let (value, len) = parse_complete(b"+OK\r\n");
+        assert_eq!(value, Value::Ok);
+        assert_eq!(len, 5);
+
+        let (value, len) = parse_complete(b"+PONG\r\n");
+        assert_eq!(value, Value::Status("PONG".to_string()));
+        assert_eq!(len, 7);
+
+        let (value, _) = parse_complete(b":-42\r\n");
+        assert_eq!(value, Value::Integer(-42));
+
+        let (value, _) = parse_complete(b"-ERR something\r\n");
+        assert_eq!(value, Value::Error("ERR something".to_string()));
+    }
+
+    #[test]
+    fn bulk_strings() {
+        let (value, len) = parse_complete(b"$5\r\nhe\r\no\r\n");
+        assert_eq!(value, Value::String(b"he\r\no".to_vec()));
+        assert_eq!(len, 11);
+
+        let (value, _) = parse_complete(b"$-1\r\n");
+        assert_eq!(value, Value::Nil);
+    }
+
+    #[test]
+    fn nested_arrays() {
+        let input = b"*3\r\n:1\r\n*2\r\n$3\r\nfoo\r\n$-1\r\n*-1\r\n";
+        let (value, len) = parse_complete(input);
+        assert_eq!(
+            value,
+            Value::Array(vec![
+                Value::Integer(1),
+                Value::Array(vec![Value::String(b"foo".to_vec()), Value::Nil]),
+                Value::Nil
+            ])
+        );
+        assert_eq!(len, input.len());
+    }
+
+    #[test]
+    fn resp3_values() {
+        let cases: &[(&[u8], Value)] = &[
+            (b"_\r\n", Value::Nil),
+            (b"#t\r\n", Value::Boolean(true)),
+            (b"#f\r\n", Value::Boolean(false)),
+            (b",1.5\r\n", Value::Double(1.5)),
+            (b",-inf\r\n", Value::Double(f64::NEG_INFINITY)),
+            (
+                b"(3492890328409238509324850943850943825024385\r\n",
+                Value::BigNumber("3492890328409238509324850943850943825024385".to_string()),
+            ),
+            (
+                b"=15\r\ntxt:Some string\r\n",
+                Value::Verbatim {
+                    format: "txt".to_string(),
+                    text: b"Some string".to_vec(),
+                },
+            ),
+            (
+                b"%2\r\n+first\r\n:1\r\n$6\r\nsecond\r\n#f\r\n",
+                Value::Map(vec![
+                    (Value::Status("first".to_string()), Value::Integer(1)),
+                    (Value::String(b"second".to_vec()), Value::Boolean(false)),
+                ]),
+            ),
+            (
+                b"~2\r\n:1\r\n:2\r\n",
+                Value::Set(vec![Value::Integer(1), Value::Integer(2)]),
+            ),
+            (
+                b">2\r\n$7\r\nmessage\r\n$3\r\nfoo\r\n",
+                Value::Push(vec![
+                    Value::String(b"message".to_vec()),
+                    Value::String(b"foo".to_vec()),
+                ]),
+            ),
+            (
+                b"|1\r\n$3\r\nttl\r\n:3600\r\n:42\r\n",
+                Value::Attribute {
+                    data: Box::new(Value::Integer(42)),
+                    attributes: vec![(Value::String(b"ttl".to_vec()), Value::Integer(3600))],
+                },
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let (value, len) = parse_complete(input);
+            assert_eq!(&value, expected);
+            assert_eq!(len, input.len());
+        }
+
+        let (value, _) = parse_complete(b"!21\r\nSYNTAX invalid syntax\r\n");
+        assert_eq!(value, Value::Error("SYNTAX invalid syntax".to_string()));
+    }
+
+    #[test]
+    fn error_in_array() {
+        let input = b"*3\r\n-ERR first\r\n:1\r\n-ERR second\r\n+OK\r\n";
+        let (value, len) = parse_complete(input);
+        assert_eq!(
+            value,
+            Value::Array(vec![
+                Value::Error("ERR first".to_string()),
+                Value::Integer(1),
+                Value::Error("ERR second".to_string()),
+            ])
+        );
+        assert_eq!(&input[len..], b"+OK\r\n");
+    }
+
+    #[test]
+    fn incomplete() {
+        let input = b"*2\r\n$3\r\nfoo\r\n$3\r\nbar\r\n";
+        for end in 0..input.len() {
+            let mut parser = Parser::new(&input[..end]);
+            assert!(parser.parse().unwrap().is_none(), "parsed at {}", end);
+        }
+    }
+
+    #[test]
+    fn invalid_data() {
+        let cases: &[(&[u8], &[u8], usize)] = &[
+            (b"?foo\r\n", b"?foo", 0),
+            (b"\r\n", b"", 0),
+            (b":12a\r\n", b":12a", 0),
+            (b"$-2\r\n", b"$-2", 0),
+            (b"*-5\r\n", b"*-5", 0),
+            (b"$3\r\nfoobar\r\n", b"ba", 7),
+            (b"*2\r\n:1\r\n:1\n", b":1\n", 8),
+            (
+                b"*1\r\n$99999999999999999999999\r\n",
+                b"$99999999999999999999999",
+                4,
+            ),
+        ];
+
+        for (input, data, position) in cases {
+            match Parser::new(input).parse() {
+                Err(Error::Protocol(d, p)) => {
+                    assert_eq!(&d, data);
+                    assert_eq!(p, *position);
+                }
+                other => panic!("expected protocol error for {:?}, got {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let input = b"*1\r\n".repeat(100_000);
+        assert!(matches!(
+            Parser::new(&input).parse(),
+            Err(Error::Protocol(_, _))
+        ));
+    }
+
+    #[test]
+    fn multiple_values() {
+        let input = b":1\r\n:2\r\n";
+        let mut parser = Parser::new(input);
+        assert_eq!(parser.parse().unwrap().unwrap(), Value::Integer(1));
+        assert_eq!(parser.parse().unwrap().unwrap(), Value::Integer(2));
+        assert!(parser.parse().unwrap().is_none());
+    }
+
+    #[test]
+    fn scanner() {
+        let input =
+            b"*3\r\n:1\r\n%1\r\n$3\r\nfoo\r\n|1\r\n+a\r\n:1\r\n=7\r\ntxt:bar\r\n$-1\r\n+OK\r\n";
+        let len = input.len() - 5;
+        let mut scanner = Scanner::new();
+        for end in 0..len {
+            assert!(!scanner.scan(&input[..end]), "complete at {}", end);
+        }
+        assert!(scanner.scan(&input[..len]));
+        assert_eq!(scanner.pos, len);
+        assert_eq!(parse_complete(input).1, len);
+
+        scanner.reset();
+        assert!(scanner.scan(b"*-1\r\n"));
+        scanner.reset();
+        assert!(scanner.scan(b"*0\r\n*2\r\n"));
+        assert_eq!(scanner.pos, 4);
+
+        //Invalid data is left to the parser
+        for input in [&b"$-2\r\n"[..], b"*a\r\n", b"+OK\n", &b"*1\r\n".repeat(200)].iter() {
+            scanner.reset();
+            assert!(scanner.scan(input));
+            assert!(Parser::new(input).parse().is_err());
+        }
+    }
+
+    fn encode(value: &Value, out: &mut Vec<u8>) {
+        match value {
+            Value::Ok => out.extend_from_slice(b"+OK\r\n"),
+            Value::Nil => out.extend_from_slice(b"$-1\r\n"),
+            Value::Status(s) => out.extend_from_slice(format!("+{}\r\n", s).as_bytes()),
+            Value::Error(e) => out.extend_from_slice(format!("-{}\r\n", e).as_bytes()),
+            Value::Integer(i) => out.extend_from_slice(format!(":{}\r\n", i).as_bytes()),
+            Value::String(s) => {
+                out.extend_from_slice(format!("${}\r\n", s.len()).as_bytes());
+                out.extend_from_slice(s);
+                out.extend_from_slice(b"\r\n");
+            }
+            Value::Array(a) => encode_aggregate(b'*', a, out),
+            Value::Map(m) => encode_pairs(b'%', m, out),
+            Value::Set(a) => encode_aggregate(b'~', a, out),
+            Value::Double(d) => out.extend_from_slice(format!(",{}\r\n", d).as_bytes()),
+            Value::Boolean(true) => out.extend_from_slice(b"#t\r\n"),
+            Value::Boolean(false) => out.extend_from_slice(b"#f\r\n"),
+            Value::BigNumber(n) => out.extend_from_slice(format!("({}\r\n", n).as_bytes()),
+            Value::Verbatim { format, text } => {
+                out.extend_from_slice(format!("={}\r\n{}:", text.len() + 4, format).as_bytes());
+                out.extend_from_slice(text);
+                out.extend_from_slice(b"\r\n");
+            }
+            Value::Attribute { data, attributes } => {
+                encode_pairs(b'|', attributes, out);
+                encode(data, out);
+            }
+            Value::Push(a) => encode_aggregate(b'>', a, out),
+        }
+    }
+
+    fn encode_aggregate(kind: u8, values: &[Value], out: &mut Vec<u8>) {
+        out.push(kind);
+        out.extend_from_slice(format!("{}\r\n", values.len()).as_bytes());
+        for v in values {
+            encode(v, out);
+        }
+    }
+
+    fn encode_pairs(kind: u8, pairs: &[(Value, Value)], out: &mut Vec<u8>) {
+        out.push(kind);
+        out.extend_from_slice(format!("{}\r\n", pairs.len()).as_bytes());
+        for (k, v) in pairs {
+            encode(k, out);
+            encode(v, out);
+        }
+    }
+
+    fn arbitrary_value() -> impl Strategy<Value = Value> {
+        let leaf = prop_oneof![
+            Just(Value::Ok),
+            Just(Value::Nil),
+            "[a-zA-Z ]{0,16}"
+                .prop_filter("OK is parsed as Value::Ok", |s| s != "OK")
+                .prop_map(Value::Status),
+            "[A-Z]{1,8} [a-z ]{0,16}".prop_map(Value::Error),
+            any::<isize>().prop_map(Value::Integer),
+            proptest::collection::vec(any::<u8>(), 0..32).prop_map(Value::String),
+            any::<f64>()
+                .prop_filter("NaN is not equal to itself", |f| !f.is_nan())
+                .prop_map(Value::Double),
+            any::<bool>().prop_map(Value::Boolean),
+            "-?[0-9]{1,64}".prop_map(Value::BigNumber),
+            ("[a-z]{3}", proptest::collection::vec(any::<u8>(), 0..32))
+                .prop_map(|(format, text)| Value::Verbatim { format, text }),
+        ];
+        leaf.prop_recursive(4, 64, 8, |inner| {
+            let pairs = proptest::collection::vec((inner.clone(), inner.clone()), 0..4);
+            prop_oneof![
+                proptest::collection::vec(inner.clone(), 0..8).prop_map(Value::Array),
+                proptest::collection::vec(inner.clone(), 0..8).prop_map(Value::Set),
+                proptest::collection::vec(inner.clone(), 0..8).prop_map(Value::Push),
+                pairs.clone().prop_map(Value::Map),
+                (inner, pairs).prop_map(|(data, attributes)| Value::Attribute {
+                    data: Box::new(data),
+                    attributes
+                }),
+            ]
+        })
+    }
+
+    //Parse values from `input` until it's exhausted or invalid, which must never panic.
+    fn parse_all(input: &[u8]) {
+        let mut parser = Parser::new(input);
+        while let Ok(Some(_)) = parser.parse() {
+            assert!(parser.position() <= input.len());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_bytes_never_panic(input in proptest::collection::vec(any::<u8>(), 0..512)) {
+            parse_all(&input);
+        }
+
+        #[test]
+        fn protocol_like_bytes_never_panic(
+            input in proptest::collection::vec(
+                prop_oneof![
+                    Just(b"\r\n".to_vec()),
+                    Just(b"*".to_vec()),
+                    Just(b"$".to_vec()),
+                    Just(b":".to_vec()),
+                    Just(b"+".to_vec()),
+                    Just(b"-".to_vec()),
+                    Just(b"%".to_vec()),
+                    Just(b"|".to_vec()),
+                    Just(b"=".to_vec()),
+                    Just(b",".to_vec()),
+                    any::<i8>().prop_map(|i| i.to_string().into_bytes()),
+                    proptest::collection::vec(any::<u8>(), 0..4),
+                ],
+                0..64,
+            )
+        ) {
+            parse_all(&input.concat());
+        }
+
+        #[test]
+        fn roundtrip(value in arbitrary_value()) {
+            let mut input = Vec::new();
+            encode(&value, &mut input);
+
+            let mut parser = Parser::new(&input);
+            prop_assert_eq!(parser.parse().unwrap().unwrap(), value);
+            prop_assert_eq!(parser.position(), input.len());
+
+            //Every prefix of a valid value is just incomplete, not invalid.
+            for end in 0..input.len() {
+                prop_assert!(Parser::new(&input[..end]).parse().unwrap().is_none());
+            }
+        }
+
+        #[test]
+        fn corrupted_values_never_panic(
+            value in arbitrary_value(),
+            index in any::<prop::sample::Index>(),
+            byte in any::<u8>(),
+        ) {
+            let mut input = Vec::new();
+            encode(&value, &mut input);
+            let index = index.index(input.len());
+            input[index] = byte;
+            parse_all(&input);
+        }
+    }
+}
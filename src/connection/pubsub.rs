@@ -0,0 +1,349 @@
+use super::{
+    stream::{Message, PMessage},
+    Connection,
+};
+use crate::{Command, CommandList, Error, FromValue, Result, Value};
+use futures::{
+    task::{Context, Poll},
+    Future, FutureExt, Stream,
+};
+use std::pin::Pin;
+
+//Sent after unsubscribing from everything, the reply to which is the last reply to be discarded.
+const UNSUBSCRIBED_TOKEN: &str = "darkredis-unsubscribed";
+
+///An event received by a [`PubSub`](struct.PubSub.html) connection.
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum PubSubEvent {
+    ///A message published to a subscribed channel.
+    Message(Message),
+    ///A message published to a channel matching a subscribed pattern.
+    PMessage(PMessage),
+    ///The connection subscribed to `channel`.
+    Subscribe {
+        ///The channel subscribed to.
+        channel: Vec<u8>,
+        ///The number of channels and patterns the connection is now subscribed to.
+        count: usize,
+    },
+    ///The connection unsubscribed from `channel`.
+    Unsubscribe {
+        ///The channel unsubscribed from, which is `None` when unsubscribing from every channel
+        ///without being subscribed to any.
+        channel: Option<Vec<u8>>,
+        ///The number of channels and patterns the connection is still subscribed to.
+        count: usize,
+    },
+    ///The connection subscribed to `pattern`.
+    PSubscribe {
+        ///The pattern subscribed to.
+        pattern: Vec<u8>,
+        ///The number of channels and patterns the connection is now subscribed to.
+        count: usize,
+    },
+    ///The connection unsubscribed from `pattern`.
+    PUnsubscribe {
+        ///The pattern unsubscribed from, which is `None` when unsubscribing from every pattern
+        ///without being subscribed to any.
+        pattern: Option<Vec<u8>>,
+        ///The number of channels and patterns the connection is still subscribed to.
+        count: usize,
+    },
+    ///The reply to [`PubSub::ping`](struct.PubSub.html#method.ping).
+    Pong,
+}
+
+impl FromValue for PubSubEvent {
+    fn from_value(value: Value) -> Result<Self> {
+        //Not subscribed to anything, or using RESP3
+        if let Value::Status(ref status) = value {
+            if status == "PONG" {
+                return Ok(PubSubEvent::Pong);
+            }
+        }
+
+        let mut fields = Vec::<Value>::from_value(value)?;
+        if fields.is_empty() {
+            return Err(Error::UnexpectedResponse("empty pub/sub event".into()));
+        }
+        let kind = String::from_value(fields.remove(0))?;
+        let fields = Value::Array(fields);
+        let event = match kind.as_str() {
+            "message" => {
+                let (channel, message) = FromValue::from_value(fields)?;
+                PubSubEvent::Message(Message { channel, message })
+            }
+            "pmessage" => {
+                let (pattern, channel, message) = FromValue::from_value(fields)?;
+                PubSubEvent::PMessage(PMessage {
+                    channel,
+                    message,
+                    pattern,
+                })
+            }
+            "subscribe" => {
+                let (channel, count) = FromValue::from_value(fields)?;
+                PubSubEvent::Subscribe { channel, count }
+            }
+            "unsubscribe" => {
+                let (channel, count) = FromValue::from_value(fields)?;
+                PubSubEvent::Unsubscribe { channel, count }
+            }
+            "psubscribe" => {
+                let (pattern, count) = FromValue::from_value(fields)?;
+                PubSubEvent::PSubscribe { pattern, count }
+            }
+            "punsubscribe" => {
+                let (pattern, count) = FromValue::from_value(fields)?;
+                PubSubEvent::PUnsubscribe { pattern, count }
+            }
+            "pong" => PubSubEvent::Pong,
+            other => {
+                return Err(Error::UnexpectedResponse(format!(
+                    "unknown pub/sub event {}",
+                    other
+                )))
+            }
+        };
+
+        Ok(event)
+    }
+}
+
+type ReadFuture = Pin<Box<dyn Future<Output = Result<Value>> + Send>>;
+
+///A connection used for Pub/Sub, created using
+///[`Connection::into_pubsub`](struct.Connection.html#method.into_pubsub). Unlike
+///[`MessageStream`](struct.MessageStream.html), channels and patterns can be subscribed to and
+///unsubscribed from at any time, and the connection can be used normally again afterwards using
+///[`into_connection`](struct.PubSub.html#method.into_connection).
+///
+///Polled as a stream, it returns every [`PubSubEvent`](enum.PubSubEvent.html) received, including
+///the confirmation of every subscription change. Errors are returned as items, and the stream ends
+///after the connection is closed or invalid data is received. Dropping a pending `next()` future does not lose any data, so
+///the stream can be polled together with other futures, such as in `futures::select!`.
+///# Example
+///```
+///use darkredis::{Connection, Message, PubSubEvent};
+///use futures::StreamExt;
+///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+///# async fn main() {
+///let mut publisher = Connection::connect("127.0.0.1:6379").await.unwrap();
+///let mut pubsub = Connection::connect("127.0.0.1:6379").await.unwrap().into_pubsub();
+///
+///pubsub.subscribe(&["pubsub-example"]).await.unwrap();
+///assert!(matches!(pubsub.next().await, Some(Ok(PubSubEvent::Subscribe { count: 1, .. }))));
+///
+///publisher.publish("pubsub-example", "hello").await.unwrap();
+///match pubsub.next().await {
+///    Some(Ok(PubSubEvent::Message(Message { message, .. }))) => assert_eq!(message, b"hello"),
+///    other => panic!("unexpected event {:?}", other),
+///}
+///
+///let mut connection = pubsub.into_connection().await.unwrap();
+///connection.ping().await.unwrap();
+///# }
+///```
+#[must_use = "No events will be received if left unused"]
+#[allow(missing_debug_implementations)]
+pub struct PubSub {
+    connection: Connection,
+    poll_future: Option<ReadFuture>,
+    closed: bool,
+}
+
+impl PubSub {
+    pub(crate) fn new(connection: Connection) -> Self {
+        Self {
+            connection,
+            poll_future: None,
+            closed: false,
+        }
+    }
+
+    fn create_poll_future(connection: Connection) -> ReadFuture {
+        async move {
+            let mut stream = connection.stream.lock().await;
+            stream.read_value().await
+        }
+        .boxed()
+    }
+
+    //Send `command` without waiting for a reply, which is received as an event instead.
+    async fn send(&mut self, command: Command<'_>) -> Result<()> {
+        //A pending read holds the lock on the connection. It is safe to cancel, as a value is only
+        //removed from the read buffer once it has been parsed completely.
+        self.poll_future = None;
+
+        let mut buffer = Vec::new();
+        command.serialize(&mut buffer);
+        let mut stream = self.connection.stream.lock().await;
+        stream.write_all(&buffer).await
+    }
+
+    ///Subscribe to `channels`. A [`PubSubEvent::Subscribe`](enum.PubSubEvent.html#variant.Subscribe)
+    ///event is received for every channel once subscribed.
+    pub async fn subscribe<K>(&mut self, channels: &[K]) -> Result<()>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(channels);
+        self.send(Command::new("SUBSCRIBE").args(channels)).await
+    }
+
+    ///Unsubscribe from `channels`. A
+    ///[`PubSubEvent::Unsubscribe`](enum.PubSubEvent.html#variant.Unsubscribe) event is received for
+    ///every channel once unsubscribed.
+    pub async fn unsubscribe<K>(&mut self, channels: &[K]) -> Result<()>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(channels);
+        self.send(Command::new("UNSUBSCRIBE").args(channels)).await
+    }
+
+    ///Unsubscribe from every channel.
+    pub async fn unsubscribe_all(&mut self) -> Result<()> {
+        self.send(Command::new("UNSUBSCRIBE")).await
+    }
+
+    ///Subscribe to channels matching `patterns`. A
+    ///[`PubSubEvent::PSubscribe`](enum.PubSubEvent.html#variant.PSubscribe) event is received for
+    ///every pattern once subscribed.
+    pub async fn psubscribe<K>(&mut self, patterns: &[K]) -> Result<()>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(patterns);
+        self.send(Command::new("PSUBSCRIBE").args(patterns)).await
+    }
+
+    ///Unsubscribe from `patterns`. A
+    ///[`PubSubEvent::PUnsubscribe`](enum.PubSubEvent.html#variant.PUnsubscribe) event is received
+    ///for every pattern once unsubscribed.
+    pub async fn punsubscribe<K>(&mut self, patterns: &[K]) -> Result<()>
+    where
+        K: AsRef<[u8]>,
+    {
+        check_slice_not_empty!(patterns);
+        self.send(Command::new("PUNSUBSCRIBE").args(patterns)).await
+    }
+
+    ///Unsubscribe from every pattern.
+    pub async fn punsubscribe_all(&mut self) -> Result<()> {
+        self.send(Command::new("PUNSUBSCRIBE")).await
+    }
+
+    ///Send a `PING`, which is answered by a [`PubSubEvent::Pong`](enum.PubSubEvent.html#variant.Pong)
+    ///event. Useful for checking that the connection is still alive while waiting for messages.
+    pub async fn ping(&mut self) -> Result<()> {
+        self.send(Command::new("PING")).await
+    }
+
+    ///Unsubscribe from every channel and pattern, and return the underlying connection once
+    ///Redis has confirmed it. Any events received in the meantime are discarded.
+    pub async fn into_connection(mut self) -> Result<Connection> {
+        self.poll_future = None;
+        let command = CommandList::new("UNSUBSCRIBE")
+            .command("PUNSUBSCRIBE")
+            .command("PING")
+            .arg(UNSUBSCRIBED_TOKEN);
+        let mut buffer = Vec::new();
+        command.serialize(&mut buffer);
+
+        let mut stream = self.connection.stream.lock().await;
+        stream.write_all(&buffer).await?;
+        //While subscribed, the reply to PING is a `pong` event instead of the token itself.
+        loop {
+            match stream.read_value().await {
+                Ok(Value::String(ref s)) if s == UNSUBSCRIBED_TOKEN.as_bytes() => break,
+                Ok(_) | Err(Error::RedisError(_)) => (),
+                Err(e) => return Err(e),
+            }
+        }
+        drop(stream);
+
+        Ok(self.connection)
+    }
+}
+
+impl Stream for PubSub {
+    type Item = Result<PubSubEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        if self.closed {
+            return Poll::Ready(None);
+        }
+
+        let connection = self.connection.clone();
+        let future = self
+            .poll_future
+            .get_or_insert_with(|| Self::create_poll_future(connection));
+        let result = match future.as_mut().poll(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        self.poll_future = None;
+
+        //Nothing more can be read after invalid data, as the start of the next value is unknown.
+        if let Err(Error::Io(_)) | Err(Error::Protocol(_, _)) = result {
+            self.closed = true;
+        }
+        Poll::Ready(Some(result.and_then(PubSubEvent::from_value)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::string;
+
+    #[test]
+    fn events() {
+        let value = Value::Array(vec![string("message"), string("ch"), string("hi")]);
+        assert_eq!(
+            PubSubEvent::from_value(value).unwrap(),
+            PubSubEvent::Message(Message {
+                channel: b"ch".to_vec(),
+                message: b"hi".to_vec(),
+            })
+        );
+
+        let value = Value::Push(vec![
+            string("pmessage"),
+            string("c*"),
+            string("ch"),
+            string("hi"),
+        ]);
+        assert_eq!(
+            PubSubEvent::from_value(value).unwrap(),
+            PubSubEvent::PMessage(PMessage {
+                channel: b"ch".to_vec(),
+                message: b"hi".to_vec(),
+                pattern: b"c*".to_vec(),
+            })
+        );
+
+        let value = Value::Array(vec![string("unsubscribe"), Value::Nil, Value::Integer(0)]);
+        assert_eq!(
+            PubSubEvent::from_value(value).unwrap(),
+            PubSubEvent::Unsubscribe {
+                channel: None,
+                count: 0
+            }
+        );
+
+        let value = Value::Array(vec![string("pong"), string("")]);
+        assert_eq!(PubSubEvent::from_value(value).unwrap(), PubSubEvent::Pong);
+        let value = Value::Status("PONG".into());
+        assert_eq!(PubSubEvent::from_value(value).unwrap(), PubSubEvent::Pong);
+
+        let value = Value::Array(vec![string("subscribe"), string("ch")]);
+        assert!(PubSubEvent::from_value(value).is_err());
+        let value = Value::Array(vec![string("smessage"), string("ch"), string("hi")]);
+        assert!(PubSubEvent::from_value(value).is_err());
+        assert!(PubSubEvent::from_value(Value::Array(vec![])).is_err());
+    }
+}
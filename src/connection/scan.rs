@@ -1,10 +1,10 @@
 use super::Connection;
-use crate::Command;
+use crate::{Command, FromValue, Result, Value};
 use futures::{
     task::{Context, Poll},
     FutureExt, Stream,
 };
-use std::{collections::VecDeque, future::Future, iter::Iterator, pin::Pin};
+use std::{collections::VecDeque, future::Future, pin::Pin};
 
 #[derive(Debug)]
 ///Builder to build a SCAN or SSCAN command. Borrows all its data.
@@ -61,10 +61,12 @@ impl<'a> ScanBuilder<'a> {
 }
 
 //Future used in ScanStream
-type ScanStreamFuture<'a> = Pin<Box<dyn Future<Output = (Vec<u8>, Vec<Vec<u8>>)> + Send + 'a>>;
+type ScanStreamFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<(Vec<u8>, Vec<Vec<u8>>)>> + Send + 'a>>;
 
 ///A Stream of results from running SCAN or SSCAN. The same value might appear multiple times. Polling
-///until the stream is empty will return all matched elements.
+///until the stream is empty will return all matched elements. The stream ends after returning an
+///error.
 #[must_use]
 #[allow(missing_debug_implementations)]
 pub struct ScanStream<'a> {
@@ -134,51 +136,40 @@ impl<'a> ScanStream<'a> {
                 command.append_arg(b"MATCH");
                 command.append_arg(pattern);
             }
-            let count = count.clone().map(|s| s.to_string()); // to fix an ownership problem
-            if let Some(ref count) = count {
+            if let Some(count) = count {
                 command.append_arg(b"COUNT");
                 command.append_arg(count);
             }
 
-            //Get the results as an iterator
-            //When using the stream, we cannot really return an error without making an awkward API.
-            //Therefore, it is okay to unwrap here as the scan commands will, at worst, return an empty list.
-            let mut result = conn
-                .run_command(command)
-                .await
-                .expect("Running SCAN command")
-                .unwrap_array()
-                .into_iter();
-
-            //Assume that the response is valid since we did not receive an error.
-            let cursor = result.next().expect("getting cursor field").unwrap_string();
-            let values = result
-                .next()
-                .expect("getting value array")
-                .unwrap_array()
-                .into_iter()
-                .map(|s| s.unwrap_string())
-                .collect();
-
-            (cursor, values)
+            //The reply is the next cursor followed by the values found.
+            conn.query(command).await
         }
         .boxed()
     }
 }
 
 impl<'a> Stream for ScanStream<'a> {
-    type Item = Vec<u8>;
+    type Item = Result<Vec<u8>>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        if let Some(v) = self.receive_buffer.pop_front() {
-            Poll::Ready(Some(v))
-        } else if self.last_cursor == b"0" {
-            //Redis returns a 0 cursor when done.
-            Poll::Ready(None)
-        } else {
+        //Redis may return no values for a batch without being done, so keep going until a value
+        //is found or the cursor is 0.
+        loop {
+            if let Some(v) = self.receive_buffer.pop_front() {
+                return Poll::Ready(Some(Ok(v)));
+            } else if self.last_cursor == b"0" {
+                //Redis returns a 0 cursor when done.
+                return Poll::Ready(None);
+            }
+
             match self.poll_future.as_mut().poll(cx) {
-                Poll::Pending => Poll::Pending,
-                Poll::Ready((cursor, fields)) => {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(e)) => {
+                    //Stop scanning, as there is no cursor to continue from.
+                    self.last_cursor = b"0".to_vec();
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Ready(Ok((cursor, fields))) => {
                     self.poll_future = Self::create_poll_future(
                         self.command,
                         self.key,
@@ -188,13 +179,9 @@ impl<'a> Stream for ScanStream<'a> {
                         self.connection.clone(),
                     );
 
-                    for f in fields {
-                        self.receive_buffer.push_back(f);
-                    }
+                    self.receive_buffer.extend(fields);
                     //Note what the last cursor is so we can exit when the receive buffer is empty.
                     self.last_cursor = cursor;
-                    //If the buffer is empty now, that means that no keys were found.
-                    Poll::Ready(self.receive_buffer.pop_front())
                 }
             }
         }
@@ -246,7 +233,8 @@ impl<'a> HScanBuilder<'a> {
 ///A Stream of results from running HSCAN. The same key might appear multiple times, and polling
 ///until the stream is empty will return all matching fields and values in the hash set.
 ///# Return Value
-///The field and its associated value in the form (field, value) as a tuple.
+///The field and its associated value in the form (field, value) as a tuple. The stream ends after
+///returning an error.
 #[must_use]
 #[allow(missing_debug_implementations)]
 pub struct HScanStream<'a> {
@@ -276,23 +264,121 @@ impl<'a> HScanStream<'a> {
 }
 
 impl<'a> Stream for HScanStream<'a> {
-    type Item = (Vec<u8>, Vec<u8>);
+    type Item = Result<(Vec<u8>, Vec<u8>)>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         match self.inner.as_mut().poll_next(cx) {
             Poll::Pending => Poll::Pending,
-            Poll::Ready(Some(v)) => {
+            Poll::Ready(Some(Ok(v))) => {
                 if self.current_field.is_some() {
                     let field = self.current_field.clone().unwrap();
                     self.current_field = None;
-                    Poll::Ready(Some((field, v)))
+                    Poll::Ready(Some(Ok((field, v))))
                 } else {
                     self.current_field = Some(v);
                     cx.waker().wake_by_ref();
                     Poll::Pending
                 }
             }
+            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e))),
             Poll::Ready(None) => Poll::Ready(None),
         }
     }
 }
+
+///Builder to build a ZSCAN command. Borrows all its data.
+#[derive(Debug)]
+pub struct ZScanBuilder<'a> {
+    connection: &'a mut Connection,
+    pattern: Option<&'a [u8]>,
+    key: &'a [u8],
+    count: Option<isize>,
+}
+
+impl<'a> ZScanBuilder<'a> {
+    pub(crate) fn new(key: &'a [u8], connection: &'a mut Connection) -> Self {
+        Self {
+            connection,
+            key,
+            pattern: None,
+            count: None,
+        }
+    }
+
+    ///Match members using `pattern`.
+    pub fn pattern<P>(mut self, pattern: &'a P) -> Self
+    where
+        P: AsRef<[u8]>,
+    {
+        self.pattern = Some(pattern.as_ref());
+        self
+    }
+
+    ///Return a maximum of `count` members per query to Redis. This does not limit the number of returned
+    ///members in the stream, see the Redis documentation on [`SCAN`](https://redis.io/commands/scan)
+    ///for more information.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count as isize);
+        self
+    }
+
+    ///Consume self and return a ZScanStream.
+    pub fn run(self) -> ZScanStream<'a> {
+        ZScanStream::new(self.key, self.pattern, self.count, self.connection)
+    }
+}
+
+///A Stream of results from running ZSCAN. The same member might appear multiple times, and polling
+///until the stream is empty will return all matching members of the sorted set.
+///# Return Value
+///The member and its score in the form (member, score) as a tuple. The stream ends after returning
+///an error.
+#[must_use]
+#[allow(missing_debug_implementations)]
+pub struct ZScanStream<'a> {
+    inner: Pin<Box<ScanStream<'a>>>,
+    current_member: Option<Vec<u8>>,
+}
+
+impl<'a> ZScanStream<'a> {
+    pub(crate) fn new(
+        key: &'a [u8],
+        pattern: Option<&'a [u8]>,
+        count: Option<isize>,
+        connection: &'a mut Connection,
+    ) -> Self {
+        let inner = Pin::new(Box::new(ScanStream::new(
+            "ZSCAN",
+            Some(key),
+            pattern,
+            count,
+            connection,
+        )));
+        Self {
+            inner,
+            current_member: None,
+        }
+    }
+}
+
+impl<'a> Stream for ZScanStream<'a> {
+    type Item = Result<(Vec<u8>, f64)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.inner.as_mut().poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Ok(v))) => match self.current_member.take() {
+                    Some(member) => {
+                        //Scores are sent as strings, including `inf` and `-inf`.
+                        let score = f64::from_value(Value::String(v));
+                        return Poll::Ready(Some(score.map(|score| (member, score))));
+                    }
+                    None => self.current_member = Some(v),
+                },
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => return Poll::Ready(None),
+            }
+        }
+    }
+}
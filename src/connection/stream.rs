@@ -1,4 +1,8 @@
-use super::{Connection, Result, Value};
+use super::{
+    pubsub::{PubSub, PubSubEvent},
+    BufferedStream,
+};
+use crate::{Error, Result, Value};
 use futures::{
     lock::Mutex,
     task::{Context, Poll},
@@ -6,11 +10,6 @@ use futures::{
 };
 use std::{pin::Pin, sync::Arc};
 
-#[cfg(feature = "runtime_async_std")]
-use async_std::net::TcpStream;
-#[cfg(feature = "runtime_tokio")]
-use tokio::net::TcpStream;
-
 ///A message received from a channel.
 #[derive(Debug, Clone)]
 #[cfg_attr(test, derive(PartialEq))]
@@ -33,119 +32,81 @@ pub struct PMessage {
     pub pattern: Vec<u8>,
 }
 
-#[allow(missing_debug_implementations)]
-struct ValueStream {
-    conn: Connection,
-    poll_future: Pin<Box<dyn Future<Output = Result<Value>> + Send>>,
-}
-
-impl ValueStream {
-    pub(crate) fn new(conn: Connection) -> Self {
-        //FIXME: Find a way to avoid cloning?
-        let poll_future = Self::create_poll_future(conn.clone());
-        Self { conn, poll_future }
-    }
-
-    fn create_poll_future(conn: Connection) -> Pin<Box<dyn Future<Output = Result<Value>> + Send>> {
-        async move {
-            let mut lock = conn.stream.lock().await;
-            Connection::read_value(&mut lock).await
-        }
-        .boxed()
-    }
-}
-
-impl Stream for ValueStream {
-    type Item = Value;
-
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        match self.poll_future.as_mut().poll(cx) {
-            Poll::Ready(p) => {
-                self.poll_future = Self::create_poll_future(self.conn.clone());
-
-                match p {
-                    Ok(p) => Poll::Ready(Some(p)),
-                    Err(_) => Poll::Ready(None),
-                }
-            }
-            Poll::Pending => Poll::Pending,
-        }
-    }
-}
-
-///A stream of [`Message`s](struct.Message.html).
+///A stream of [`Message`s](struct.Message.html), created using
+///[`Connection::subscribe`](struct.Connection.html#method.subscribe). Subscription confirmations
+///are skipped, while errors and unexpected events are returned as items. The stream ends after the
+///connection is closed or invalid data is received.
 #[must_use = "No messages will be received if left unused"]
 #[allow(missing_debug_implementations)]
 pub struct MessageStream {
-    inner: Pin<Box<ValueStream>>,
+    inner: PubSub,
 }
 
 impl MessageStream {
-    pub(crate) fn new(connection: Connection) -> Self {
-        Self {
-            inner: Pin::new(Box::new(ValueStream::new(connection))),
-        }
+    pub(crate) fn new(inner: PubSub) -> Self {
+        Self { inner }
     }
 }
 
 impl Stream for MessageStream {
-    type Item = Message;
+    type Item = Result<Message>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        match self.inner.as_mut().poll_next(cx) {
-            Poll::Ready(Some(v)) => {
-                let mut result = v.unwrap_array().into_iter();
-                assert_eq!(result.next().unwrap(), Value::String("message".into()));
-                let channel = result.next().unwrap().unwrap_string();
-                let message = result.next().unwrap().unwrap_string();
-                let output = Message { channel, message };
-                Poll::Ready(Some(output))
+        loop {
+            let event = match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(event)) => event,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+            match event {
+                Ok(PubSubEvent::Message(message)) => return Poll::Ready(Some(Ok(message))),
+                Ok(PubSubEvent::Subscribe { .. }) | Ok(PubSubEvent::Pong) => (),
+                Ok(other) => return Poll::Ready(Some(Err(unexpected_event(other)))),
+                Err(e) => return Poll::Ready(Some(Err(e))),
             }
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
         }
     }
 }
 
-///A stream of [`PMessage`s](struct.PMessage.html). See [`MessageStream`](struct.MessageStream.html) for more info.
+///A stream of [`PMessage`s](struct.PMessage.html), created using
+///[`Connection::psubscribe`](struct.Connection.html#method.psubscribe). See
+///[`MessageStream`](struct.MessageStream.html) for more info.
 #[must_use = "No messages will be received if left unused"]
 #[allow(missing_debug_implementations)]
 pub struct PMessageStream {
-    inner: Pin<Box<ValueStream>>,
+    inner: PubSub,
 }
 
 impl PMessageStream {
-    pub(crate) fn new(connection: Connection) -> Self {
-        Self {
-            inner: Pin::new(Box::new(ValueStream::new(connection))),
-        }
+    pub(crate) fn new(inner: PubSub) -> Self {
+        Self { inner }
     }
 }
 
 impl Stream for PMessageStream {
-    type Item = PMessage;
+    type Item = Result<PMessage>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        match self.inner.as_mut().poll_next(cx) {
-            Poll::Ready(Some(v)) => {
-                let mut result = v.unwrap_array().into_iter();
-                assert_eq!(result.next().unwrap(), Value::String("pmessage".into()));
-                let pattern = result.next().unwrap().unwrap_string();
-                let channel = result.next().unwrap().unwrap_string();
-                let message = result.next().unwrap().unwrap_string();
-                let output = PMessage {
-                    channel,
-                    message,
-                    pattern,
-                };
-                Poll::Ready(Some(output))
+        loop {
+            let event = match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(event)) => event,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+            match event {
+                Ok(PubSubEvent::PMessage(message)) => return Poll::Ready(Some(Ok(message))),
+                Ok(PubSubEvent::PSubscribe { .. }) | Ok(PubSubEvent::Pong) => (),
+                Ok(other) => return Poll::Ready(Some(Err(unexpected_event(other)))),
+                Err(e) => return Poll::Ready(Some(Err(e))),
             }
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
         }
     }
 }
 
+fn unexpected_event(event: PubSubEvent) -> Error {
+    Error::UnexpectedResponse(format!("unexpected pub/sub event {:?}", event))
+}
+
 type ResponseFuture = Pin<Box<dyn Future<Output = Result<Value>> + Send>>;
 ///A stream of responses from a pipelined command.
 #[must_use]
@@ -153,12 +114,12 @@ type ResponseFuture = Pin<Box<dyn Future<Output = Result<Value>> + Send>>;
 pub struct ResponseStream {
     expected: usize,
     received: usize,
-    stream: Arc<Mutex<TcpStream>>,
+    stream: Arc<Mutex<BufferedStream>>,
     poll_future: ResponseFuture,
 }
 
 impl ResponseStream {
-    pub(crate) fn new(reply_count: usize, stream: Arc<Mutex<TcpStream>>) -> Self {
+    pub(crate) fn new(reply_count: usize, stream: Arc<Mutex<BufferedStream>>) -> Self {
         let poll_future = Self::create_future(stream.clone());
         Self {
             poll_future,
@@ -168,10 +129,10 @@ impl ResponseStream {
         }
     }
 
-    fn create_future(stream: Arc<Mutex<TcpStream>>) -> ResponseFuture {
+    fn create_future(stream: Arc<Mutex<BufferedStream>>) -> ResponseFuture {
         async move {
             let mut stream = stream.lock().await;
-            Connection::read_value(&mut stream).await
+            stream.read_value().await
         }
         .boxed()
     }
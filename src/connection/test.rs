@@ -1,6 +1,28 @@
 use super::*;
-use crate::{redis_test, test::*, Command, CommandList, DataType, PMessage, Value};
+use crate::{
+    redis_test, test::*, Aggregate, BitFieldBuilder, BitFieldOverflow, BitFieldType, BitOp,
+    BitRange, Command, CommandList, DataType, ExpireCondition, Expiry, FunctionRestorePolicy,
+    GeoPosition, GeoSearchBuilder, GeoShape, GeoUnit, GetExOption, HashExpireResult,
+    InsertPosition, ListSide, MSetBuilder, MigrateOptions, PMessage, PendingSummary,
+    RestoreOptions, Script, SetOptions, SortBuilder, StreamEntry, StreamId, StreamTrim, Ttl, Value,
+    XPendingBuilder, ZAddBuilder, ZRangeBuilder,
+};
 use futures::{StreamExt, TryStreamExt};
+use std::io::{Read, Write};
+use std::{collections::BTreeMap, time::Duration};
+
+//Start a server which replies to the first request with `reply` and then closes the connection.
+fn fake_server(reply: &'static [u8]) -> std::net::SocketAddr {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (mut socket, _) = listener.accept().unwrap();
+        let mut request = [0; 64];
+        let _ = socket.read(&mut request).unwrap();
+        socket.write_all(reply).unwrap();
+    });
+    address
+}
 
 #[cfg_attr(feature = "runtime_tokio", tokio::test)]
 #[cfg_attr(feature = "runtime_async_std", async_std::test)]
@@ -62,6 +84,129 @@ async fn pipelined_commands() {
     );
 }
 
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn serialized_commands() {
+    redis_test!(
+        redis,
+        {
+            let command = CommandList::new("RPUSH")
+                .arg(&list_key)
+                .arg(b"")
+                .command("LLEN")
+                .arg(&list_key)
+                .into_owned()
+                .into_serialized();
+
+            for i in 1..=2 {
+                assert_eq!(
+                    redis
+                        .run_serialized_commands(&command)
+                        .await
+                        .unwrap()
+                        .try_collect::<Vec<Value>>()
+                        .await
+                        .unwrap(),
+                    vec![Value::Integer(i), Value::Integer(i)]
+                );
+            }
+        },
+        list_key
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn closed_mid_reply() {
+    let address = fake_server(b"*2\r\n$3\r\nfoo\r\n$10\r\nabc");
+    let mut redis = Connection::connect(address).await.unwrap();
+
+    match redis.run_command(Command::new("PING")).await {
+        Err(Error::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof),
+        other => panic!("expected EOF error, got {:?}", other),
+    }
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn invalid_reply() {
+    let address = fake_server(b"*1\r\n:not-a-number\r\n");
+    let mut redis = Connection::connect(address).await.unwrap();
+
+    match redis.run_command(Command::new("PING")).await {
+        Err(Error::Protocol(data, position)) => {
+            assert_eq!(data, b":not-a-number");
+            assert_eq!(position, 4);
+        }
+        other => panic!("expected protocol error, got {:?}", other),
+    }
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn key_type_bulk_reply() {
+    let address = fake_server(b"$6\r\nstring\r\n");
+    let mut redis = Connection::connect(address).await.unwrap();
+
+    match redis.key_type("key").await {
+        Err(Error::UnexpectedResponse(_)) => (),
+        other => panic!("expected unexpected response error, got {:?}", other),
+    }
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn scan_invalid_reply() {
+    let address = fake_server(b"*2\r\n$1\r\n0\r\n:5\r\n");
+    let mut redis = Connection::connect(address).await.unwrap();
+
+    let mut stream = redis.scan().run();
+    match stream.next().await {
+        Some(Err(Error::UnexpectedResponse(_))) => (),
+        other => panic!("expected unexpected response error, got {:?}", other),
+    }
+    assert!(stream.next().await.is_none());
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn nested_error_reply() {
+    let address = fake_server(b"*2\r\n:1\r\n-WRONGTYPE wrong kind of value\r\n");
+    let mut redis = Connection::connect(address).await.unwrap();
+
+    let mut result = redis
+        .run_command(Command::new("EXEC"))
+        .await
+        .unwrap()
+        .unwrap_array();
+    assert!(result[1].is_error());
+    match result.pop().unwrap().into_result() {
+        Err(Error::RedisError(e)) => assert_eq!(e, "WRONGTYPE wrong kind of value"),
+        other => panic!("expected error, got {:?}", other),
+    }
+    assert_eq!(result[0].clone().into_result().unwrap(), Value::Integer(1));
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn large_reply() {
+    redis_test!(
+        redis,
+        {
+            //Make sure the reply is a lot bigger than the initial read buffer
+            let element = vec![b'a'; 100];
+            let elements = vec![element; 10000];
+            redis.rpush_slice(&list_key, &elements).await.unwrap();
+
+            let result = redis.lrange(&list_key, 0, -1).await.unwrap();
+            assert_eq!(result, elements);
+            //The connection should still be usable afterwards
+            assert_eq!(redis.llen(&list_key).await.unwrap(), Some(10000));
+        },
+        list_key
+    );
+}
+
 #[cfg_attr(feature = "runtime_tokio", tokio::test)]
 #[cfg_attr(feature = "runtime_async_std", async_std::test)]
 async fn pubsub() {
@@ -74,13 +219,13 @@ async fn pubsub() {
             let publish_future = async {
                 let commands = CommandList::new("PUBLISH")
                     .arg(&channel0)
-                    .arg(&"foo")
+                    .arg("foo")
                     .command("PUBLISH")
                     .arg(&channel1)
-                    .arg(&"bar")
-                    .command(&"PUBLISH")
+                    .arg("bar")
+                    .command("PUBLISH")
                     .arg(&channel2)
-                    .arg(&"foobar");
+                    .arg("foobar");
 
                 publisher
                     .run_commands(commands)
@@ -95,7 +240,7 @@ async fn pubsub() {
             let c1 = channel1.clone();
             let c2 = channel2.clone();
             let receiver_future = async move {
-                let expected = vec![
+                let expected = [
                     Message {
                         channel: c0,
                         message: "foo".into(),
@@ -109,9 +254,9 @@ async fn pubsub() {
                         message: "foobar".into(),
                     },
                 ];
-                for i in 0..3 {
-                    let result = stream.next().await.unwrap();
-                    assert_eq!(result, expected[i]);
+                for message in expected.iter() {
+                    let result = stream.next().await.unwrap().unwrap();
+                    assert_eq!(&result, message);
                 }
             };
 
@@ -149,7 +294,7 @@ async fn pubsub_pattern() {
                     channel: receive_channel,
                     pattern,
                 };
-                let result = stream.next().await.unwrap();
+                let result = stream.next().await.unwrap().unwrap();
                 assert_eq!(result, expected);
             };
 
@@ -159,6 +304,136 @@ async fn pubsub_pattern() {
     );
 }
 
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn pubsub_dynamic() {
+    redis_test!(
+        publisher,
+        {
+            let mut pubsub = Connection::connect(TEST_ADDRESS)
+                .await
+                .unwrap()
+                .into_pubsub();
+            let empty: &[&[u8]] = &[];
+            assert!(pubsub.subscribe(empty).await.is_err());
+
+            pubsub.subscribe(&[&channel]).await.unwrap();
+            assert_eq!(
+                pubsub.next().await.unwrap().unwrap(),
+                PubSubEvent::Subscribe {
+                    channel: channel.clone(),
+                    count: 1
+                }
+            );
+
+            //Waiting for a message while changing the subscriptions
+            assert!(futures::poll!(pubsub.next()).is_pending());
+            let mut pattern = base.clone();
+            pattern.push(b'*');
+            pubsub.psubscribe(&[&pattern]).await.unwrap();
+            assert_eq!(
+                pubsub.next().await.unwrap().unwrap(),
+                PubSubEvent::PSubscribe {
+                    pattern: pattern.clone(),
+                    count: 2
+                }
+            );
+
+            assert_eq!(publisher.publish(&channel, "foo").await.unwrap(), 1);
+            assert_eq!(
+                pubsub.next().await.unwrap().unwrap(),
+                PubSubEvent::Message(Message {
+                    channel: channel.clone(),
+                    message: b"foo".to_vec(),
+                })
+            );
+            let mut matching = base.clone();
+            matching.push(b'a');
+            assert_eq!(publisher.publish(&matching, "bar").await.unwrap(), 1);
+            assert_eq!(
+                pubsub.next().await.unwrap().unwrap(),
+                PubSubEvent::PMessage(PMessage {
+                    channel: matching,
+                    message: b"bar".to_vec(),
+                    pattern: pattern.clone(),
+                })
+            );
+
+            pubsub.ping().await.unwrap();
+            assert_eq!(pubsub.next().await.unwrap().unwrap(), PubSubEvent::Pong);
+
+            pubsub.unsubscribe(&[&channel]).await.unwrap();
+            assert_eq!(
+                pubsub.next().await.unwrap().unwrap(),
+                PubSubEvent::Unsubscribe {
+                    channel: Some(channel.clone()),
+                    count: 1
+                }
+            );
+            assert_eq!(publisher.publish(&channel, "foo").await.unwrap(), 0);
+
+            //Unconsumed events are discarded
+            assert_eq!(publisher.publish(&base, "baz").await.unwrap(), 1);
+            let mut connection = pubsub.into_connection().await.unwrap();
+            connection.set(&key, "value").await.unwrap();
+            assert_eq!(connection.get(&key).await.unwrap(), Some(b"value".to_vec()));
+            assert_eq!(publisher.publish(&base, "baz").await.unwrap(), 0);
+        },
+        channel,
+        base,
+        key
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn pubsub_errors() {
+    let address = fake_server(b"-ERR subscribing is not allowed\r\n");
+    let redis = Connection::connect(address).await.unwrap();
+    assert!(redis.subscribe(&["a"]).await.is_err());
+
+    let empty: &[&str] = &[];
+    let redis = Connection::connect(TEST_ADDRESS).await.unwrap();
+    assert!(redis.psubscribe(empty).await.is_err());
+
+    let address = fake_server(
+        b"*3\r\n$9\r\nsubscribe\r\n$1\r\na\r\n:1\r\n\
+          *3\r\n$7\r\nmessage\r\n$1\r\na\r\n$5\r\nhello\r\n\
+          *4\r\n$8\r\npmessage\r\n$1\r\n*\r\n$1\r\na\r\n$5\r\nhello\r\n",
+    );
+    let redis = Connection::connect(address).await.unwrap();
+    let mut stream = redis.subscribe(&["a"]).await.unwrap();
+    assert_eq!(
+        stream.next().await.unwrap().unwrap(),
+        Message {
+            channel: b"a".to_vec(),
+            message: b"hello".to_vec()
+        }
+    );
+    match stream.next().await {
+        Some(Err(Error::UnexpectedResponse(_))) => (),
+        other => panic!("expected unexpected response error, got {:?}", other),
+    }
+    match stream.next().await {
+        Some(Err(Error::Io(e))) => assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof),
+        other => panic!("expected EOF error, got {:?}", other),
+    }
+    assert!(stream.next().await.is_none());
+
+    //The stream ends after invalid data instead of trying to read past it.
+    let address = fake_server(
+        b"*3\r\n$9\r\nsubscribe\r\n$1\r\na\r\n:1\r\n\
+          *3\r\n$7\r\nmessage\r\n$1\r\na\r\n:hello\r\n",
+    );
+    let redis = Connection::connect(address).await.unwrap();
+    let mut stream = redis.subscribe(&["a"]).await.unwrap();
+    match stream.next().await {
+        Some(Err(Error::Protocol(_, _))) => (),
+        other => panic!("expected protocol error, got {:?}", other),
+    }
+    assert!(stream.next().await.is_none());
+}
+
 #[cfg_attr(feature = "runtime_tokio", tokio::test)]
 #[cfg_attr(feature = "runtime_async_std", async_std::test)]
 async fn get_set() {
@@ -172,6 +447,92 @@ async fn get_set() {
     );
 }
 
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn set_options() {
+    redis_test!(
+        redis,
+        {
+            let pttl = |key: &Vec<u8>| Command::new("PTTL").arg(key).into_owned();
+
+            assert!(!redis
+                .set_with_options(&key, "foo", SetOptions::new().xx())
+                .await
+                .unwrap());
+            let options = SetOptions::new().nx().expire(Expiry::Seconds(100));
+            assert!(redis.set_with_options(&key, "foo", options).await.unwrap());
+            assert!(!redis.set_with_options(&key, "bar", options).await.unwrap());
+            let ttl: isize = redis.query(pttl(&key)).await.unwrap();
+            assert!(ttl > 90_000 && ttl <= 100_000);
+
+            let options = SetOptions::new().xx().expire(Expiry::Seconds(1)).keep_ttl();
+            assert!(redis.set_with_options(&key, "bar", options).await.unwrap());
+            let ttl: isize = redis.query(pttl(&key)).await.unwrap();
+            assert!(ttl > 90_000);
+            redis
+                .set_with_options(&key, "bar", SetOptions::new())
+                .await
+                .unwrap();
+            assert_eq!(redis.query::<isize>(pttl(&key)).await.unwrap(), -1);
+
+            let far_future = Expiry::AtMilliseconds(32_503_680_000_000);
+            assert_eq!(
+                redis
+                    .set_and_get(&key, "baz", SetOptions::new().expire(far_future))
+                    .await
+                    .unwrap(),
+                Some(b"bar".to_vec())
+            );
+            assert!(redis.query::<isize>(pttl(&key)).await.unwrap() > 0);
+            assert_eq!(
+                redis
+                    .set_and_get(&other, "new", SetOptions::new().nx())
+                    .await
+                    .unwrap(),
+                None
+            );
+
+            assert!(!redis.setnx(&key, "nope").await.unwrap());
+            assert_eq!(
+                redis.getset(&key, "qux").await.unwrap(),
+                Some(b"baz".to_vec())
+            );
+            assert_eq!(
+                redis
+                    .getex(
+                        &key,
+                        Some(GetExOption::Expire(Expiry::Milliseconds(50_000)))
+                    )
+                    .await
+                    .unwrap(),
+                Some(b"qux".to_vec())
+            );
+            assert!(redis.query::<isize>(pttl(&key)).await.unwrap() > 40_000);
+            //Without an option the expiry is kept
+            assert_eq!(
+                redis.getex(&key, None).await.unwrap(),
+                Some(b"qux".to_vec())
+            );
+            assert!(redis.query::<isize>(pttl(&key)).await.unwrap() > 40_000);
+            redis.getex(&key, Some(GetExOption::Persist)).await.unwrap();
+            assert_eq!(redis.query::<isize>(pttl(&key)).await.unwrap(), -1);
+            assert_eq!(redis.getdel(&key).await.unwrap(), Some(b"qux".to_vec()));
+            assert_eq!(redis.getdel(&key).await.unwrap(), None);
+            assert!(redis.setnx(&key, "again").await.unwrap());
+
+            redis.del(&key).await.unwrap();
+            let builder = MSetBuilder::new().set(&key, b"1").set(&other, b"2");
+            assert!(!redis.msetnx(builder).await.unwrap());
+            assert_eq!(redis.get(&key).await.unwrap(), None);
+            redis.del(&other).await.unwrap();
+            let builder = MSetBuilder::new().set(&key, b"1").set(&other, b"2");
+            assert!(redis.msetnx(builder).await.unwrap());
+        },
+        key,
+        other
+    );
+}
+
 #[cfg_attr(feature = "runtime_tokio", tokio::test)]
 #[cfg_attr(feature = "runtime_async_std", async_std::test)]
 async fn list_convenience() {
@@ -190,7 +551,7 @@ async fn list_convenience() {
             assert_eq!(redis.rpop(&list_key).await.unwrap(), Some(b"2".to_vec()));
             assert_eq!(redis.llen(&list_key).await.unwrap(), Some(1));
 
-            let long_list: Vec<String> = std::iter::repeat("value".to_string()).take(10).collect();
+            let long_list: Vec<String> = std::iter::repeat_n("value".to_string(), 10).collect();
             redis.lpush_slice(&list_key, &long_list).await.unwrap();
             redis.ltrim(&list_key, 0, 4).await.unwrap();
             redis.lset(&list_key, 0, b"hello").await.unwrap();
@@ -201,6 +562,105 @@ async fn list_convenience() {
     );
 }
 
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn list_commands() {
+    redis_test!(
+        redis,
+        {
+            assert_eq!(redis.lpushx(&list, "a").await.unwrap(), 0);
+            assert_eq!(redis.rpushx(&list, "a").await.unwrap(), 0);
+            redis.rpush_slice(&list, &["a", "b", "a"]).await.unwrap();
+            assert_eq!(redis.lpushx(&list, "c").await.unwrap(), 4);
+            assert_eq!(redis.rpushx(&list, "a").await.unwrap(), 5);
+
+            assert_eq!(redis.lindex(&list, 0).await.unwrap(), Some(b"c".to_vec()));
+            assert_eq!(redis.lindex(&list, -2).await.unwrap(), Some(b"a".to_vec()));
+            assert_eq!(redis.lindex(&list, 10).await.unwrap(), None);
+
+            assert_eq!(redis.lpos(&list, "a", None, None).await.unwrap(), Some(1));
+            assert_eq!(
+                redis.lpos(&list, "a", Some(-1), None).await.unwrap(),
+                Some(4)
+            );
+            assert_eq!(
+                redis.lpos(&list, "a", Some(2), Some(2)).await.unwrap(),
+                None
+            );
+            assert_eq!(redis.lpos(&list, "x", None, None).await.unwrap(), None);
+            assert_eq!(
+                redis.lpos_count(&list, "a", 0, None, None).await.unwrap(),
+                vec![1, 3, 4]
+            );
+            assert_eq!(
+                redis
+                    .lpos_count(&list, "a", 2, Some(-1), None)
+                    .await
+                    .unwrap(),
+                vec![4, 3]
+            );
+
+            assert_eq!(
+                redis
+                    .linsert(&list, InsertPosition::Before, "b", "x")
+                    .await
+                    .unwrap(),
+                6
+            );
+            assert_eq!(
+                redis
+                    .linsert(&list, InsertPosition::After, "b", "y")
+                    .await
+                    .unwrap(),
+                7
+            );
+            assert_eq!(
+                redis
+                    .linsert(&list, InsertPosition::After, "nope", "y")
+                    .await
+                    .unwrap(),
+                -1
+            );
+            assert_eq!(
+                redis
+                    .linsert(&other, InsertPosition::After, "b", "y")
+                    .await
+                    .unwrap(),
+                0
+            );
+            assert_eq!(
+                redis.lrange(&list, 0, -1).await.unwrap(),
+                vec![
+                    b"c".to_vec(),
+                    b"a".to_vec(),
+                    b"x".to_vec(),
+                    b"b".to_vec(),
+                    b"y".to_vec(),
+                    b"a".to_vec(),
+                    b"a".to_vec()
+                ]
+            );
+
+            assert_eq!(redis.lrem(&list, -1, "a").await.unwrap(), 1);
+            assert_eq!(redis.lrem(&list, 0, "a").await.unwrap(), 2);
+            assert_eq!(redis.lrem(&list, 0, "a").await.unwrap(), 0);
+
+            assert_eq!(
+                redis.lpop_count(&list, 2).await.unwrap(),
+                vec![b"c".to_vec(), b"x".to_vec()]
+            );
+            assert_eq!(
+                redis.rpop_count(&list, 5).await.unwrap(),
+                vec![b"y".to_vec(), b"b".to_vec()]
+            );
+            assert!(redis.lpop_count(&list, 2).await.unwrap().is_empty());
+            assert!(redis.rpop_count(&list, 2).await.unwrap().is_empty());
+        },
+        list,
+        other
+    );
+}
+
 #[cfg_attr(feature = "runtime_tokio", tokio::test)]
 #[cfg_attr(feature = "runtime_async_std", async_std::test)]
 async fn incr_decr() {
@@ -215,6 +675,7 @@ async fn incr_decr() {
 
             assert_eq!(redis.incrbyfloat(&float_key, 8.0).await.unwrap(), 8.0);
             assert_eq!(redis.incrbyfloat(&float_key, -4.0).await.unwrap(), 4.0);
+            assert_eq!(redis.decrbyfloat(&float_key, 1.5).await.unwrap(), 2.5);
         },
         int_key,
         float_key
@@ -238,6 +699,41 @@ async fn append() {
     );
 }
 
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn string_ranges() {
+    redis_test!(
+        redis,
+        {
+            redis.set(&key, "Hello World").await.unwrap();
+            assert_eq!(redis.setrange(&key, 6, "Redis").await.unwrap(), 11);
+            assert_eq!(
+                redis.get(&key).await.unwrap(),
+                Some(b"Hello Redis".to_vec())
+            );
+            assert_eq!(redis.setrange(&padded, 2, "x").await.unwrap(), 3);
+            assert_eq!(redis.get(&padded).await.unwrap(), Some(b"\0\0x".to_vec()));
+
+            assert_eq!(redis.getrange(&key, 0, 4).await.unwrap(), b"Hello");
+            assert_eq!(redis.getrange(&key, -5, -1).await.unwrap(), b"Redis");
+            assert_eq!(redis.getrange(&key, 5, 2).await.unwrap(), b"");
+            assert_eq!(redis.getrange(&padded, 10, 20).await.unwrap(), b"");
+
+            assert_eq!(redis.strlen(&key).await.unwrap(), 11);
+            assert_eq!(redis.strlen(&missing).await.unwrap(), 0);
+
+            redis.set(&key, "ohmytext").await.unwrap();
+            redis.set(&padded, "mynewtext").await.unwrap();
+            assert_eq!(redis.lcs(&key, &padded).await.unwrap(), b"mytext");
+            assert_eq!(redis.lcs_len(&key, &padded).await.unwrap(), 6);
+            assert_eq!(redis.lcs_len(&key, &missing).await.unwrap(), 0);
+        },
+        key,
+        padded,
+        missing
+    );
+}
+
 #[cfg_attr(feature = "runtime_tokio", tokio::test)]
 #[cfg_attr(feature = "runtime_async_std", async_std::test)]
 async fn mget_mset() {
@@ -269,9 +765,9 @@ async fn exists() {
     redis_test!(
         redis,
         {
-            assert_eq!(redis.exists(&key).await.unwrap(), false);
+            assert!(!redis.exists(&key).await.unwrap());
             redis.set(&key, "foo").await.unwrap();
-            assert_eq!(redis.exists(&key).await.unwrap(), true);
+            assert!(redis.exists(&key).await.unwrap());
         },
         key
     );
@@ -318,9 +814,9 @@ async fn hash_sets() {
                     .unwrap(),
                 2
             );
-            assert_eq!(redis.hdel(&key, b"field1").await.unwrap(), true);
+            assert!(redis.hdel(&key, b"field1").await.unwrap());
 
-            assert_eq!(redis.hsetnx(&key, b"field3", b"foo").await.unwrap(), true);
+            assert!(redis.hsetnx(&key, b"field3", b"foo").await.unwrap());
         },
         key
     );
@@ -328,58 +824,279 @@ async fn hash_sets() {
 
 #[cfg_attr(feature = "runtime_tokio", tokio::test)]
 #[cfg_attr(feature = "runtime_async_std", async_std::test)]
-async fn sets() {
+async fn hash_commands() {
     redis_test!(
         redis,
         {
-            redis.sadd(&set, "foo").await.unwrap();
-            assert_eq!(redis.smembers(&set).await.unwrap(), vec![b"foo"]);
+            assert!(redis.hgetall(&hash).await.unwrap().is_empty());
+            let builder = MSetBuilder::new()
+                .set(b"one", b"1")
+                .set(b"two", b"2")
+                .set(b"three", b"3");
+            redis.hset_many(&hash, builder).await.unwrap();
 
-            redis.sadd_slice(&set, &["bar", "baz"]).await.unwrap();
-            assert!(redis.sismember(&set, "bar").await.unwrap());
-        },
-        set
-    );
-}
+            let all = redis.hgetall(&hash).await.unwrap();
+            assert_eq!(all.len(), 3);
+            assert_eq!(all[&b"two".to_vec()], b"2");
+            let typed: BTreeMap<String, isize> = redis.hgetall_as(&hash).await.unwrap();
+            assert_eq!(
+                typed.into_iter().collect::<Vec<_>>(),
+                vec![
+                    ("one".to_string(), 1),
+                    ("three".to_string(), 3),
+                    ("two".to_string(), 2)
+                ]
+            );
 
-#[cfg_attr(feature = "runtime_tokio", tokio::test)]
-#[cfg_attr(feature = "runtime_async_std", async_std::test)]
-async fn blpop() {
-    redis_test!(
-        redis,
-        {
-            redis.rpush(&key, "foo").await.unwrap();
-            redis.rpush(&key, "foobar").await.unwrap();
-            redis.rpush(&key2, "bar").await.unwrap();
+            assert_eq!(
+                redis.hmget(&hash, &["one", "four", "three"]).await.unwrap(),
+                vec![Some(b"1".to_vec()), None, Some(b"3".to_vec())]
+            );
+            assert!(redis.hmget::<_, &str>(&hash, &[]).await.is_err());
 
+            let field = redis.hrandfield(&hash).await.unwrap().unwrap();
+            assert!(all.contains_key(&field));
+            assert_eq!(redis.hrandfield(&missing).await.unwrap(), None);
+            let mut fields = redis.hrandfield_count(&hash, 5).await.unwrap();
+            fields.sort();
             assert_eq!(
-                redis.blpop(&[&key, &key2], 0).await.unwrap().unwrap(),
-                (key.clone(), b"foo".to_vec())
+                fields,
+                vec![b"one".to_vec(), b"three".to_vec(), b"two".to_vec()]
             );
+            assert_eq!(redis.hrandfield_count(&hash, -5).await.unwrap().len(), 5);
+            for protocol in [ProtocolVersion::Resp3, ProtocolVersion::Resp2] {
+                redis.hello(protocol).await.unwrap();
+                assert_eq!(redis.hgetall(&hash).await.unwrap(), all);
+                let pairs = redis.hrandfield_withvalues(&hash, 2).await.unwrap();
+                assert_eq!(pairs.len(), 2);
+                for (field, value) in pairs {
+                    assert_eq!(all[&field], value);
+                }
+            }
+
             assert_eq!(
-                redis.brpop(&[&key2, &key], 0).await.unwrap().unwrap(),
-                (key2.clone(), b"bar".to_vec())
+                redis.httl(&hash, &["one", "four"]).await.unwrap(),
+                vec![Ttl::Persistent, Ttl::Missing]
             );
             assert_eq!(
-                redis.brpop(&[&key, &key2], 0).await.unwrap().unwrap(),
-                (key.clone(), b"foobar".to_vec())
+                redis
+                    .hexpire(&hash, 100, &["one", "two", "four"], None)
+                    .await
+                    .unwrap(),
+                vec![
+                    HashExpireResult::Set,
+                    HashExpireResult::Set,
+                    HashExpireResult::Missing
+                ]
             );
-        },
-        key,
-        key2
-    );
-}
-
-#[cfg_attr(feature = "runtime_tokio", tokio::test)]
-#[cfg_attr(feature = "runtime_async_std", async_std::test)]
-async fn sscan() {
-    redis_test!(
-        redis,
-        {
-            //Verify that it handles an empty search correctly
-            let result: Vec<Vec<u8>> = redis.sscan(&some_set).run().collect().await;
-            assert!(result.is_empty());
-
+            assert_eq!(
+                redis
+                    .hexpire(&hash, 200, &["one", "three"], Some(ExpireCondition::Xx))
+                    .await
+                    .unwrap(),
+                vec![HashExpireResult::Set, HashExpireResult::ConditionNotMet]
+            );
+            assert_eq!(
+                redis
+                    .hpexpire(&hash, 50_000, &["one"], Some(ExpireCondition::Gt))
+                    .await
+                    .unwrap(),
+                vec![HashExpireResult::ConditionNotMet]
+            );
+            match redis.httl(&hash, &["one"]).await.unwrap()[0] {
+                Ttl::Expires(ttl) => assert!(ttl > 190 && ttl <= 200),
+                other => panic!("unexpected TTL {:?}", other),
+            }
+            match redis.hpttl(&hash, &["two"]).await.unwrap()[0] {
+                Ttl::Expires(ttl) => assert!(ttl > 90_000 && ttl <= 100_000),
+                other => panic!("unexpected TTL {:?}", other),
+            }
+
+            assert_eq!(
+                redis
+                    .hpersist(&hash, &["one", "three", "four"])
+                    .await
+                    .unwrap(),
+                vec![true, false, false]
+            );
+            assert_eq!(
+                redis.httl(&hash, &["one"]).await.unwrap(),
+                vec![Ttl::Persistent]
+            );
+
+            assert_eq!(
+                redis.hpexpire(&hash, 0, &["three"], None).await.unwrap(),
+                vec![HashExpireResult::Deleted]
+            );
+            assert!(!redis.hexists(&hash, "three").await.unwrap());
+        },
+        hash,
+        missing
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn sets() {
+    redis_test!(
+        redis,
+        {
+            redis.sadd(&set, "foo").await.unwrap();
+            assert_eq!(redis.smembers(&set).await.unwrap(), vec![b"foo"]);
+
+            redis.sadd_slice(&set, &["bar", "baz"]).await.unwrap();
+            assert!(redis.sismember(&set, "bar").await.unwrap());
+        },
+        set
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn blpop() {
+    redis_test!(
+        redis,
+        {
+            redis.rpush(&key, "foo").await.unwrap();
+            redis.rpush(&key, "foobar").await.unwrap();
+            redis.rpush(&key2, "bar").await.unwrap();
+
+            assert_eq!(
+                redis.blpop(&[&key, &key2], 0).await.unwrap().unwrap(),
+                (key.clone(), b"foo".to_vec())
+            );
+            assert_eq!(
+                redis.brpop(&[&key2, &key], 0).await.unwrap().unwrap(),
+                (key2.clone(), b"bar".to_vec())
+            );
+            assert_eq!(
+                redis.brpop(&[&key, &key2], 0).await.unwrap().unwrap(),
+                (key.clone(), b"foobar".to_vec())
+            );
+        },
+        key,
+        key2
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn list_moves() {
+    redis_test!(
+        redis,
+        {
+            redis.rpush_slice(&queue, &["1", "2", "3"]).await.unwrap();
+            assert_eq!(
+                redis
+                    .lmove(&queue, &processing, ListSide::Left, ListSide::Right)
+                    .await
+                    .unwrap(),
+                Some(b"1".to_vec())
+            );
+            assert_eq!(
+                redis.rpoplpush(&queue, &processing).await.unwrap(),
+                Some(b"3".to_vec())
+            );
+            assert_eq!(
+                redis.lrange(&processing, 0, -1).await.unwrap(),
+                vec![b"3".to_vec(), b"1".to_vec()]
+            );
+            //Rotate a list onto itself
+            assert_eq!(
+                redis
+                    .lmove(&processing, &processing, ListSide::Right, ListSide::Left)
+                    .await
+                    .unwrap(),
+                Some(b"1".to_vec())
+            );
+            assert_eq!(
+                redis
+                    .lmove(&empty, &processing, ListSide::Left, ListSide::Left)
+                    .await
+                    .unwrap(),
+                None
+            );
+
+            assert_eq!(
+                redis
+                    .lmpop(&[&empty, &processing], ListSide::Left, Some(5))
+                    .await
+                    .unwrap(),
+                Some((processing.clone(), vec![b"1".to_vec(), b"3".to_vec()]))
+            );
+            assert_eq!(
+                redis
+                    .lmpop(&[&empty, &queue], ListSide::Right, None)
+                    .await
+                    .unwrap(),
+                Some((queue.clone(), vec![b"2".to_vec()]))
+            );
+            assert_eq!(
+                redis
+                    .lmpop(&[&empty, &queue], ListSide::Left, None)
+                    .await
+                    .unwrap(),
+                None
+            );
+            assert!(redis
+                .lmpop::<&[u8]>(&[], ListSide::Left, None)
+                .await
+                .is_err());
+
+            //The blocking variants wait for another client to push
+            let mut worker = Connection::connect(TEST_ADDRESS).await.unwrap();
+            let (moved, _) = futures::join!(
+                worker.blmove(&queue, &processing, ListSide::Right, ListSide::Left, 0),
+                redis.rpush(&queue, "4")
+            );
+            assert_eq!(moved.unwrap(), Some(b"4".to_vec()));
+            let (moved, _) = futures::join!(
+                worker.brpoplpush(&queue, &processing, 0),
+                redis.rpush(&queue, "5")
+            );
+            assert_eq!(moved.unwrap(), Some(b"5".to_vec()));
+            let lists = [&empty, &queue];
+            let (popped, _) = futures::join!(
+                worker.blmpop(&lists, ListSide::Left, Some(2), 0),
+                redis.rpush_slice(&queue, &["6", "7", "8"])
+            );
+            assert_eq!(
+                popped.unwrap(),
+                Some((queue.clone(), vec![b"6".to_vec(), b"7".to_vec()]))
+            );
+            redis.del(&queue).await.unwrap();
+
+            assert_eq!(
+                redis
+                    .blmove(&queue, &processing, ListSide::Left, ListSide::Left, 1)
+                    .await
+                    .unwrap(),
+                None
+            );
+            assert_eq!(
+                redis
+                    .blmpop(&[&queue, &empty], ListSide::Left, None, 1)
+                    .await
+                    .unwrap(),
+                None
+            );
+        },
+        queue,
+        processing,
+        empty
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn sscan() {
+    redis_test!(
+        redis,
+        {
+            //Verify that it handles an empty search correctly
+            let result: Vec<Vec<u8>> = redis.sscan(&some_set).run().try_collect().await.unwrap();
+            assert!(result.is_empty());
+
             let big_number = 100u8;
             //Populate some_set with a lot of members in order to ensure that we have to call SSCAN twice.
             let name_prefix = "foo";
@@ -392,7 +1109,7 @@ async fn sscan() {
 
             //Check that all values are received correctly
             let stream = redis.sscan(&some_set).pattern(b"foo.*").run();
-            let result: Vec<Vec<u8>> = stream.collect().await;
+            let result: Vec<Vec<u8>> = stream.try_collect().await.unwrap();
             let mut result: Vec<String> = result
                 .into_iter()
                 .map(|s| String::from_utf8(s).unwrap())
@@ -416,7 +1133,13 @@ async fn scan() {
         redis,
         {
             //Verify that it handles an empty search correctly
-            let result: Vec<Vec<u8>> = redis.scan().pattern(&key).run().collect().await;
+            let result: Vec<Vec<u8>> = redis
+                .scan()
+                .pattern(&key)
+                .run()
+                .try_collect()
+                .await
+                .unwrap();
             dbg!(&result);
             assert!(result.is_empty());
 
@@ -424,7 +1147,7 @@ async fn scan() {
             redis.set(&key, "dummy-data").await.unwrap();
 
             //Check that it's returned in the results
-            let result: Vec<Vec<u8>> = redis.scan().run().collect().await;
+            let result: Vec<Vec<u8>> = redis.scan().run().try_collect().await.unwrap();
             assert!(result.contains(&key));
         },
         key
@@ -438,7 +1161,8 @@ async fn hscan() {
         redis,
         {
             //Verify that it handles an empty search correctly
-            let result: Vec<(Vec<u8>, Vec<u8>)> = redis.hscan(&some_hash).run().collect().await;
+            let result: Vec<(Vec<u8>, Vec<u8>)> =
+                redis.hscan(&some_hash).run().try_collect().await.unwrap();
             assert!(result.is_empty());
 
             let big_number = 100u8;
@@ -454,7 +1178,7 @@ async fn hscan() {
 
             //Check that all values are received correctly
             let stream = redis.hscan(&some_hash).pattern(b"foo.*").run();
-            let result: Vec<(Vec<u8>, Vec<u8>)> = stream.collect().await;
+            let result: Vec<(Vec<u8>, Vec<u8>)> = stream.try_collect().await.unwrap();
             let result: Vec<(String, String)> = result
                 .into_iter()
                 .map(|(key, value)| {
@@ -474,6 +1198,60 @@ async fn hscan() {
     );
 }
 
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn zscan() {
+    redis_test!(
+        redis,
+        {
+            //Verify that it handles an empty search correctly
+            let result: Vec<(Vec<u8>, f64)> =
+                redis.zscan(&some_zset).run().try_collect().await.unwrap();
+            assert!(result.is_empty());
+
+            let big_number = 100u8;
+            //Populate some_zset with a lot of members in order to ensure that we have to call ZSCAN twice.
+            for i in 0..big_number {
+                redis
+                    .zadd(&some_zset, f64::from(i) / 2.0, format!("foo.{}", i))
+                    .await
+                    .unwrap();
+            }
+            redis.zadd(&some_zset, f64::INFINITY, "bar").await.unwrap();
+
+            //Check that all members are received correctly
+            let stream = redis.zscan(&some_zset).pattern(b"foo.*").count(10).run();
+            let result: Vec<(Vec<u8>, f64)> = stream.try_collect().await.unwrap();
+            assert_eq!(result.len(), big_number as usize);
+            for i in 0..big_number {
+                let member = format!("foo.{}", i).into_bytes();
+                assert!(result.contains(&(member, f64::from(i) / 2.0)));
+            }
+
+            let result: Vec<(Vec<u8>, f64)> = redis
+                .zscan(&some_zset)
+                .pattern(b"bar")
+                .run()
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(result, vec![(b"bar".to_vec(), f64::INFINITY)]);
+
+            //Most replies contain no matching members
+            let result: Vec<(Vec<u8>, f64)> = redis
+                .zscan(&some_zset)
+                .pattern(b"foo.5*")
+                .count(1)
+                .run()
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(result.len(), 11);
+        },
+        some_zset
+    );
+}
+
 #[cfg_attr(feature = "runtime_tokio", tokio::test)]
 #[cfg_attr(feature = "runtime_async_std", async_std::test)]
 async fn key_type() {
@@ -541,84 +1319,504 @@ async fn key_type() {
 
 #[cfg_attr(feature = "runtime_tokio", tokio::test)]
 #[cfg_attr(feature = "runtime_async_std", async_std::test)]
-async fn set_commands() {
+async fn key_lifecycle() {
     redis_test!(
         redis,
         {
-            //Setting, cardinality
-            assert!(redis.sadd(&set, "foo").await.unwrap());
-            assert!(!redis.sadd(&set, "foo").await.unwrap());
-            assert_eq!(redis.scard(&set).await.unwrap(), 1);
+            assert_eq!(redis.ttl(&key).await.unwrap(), Ttl::Missing);
+            assert_eq!(redis.pttl(&key).await.unwrap(), Ttl::Missing);
+            assert_eq!(redis.expiretime(&key).await.unwrap(), Ttl::Missing);
+            redis.set(&key, "value").await.unwrap();
+            assert_eq!(redis.ttl(&key).await.unwrap(), Ttl::Persistent);
+            assert_eq!(redis.pexpiretime(&key).await.unwrap(), Ttl::Persistent);
+            assert!(!redis.persist(&key).await.unwrap());
 
-            //Sismember, smembers
-            assert!(redis.sismember(&set, "foo").await.unwrap());
-            assert_eq!(redis.smembers(&set).await.unwrap(), vec![b"foo"]);
+            redis.expire_at_seconds(&key, 32_503_680_000).await.unwrap();
+            assert_eq!(
+                redis.expiretime(&key).await.unwrap(),
+                Ttl::Expires(32_503_680_000)
+            );
+            assert_eq!(
+                redis.pexpiretime(&key).await.unwrap(),
+                Ttl::Expires(32_503_680_000_000)
+            );
+            redis.expire_seconds(&key, 100).await.unwrap();
+            match redis.ttl(&key).await.unwrap() {
+                Ttl::Expires(ttl) => assert!(ttl > 90 && ttl <= 100),
+                other => panic!("unexpected TTL {:?}", other),
+            }
+            match redis.pttl(&key).await.unwrap() {
+                Ttl::Expires(ttl) => assert!(ttl > 90_000 && ttl <= 100_000),
+                other => panic!("unexpected TTL {:?}", other),
+            }
 
-            //Move, delete
-            assert!(redis.smove(&set, &other_set, "foo").await.unwrap());
-            assert!(!redis.sismember(&set, "foo").await.unwrap());
-            assert!(redis.sismember(&other_set, "foo").await.unwrap());
-            assert_eq!(redis.srem(&other_set, "foo").await.unwrap(), true);
+            //Renaming and copying keep the expiry
+            redis.rename(&key, &renamed).await.unwrap();
+            assert!(!redis.exists(&key).await.unwrap());
+            assert!(matches!(
+                redis.ttl(&renamed).await.unwrap(),
+                Ttl::Expires(_)
+            ));
+            assert!(redis.rename(&key, &renamed).await.is_err());
+            redis.set(&key, "other").await.unwrap();
+            assert!(!redis.renamenx(&key, &renamed).await.unwrap());
+            assert!(redis.copy(&renamed, &copied, false).await.unwrap());
+            assert!(!redis.copy(&key, &copied, false).await.unwrap());
+            assert_eq!(redis.get(&copied).await.unwrap(), Some(b"value".to_vec()));
+            assert!(matches!(redis.ttl(&copied).await.unwrap(), Ttl::Expires(_)));
+            assert!(redis.copy(&key, &copied, true).await.unwrap());
+            assert_eq!(redis.get(&copied).await.unwrap(), Some(b"other".to_vec()));
+            assert_eq!(redis.ttl(&copied).await.unwrap(), Ttl::Persistent);
+            assert!(redis.persist(&renamed).await.unwrap());
+            assert_eq!(redis.ttl(&renamed).await.unwrap(), Ttl::Persistent);
 
-            //Adding and deletion as a slice:
             assert_eq!(
                 redis
-                    .sadd_slice(&other_set, &["foo", "bar", "baz"])
+                    .exists_slice(&[&key, &renamed, &missing, &key])
                     .await
                     .unwrap(),
                 3
             );
+            assert!(redis.touch(&key).await.unwrap());
+            assert!(!redis.touch(&missing).await.unwrap());
+            assert_eq!(redis.touch_slice(&[&key, &missing]).await.unwrap(), 1);
+            assert!(redis.randomkey().await.unwrap().is_some());
+
+            assert!(redis.unlink(&key).await.unwrap());
+            assert!(!redis.unlink(&key).await.unwrap());
             assert_eq!(
                 redis
-                    .srem_slice(&other_set, &["foo", "bar", "baz"])
+                    .unlink_slice(&[&renamed, &copied, &missing])
                     .await
                     .unwrap(),
-                3
+                2
             );
+            assert!(redis.renamenx(&key, &renamed).await.is_err());
+        },
+        key,
+        renamed,
+        copied,
+        missing
+    );
+}
 
-            //Difference commands
-            assert!(redis.sadd(&set, "foo").await.unwrap());
-            assert!(redis.sadd(&set, "bar").await.unwrap());
-            assert!(redis.sadd(&other_set, "bar").await.unwrap());
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn object_info() {
+    redis_test!(
+        redis,
+        {
+            redis.set(&key, "12345").await.unwrap();
             assert_eq!(
-                redis.sdiff(&[&set, &other_set]).await.unwrap(),
-                vec![b"foo"]
+                redis.object_encoding(&key).await.unwrap(),
+                Some("int".to_string())
+            );
+            assert_eq!(redis.object_encoding(&missing).await.unwrap(), None);
+            assert!(redis.object_idletime(&key).await.unwrap().unwrap() >= 0);
+            assert_eq!(redis.object_idletime(&missing).await.unwrap(), None);
+            assert!(redis.memory_usage(&key, None).await.unwrap().unwrap() > 0);
+            assert!(redis.memory_usage(&key, Some(0)).await.unwrap().unwrap() > 0);
+            assert_eq!(redis.memory_usage(&missing, None).await.unwrap(), None);
+            //Only available with an LFU eviction policy, which isn't the default
+            assert!(redis.object_freq(&key).await.is_err());
+        },
+        key,
+        missing
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn sort() {
+    redis_test!(
+        redis,
+        {
+            let strings = |values: &[&str]| -> Vec<Option<Vec<u8>>> {
+                values.iter().map(|v| Some(v.as_bytes().to_vec())).collect()
+            };
+
+            redis.rpush_slice(&list, &["3", "1", "2"]).await.unwrap();
+            let weights = [(&weight1, "30"), (&weight2, "10"), (&weight3, "20")];
+            for (key, weight) in weights.iter() {
+                redis.set(key, weight).await.unwrap();
+            }
+            redis.hset(&hash1, "name", "carol").await.unwrap();
+            redis.hset(&hash2, "name", "alice").await.unwrap();
+
+            assert_eq!(
+                redis.sort(&list, SortBuilder::new()).await.unwrap(),
+                strings(&["1", "2", "3"])
             );
             assert_eq!(
                 redis
-                    .sdiffstore(&diff_set, &[&set, &other_set])
+                    .sort_ro(&list, SortBuilder::new().desc().limit(0, 2))
                     .await
                     .unwrap(),
-                1
+                strings(&["3", "2"])
             );
-            assert_eq!(redis.smembers(&diff_set).await.unwrap(), vec![b"foo"]);
 
-            //intersect commands
+            let by = format!(
+                "{}*",
+                String::from_utf8_lossy(&weight1[..weight1.len() - 1])
+            );
             assert_eq!(
-                redis.sinter(&[&set, &other_set]).await.unwrap(),
-                vec![b"bar"]
+                redis.sort(&list, SortBuilder::new().by(&by)).await.unwrap(),
+                strings(&["2", "3", "1"])
+            );
+
+            let get = format!(
+                "{}*->name",
+                String::from_utf8_lossy(&hash1[..hash1.len() - 1])
+            );
+            let builder = SortBuilder::new().get("#").get(&get);
+            assert_eq!(
+                redis.sort(&list, builder.clone()).await.unwrap(),
+                vec![
+                    Some(b"1".to_vec()),
+                    Some(b"carol".to_vec()),
+                    Some(b"2".to_vec()),
+                    Some(b"alice".to_vec()),
+                    Some(b"3".to_vec()),
+                    None
+                ]
             );
             assert_eq!(
                 redis
-                    .sinterstore(&diff_set, &[&set, &other_set])
+                    .sort_store(&list, &stored, builder.desc())
                     .await
                     .unwrap(),
-                1
+                6
             );
-            assert_eq!(redis.smembers(&diff_set).await.unwrap(), vec![b"bar"]);
+            assert_eq!(redis.llen(&stored).await.unwrap(), Some(6));
 
-            //get random members
+            redis.sadd_slice(&set, &["b", "c", "a"]).await.unwrap();
+            assert!(redis.sort(&set, SortBuilder::new()).await.is_err());
             assert_eq!(
-                redis.srandmember(&other_set, 1).await.unwrap(),
-                vec![b"bar"]
+                redis.sort(&set, SortBuilder::new().alpha()).await.unwrap(),
+                strings(&["a", "b", "c"])
             );
-            assert_eq!(redis.spop(&other_set, 1).await.unwrap(), vec![b"bar"]);
+        },
+        list,
+        set,
+        stored,
+        weight1,
+        weight2,
+        weight3,
+        hash1,
+        hash2
+    );
+}
 
-            //Union commands
-            let union = redis.sunion(&[&set, &other_set]).await.unwrap();
-            assert!(union.contains(&b"foo".to_vec()));
-            assert!(union.contains(&b"bar".to_vec()));
-            redis
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn dump_restore() {
+    redis_test!(
+        redis,
+        {
+            assert_eq!(redis.dump(&source).await.unwrap(), None);
+            redis.rpush_slice(&source, &["a", "b"]).await.unwrap();
+            let payload = redis.dump(&source).await.unwrap().unwrap();
+
+            redis
+                .restore(&copy, 10_000, &payload, RestoreOptions::new())
+                .await
+                .unwrap();
+            assert_eq!(
+                redis.lrange(&copy, 0, -1).await.unwrap(),
+                vec![b"a".to_vec(), b"b".to_vec()]
+            );
+            match redis.pttl(&copy).await.unwrap() {
+                Ttl::Expires(ms) => assert!(ms > 0 && ms <= 10_000),
+                other => panic!("unexpected TTL {:?}", other),
+            }
+
+            //The key already exists
+            assert!(redis
+                .restore(&copy, 0, &payload, RestoreOptions::new())
+                .await
+                .is_err());
+            let options = RestoreOptions::new().replace().idletime(100);
+            redis.restore(&copy, 0, &payload, options).await.unwrap();
+            assert_eq!(redis.pttl(&copy).await.unwrap(), Ttl::Persistent);
+
+            let options = RestoreOptions::new().replace().absttl();
+            redis
+                .restore(&copy, 32_503_680_000_000, &payload, options)
+                .await
+                .unwrap();
+            assert_eq!(
+                redis.pexpiretime(&copy).await.unwrap(),
+                Ttl::Expires(32_503_680_000_000)
+            );
+
+            assert!(redis
+                .restore(&copy, 0, "invalid", RestoreOptions::new().replace())
+                .await
+                .is_err());
+        },
+        source,
+        copy
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn migrate() {
+    redis_test!(
+        redis,
+        {
+            let empty: &[&[u8]] = &[];
+            assert!(redis
+                .migrate("127.0.0.1", 1, empty, 0, 100, MigrateOptions::new())
+                .await
+                .is_err());
+
+            //None of the keys exist, so nothing is sent
+            let keys = [&key];
+            let options = MigrateOptions::new().copy().replace();
+            assert!(!redis
+                .migrate("127.0.0.1", 1, &keys, 0, 100, options)
+                .await
+                .unwrap());
+
+            redis.set(&key, "value").await.unwrap();
+            assert!(redis
+                .migrate("127.0.0.1", 1, &keys, 0, 100, options.auth("password"))
+                .await
+                .is_err());
+            assert!(redis.exists(&key).await.unwrap());
+        },
+        key
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn copy_keys() {
+    redis_test!(
+        redis,
+        {
+            redis
+                .set_and_expire_ms(&string, "value", 60_000)
+                .await
+                .unwrap();
+            redis.rpush(&list, "element").await.unwrap();
+            redis.hset(&hash, "field", "value").await.unwrap();
+            let pattern = format!("{}*", String::from_utf8_lossy(&string[..string.len() - 6]));
+
+            //The destination is the same instance, so every key already exists.
+            let mut destination = Connection::connect(TEST_ADDRESS).await.unwrap();
+            let mut source = redis.clone();
+            let progress: Vec<_> = source
+                .copy_keys_to(&pattern, &mut destination, 2, false)
+                .try_collect()
+                .await
+                .unwrap();
+            assert!(progress.iter().all(|p| p.keys.is_empty()));
+            assert_eq!(progress.iter().map(|p| p.failed.len()).sum::<usize>(), 3);
+
+            let mut source = redis.clone();
+            let progress: Vec<_> = source
+                .copy_keys_to(&pattern, &mut destination, 2, true)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(progress.len(), 2);
+            assert_eq!(progress[0].keys.len(), 2);
+            assert_eq!(progress[1].copied, 3);
+            assert_eq!(progress[1].skipped, 0);
+            let mut copied: Vec<_> = progress.into_iter().flat_map(|p| p.keys).collect();
+            copied.sort();
+            let mut expected = vec![string.clone(), list.clone(), hash.clone()];
+            expected.sort();
+            assert_eq!(copied, expected);
+
+            assert_eq!(redis.get(&string).await.unwrap(), Some(b"value".to_vec()));
+            match redis.pttl(&string).await.unwrap() {
+                Ttl::Expires(ms) => assert!(ms > 0 && ms <= 60_000),
+                other => panic!("unexpected TTL {:?}", other),
+            }
+            assert_eq!(redis.pttl(&list).await.unwrap(), Ttl::Persistent);
+            assert_eq!(
+                redis.hget(&hash, "field").await.unwrap(),
+                Some(b"value".to_vec())
+            );
+        },
+        string,
+        list,
+        hash
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn copy_keys_existing() {
+    redis_test!(
+        redis,
+        {
+            redis.set(&first, "1").await.unwrap();
+            redis.set(&second, "2").await.unwrap();
+            let pattern = format!("{}*", String::from_utf8_lossy(&first[..first.len() - 5]));
+
+            //Copy to another database, where one of the keys already exists.
+            let mut destination = Connection::connect(TEST_ADDRESS).await.unwrap();
+            destination
+                .run_command(Command::new("SELECT").arg(b"1"))
+                .await
+                .unwrap();
+            destination.set(&first, "existing").await.unwrap();
+            let mut source = redis.clone();
+            let progress: Vec<_> = source
+                .copy_keys_to(&pattern, &mut destination, 10, false)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(progress.len(), 1);
+            assert_eq!(progress[0].keys, vec![second.clone()]);
+            assert_eq!(progress[0].copied, 1);
+            match &progress[0].failed[..] {
+                [(key, Error::RedisError(e))] => {
+                    assert_eq!(key, &first);
+                    assert!(e.starts_with("BUSYKEY"), "{}", e);
+                }
+                other => panic!("expected one BUSYKEY error, got {:?}", other),
+            }
+
+            assert_eq!(
+                destination.get(&first).await.unwrap(),
+                Some(b"existing".to_vec())
+            );
+            assert_eq!(destination.get(&second).await.unwrap(), Some(b"2".to_vec()));
+            destination.del_slice(&[&first, &second]).await.unwrap();
+        },
+        first,
+        second
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn copy_keys_sparse() {
+    redis_test!(
+        redis,
+        {
+            //Most SCAN replies contain no matching keys when only a few keys match.
+            let name = |kind: &str, i: usize| {
+                let mut key = prefix.clone();
+                key.extend_from_slice(format!(".{}.{}", kind, i).as_bytes());
+                key
+            };
+            let matching: Vec<_> = (0..3).map(|i| name("match", i)).collect();
+            let other: Vec<_> = (0..30).map(|i| name("ignored", i)).collect();
+            for key in matching.iter().chain(&other) {
+                redis.set(key, "value").await.unwrap();
+            }
+            let pattern = format!("{}.match.*", String::from_utf8_lossy(&prefix));
+
+            let mut destination = Connection::connect(TEST_ADDRESS).await.unwrap();
+            destination
+                .run_command(Command::new("SELECT").arg(b"1"))
+                .await
+                .unwrap();
+            let mut source = redis.clone();
+            let progress: Vec<_> = source
+                .copy_keys_to(&pattern, &mut destination, 1, false)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(progress.last().unwrap().copied, matching.len());
+            for key in &matching {
+                assert_eq!(destination.get(key).await.unwrap(), Some(b"value".to_vec()));
+            }
+
+            destination.del_slice(&matching).await.unwrap();
+            redis.del_slice(&matching).await.unwrap();
+            redis.del_slice(&other).await.unwrap();
+        },
+        prefix
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn set_commands() {
+    redis_test!(
+        redis,
+        {
+            //Setting, cardinality
+            assert!(redis.sadd(&set, "foo").await.unwrap());
+            assert!(!redis.sadd(&set, "foo").await.unwrap());
+            assert_eq!(redis.scard(&set).await.unwrap(), 1);
+
+            //Sismember, smembers
+            assert!(redis.sismember(&set, "foo").await.unwrap());
+            assert_eq!(redis.smembers(&set).await.unwrap(), vec![b"foo"]);
+
+            //Move, delete
+            assert!(redis.smove(&set, &other_set, "foo").await.unwrap());
+            assert!(!redis.sismember(&set, "foo").await.unwrap());
+            assert!(redis.sismember(&other_set, "foo").await.unwrap());
+            assert!(redis.srem(&other_set, "foo").await.unwrap());
+
+            //Adding and deletion as a slice:
+            assert_eq!(
+                redis
+                    .sadd_slice(&other_set, &["foo", "bar", "baz"])
+                    .await
+                    .unwrap(),
+                3
+            );
+            assert_eq!(
+                redis
+                    .srem_slice(&other_set, &["foo", "bar", "baz"])
+                    .await
+                    .unwrap(),
+                3
+            );
+
+            //Difference commands
+            assert!(redis.sadd(&set, "foo").await.unwrap());
+            assert!(redis.sadd(&set, "bar").await.unwrap());
+            assert!(redis.sadd(&other_set, "bar").await.unwrap());
+            assert_eq!(
+                redis.sdiff(&[&set, &other_set]).await.unwrap(),
+                vec![b"foo"]
+            );
+            assert_eq!(
+                redis
+                    .sdiffstore(&diff_set, &[&set, &other_set])
+                    .await
+                    .unwrap(),
+                1
+            );
+            assert_eq!(redis.smembers(&diff_set).await.unwrap(), vec![b"foo"]);
+
+            //intersect commands
+            assert_eq!(
+                redis.sinter(&[&set, &other_set]).await.unwrap(),
+                vec![b"bar"]
+            );
+            assert_eq!(
+                redis
+                    .sinterstore(&diff_set, &[&set, &other_set])
+                    .await
+                    .unwrap(),
+                1
+            );
+            assert_eq!(redis.smembers(&diff_set).await.unwrap(), vec![b"bar"]);
+
+            //get random members
+            assert_eq!(
+                redis.srandmember(&other_set, 1).await.unwrap(),
+                vec![b"bar"]
+            );
+            assert_eq!(redis.spop(&other_set, 1).await.unwrap(), vec![b"bar"]);
+
+            //Union commands
+            let union = redis.sunion(&[&set, &other_set]).await.unwrap();
+            assert!(union.contains(&b"foo".to_vec()));
+            assert!(union.contains(&b"bar".to_vec()));
+            redis
                 .sunionstore(&diff_set, &[&set, &other_set])
                 .await
                 .unwrap();
@@ -631,3 +1829,1028 @@ async fn set_commands() {
         diff_set
     );
 }
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn resp3() {
+    redis_test!(
+        redis,
+        {
+            assert!(matches!(
+                redis.hello(ProtocolVersion::Resp3).await.unwrap(),
+                Value::Map(_)
+            ));
+
+            redis.hset(&hash, "field", "value").await.unwrap();
+            let command = Command::new("HGETALL").arg(&hash);
+            assert_eq!(
+                redis.run_command(command).await.unwrap(),
+                Value::Map(vec![(
+                    Value::String(b"field".to_vec()),
+                    Value::String(b"value".to_vec())
+                )])
+            );
+            assert_eq!(redis.get(&set).await.unwrap(), None);
+
+            //Convenience methods keep working with sets
+            redis.sadd(&set, "member").await.unwrap();
+            assert_eq!(redis.smembers(&set).await.unwrap(), vec![b"member"]);
+
+            redis.hello(ProtocolVersion::Resp2).await.unwrap();
+        },
+        hash,
+        set
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn ping() {
+    let mut redis = Connection::connect(TEST_ADDRESS).await.unwrap();
+    redis.ping().await.unwrap();
+    assert_eq!(
+        redis.run_command(Command::new("PING")).await.unwrap(),
+        Value::Status("PONG".to_string())
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn transactions() {
+    redis_test!(
+        redis,
+        {
+            let transaction = Transaction::new()
+                .command(Command::new("SET").arg(&key).arg(1))
+                .command(Command::new("INCRBY").arg(&key).arg(2))
+                .command(Command::new("LPUSH").arg(&key).arg("wrong type"));
+            let result: Vec<Value> = redis.run_transaction(transaction).await.unwrap().unwrap();
+            assert_eq!(result[0], Value::Ok);
+            assert_eq!(result[1], Value::Integer(3));
+            assert!(result[2].is_error());
+
+            //Typed results
+            let transaction = Transaction::new()
+                .command(Command::new("INCR").arg(&key))
+                .command(Command::new("GET").arg(&key));
+            let result: Option<(isize, String)> = redis.run_transaction(transaction).await.unwrap();
+            assert_eq!(result, Some((4, "4".to_string())));
+
+            //A command which fails to queue aborts the transaction
+            let transaction = Transaction::new()
+                .command(Command::new("INCR").arg(&key))
+                .command(Command::new("NOTACOMMAND"));
+            match redis.run_transaction::<Vec<Value>>(transaction).await {
+                Err(Error::RedisError(e)) => assert!(e.starts_with("ERR unknown command")),
+                other => panic!("expected error, got {:?}", other),
+            }
+            assert_eq!(redis.get(&key).await.unwrap(), Some(b"4".to_vec()));
+        },
+        key
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn watch_transaction() {
+    redis_test!(
+        redis,
+        {
+            let mut other = Connection::connect(TEST_ADDRESS).await.unwrap();
+            redis.set(&key, "1").await.unwrap();
+
+            //Modify the key from another connection on the first attempt, forcing a retry
+            let mut attempts = 0;
+            let ((), value): ((), isize) = redis
+                .watch_transaction(&[&key], |mut connection| {
+                    attempts += 1;
+                    let first_attempt = attempts == 1;
+                    let mut other = other.clone();
+                    let key = key.clone();
+                    async move {
+                        let value: isize = connection.query(Command::new("GET").arg(&key)).await?;
+                        if first_attempt {
+                            other.incr(&key).await?;
+                        }
+                        Ok(Transaction::new()
+                            .command(Command::new("SET").arg(key.clone()).arg(value * 10))
+                            .command(Command::new("GET").arg(key)))
+                    }
+                })
+                .await
+                .unwrap();
+            assert_eq!(attempts, 2);
+            assert_eq!(value, 20);
+
+            //Errors from the closure are returned
+            let result: Result<()> = redis
+                .watch_transaction(&[&key], |_| async {
+                    Err(Error::UnexpectedResponse("oops".to_string()))
+                })
+                .await;
+            assert!(result.is_err());
+            other.del(&key).await.unwrap();
+        },
+        key
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn scripts() {
+    redis_test!(
+        redis,
+        {
+            let script = Script::new("return redis.call('SET', KEYS[1], ARGV[1])");
+            redis.script_flush().await.unwrap();
+            assert_eq!(
+                redis.script_exists(&[script.hash()]).await.unwrap(),
+                vec![false]
+            );
+
+            //Falls back to EVAL, which caches the script
+            script
+                .key(&key)
+                .arg(1)
+                .invoke::<()>(&mut redis)
+                .await
+                .unwrap();
+            assert_eq!(
+                redis.script_exists(&[script.hash()]).await.unwrap(),
+                vec![true]
+            );
+            script
+                .key(&key)
+                .arg(2)
+                .invoke::<()>(&mut redis)
+                .await
+                .unwrap();
+            assert_eq!(redis.get(&key).await.unwrap(), Some(b"2".to_vec()));
+
+            let other = Script::new("return {KEYS[1], ARGV[1], ARGV[2]}");
+            redis.script_flush().await.unwrap();
+            other.load(&mut redis).await.unwrap();
+            let result: (String, String, isize) = other
+                .key("a")
+                .args(&["b", "3"])
+                .invoke(&mut redis)
+                .await
+                .unwrap();
+            assert_eq!(result, ("a".to_string(), "b".to_string(), 3));
+        },
+        key
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn functions() {
+    redis_test!(
+        redis,
+        {
+            let library = "#!lua name=darkredis_test\n\
+                redis.register_function('darkredis_set', function(keys, args) return redis.call('SET', keys[1], args[1]) end)\n\
+                redis.register_function{function_name='darkredis_get', callback=function(keys, args) return redis.call('GET', keys[1]) end, flags={'no-writes'}}";
+            let path = std::env::temp_dir().join("darkredis_test.lua");
+            std::fs::write(&path, library).unwrap();
+            let _ = redis.function_delete("darkredis_test").await;
+
+            assert_eq!(
+                redis.function_load_file(&path, false).await.unwrap(),
+                "darkredis_test"
+            );
+            assert!(redis.function_load(library, false).await.is_err());
+            assert_eq!(
+                redis.function_load(library, true).await.unwrap(),
+                "darkredis_test"
+            );
+            std::fs::remove_file(&path).unwrap();
+
+            let libraries = redis
+                .function_list(Some("darkredis_test"), true)
+                .await
+                .unwrap();
+            assert_eq!(libraries.len(), 1);
+            assert_eq!(libraries[0].name, "darkredis_test");
+            assert_eq!(libraries[0].code.as_deref(), Some(library));
+            let mut functions = libraries[0].functions.clone();
+            functions.sort_by(|a, b| a.name.cmp(&b.name));
+            assert_eq!(functions[0].name, "darkredis_get");
+            assert_eq!(functions[0].flags, vec!["no-writes".to_string()]);
+            assert!(functions[1].flags.is_empty());
+
+            let no_args: &[&str] = &[];
+            redis
+                .fcall::<_, _, _, ()>("darkredis_set", &[&key], &["value"])
+                .await
+                .unwrap();
+            let value: Option<String> = redis
+                .fcall_ro("darkredis_get", &[&key], no_args)
+                .await
+                .unwrap();
+            assert_eq!(value, Some("value".to_string()));
+            assert!(redis
+                .fcall_ro::<_, _, _, ()>("darkredis_set", &[&key], &["value"])
+                .await
+                .is_err());
+
+            let dump = redis.function_dump().await.unwrap();
+            redis.function_delete("darkredis_test").await.unwrap();
+            assert!(redis.function_delete("darkredis_test").await.is_err());
+            assert!(redis
+                .function_list(Some("darkredis_test"), false)
+                .await
+                .unwrap()
+                .is_empty());
+            redis
+                .function_restore(&dump, Some(FunctionRestorePolicy::Replace))
+                .await
+                .unwrap();
+            assert_eq!(
+                redis
+                    .function_list(Some("darkredis_test"), false)
+                    .await
+                    .unwrap()
+                    .len(),
+                1
+            );
+            redis.function_delete("darkredis_test").await.unwrap();
+        },
+        key
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn sorted_sets() {
+    redis_test!(
+        redis,
+        {
+            assert!(redis.zadd(&zset, 1.0, "one").await.unwrap());
+            assert!(!redis.zadd(&zset, 1.5, "one").await.unwrap());
+            let builder = ZAddBuilder::new().add(2.0, "two").add(3.0, "three");
+            assert_eq!(redis.zadd_many(&zset, builder).await.unwrap(), 2);
+            let builder = ZAddBuilder::new()
+                .add(1.0, "one")
+                .add(4.0, "four")
+                .xx()
+                .gt()
+                .ch();
+            assert_eq!(redis.zadd_many(&zset, builder).await.unwrap(), 0);
+            let builder = ZAddBuilder::new().add(1.0, "one").lt().ch();
+            assert_eq!(redis.zadd_many(&zset, builder).await.unwrap(), 1);
+            //Only the last of NX, GT and LT is used
+            let builder = ZAddBuilder::new().add(5.0, "five").gt().nx();
+            assert_eq!(redis.zadd_many(&zset, builder).await.unwrap(), 1);
+            let builder = ZAddBuilder::new().add(6.0, "five").nx().gt().ch();
+            assert_eq!(redis.zadd_many(&zset, builder).await.unwrap(), 1);
+            let builder = ZAddBuilder::new().add(1.0, "five").gt().lt().ch();
+            assert_eq!(redis.zadd_many(&zset, builder).await.unwrap(), 1);
+            assert!(redis.zrem(&zset, "five").await.unwrap());
+
+            assert_eq!(
+                redis
+                    .zadd_incr(&zset, ZAddBuilder::new().add(2.0, "one"))
+                    .await
+                    .unwrap(),
+                Some(3.0)
+            );
+            assert_eq!(
+                redis
+                    .zadd_incr(&zset, ZAddBuilder::new().add(2.0, "one").nx())
+                    .await
+                    .unwrap(),
+                None
+            );
+            assert_eq!(redis.zincrby(&zset, -2.5, "one").await.unwrap(), 0.5);
+
+            assert_eq!(redis.zcard(&zset).await.unwrap(), 3);
+            assert_eq!(redis.zscore(&zset, "two").await.unwrap(), Some(2.0));
+            assert_eq!(redis.zscore(&zset, "four").await.unwrap(), None);
+            assert_eq!(
+                redis.zmscore(&zset, &["one", "four"]).await.unwrap(),
+                vec![Some(0.5), None]
+            );
+            assert_eq!(redis.zrank(&zset, "three").await.unwrap(), Some(2));
+            assert_eq!(redis.zrevrank(&zset, "three").await.unwrap(), Some(0));
+            assert_eq!(redis.zrank(&zset, "four").await.unwrap(), None);
+            assert_eq!(redis.zcount(&zset, 1, "+inf").await.unwrap(), 2);
+            assert_eq!(redis.zcount(&zset, "(0.5", 2).await.unwrap(), 1);
+
+            assert_eq!(
+                redis
+                    .zrange(&zset, ZRangeBuilder::new(0, -1))
+                    .await
+                    .unwrap(),
+                vec![b"one".to_vec(), b"two".to_vec(), b"three".to_vec()]
+            );
+            let range = ZRangeBuilder::new("+inf", 1).by_score().rev().limit(1, 5);
+            assert_eq!(
+                redis.zrange_withscores(&zset, range).await.unwrap(),
+                vec![(b"two".to_vec(), 2.0)]
+            );
+            let range = ZRangeBuilder::new("[t", "+").by_lex();
+            assert_eq!(
+                redis.zrange(&lex, range.clone()).await.unwrap(),
+                Vec::<Vec<u8>>::new()
+            );
+
+            assert_eq!(redis.zrem_slice(&zset, &["two", "four"]).await.unwrap(), 1);
+            assert!(redis.zrem(&zset, "three").await.unwrap());
+            assert!(redis
+                .zrange_withscores(&zset, range)
+                .await
+                .unwrap()
+                .is_empty());
+            assert!(redis.zrem_slice::<_, &str>(&zset, &[]).await.is_err());
+        },
+        zset,
+        lex
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn sorted_set_pops_and_stores() {
+    redis_test!(
+        redis,
+        {
+            //Scores are doubles in RESP3, and pairs are nested arrays
+            for protocol in [ProtocolVersion::Resp2, ProtocolVersion::Resp3] {
+                redis.hello(protocol).await.unwrap();
+                let builder = ZAddBuilder::new().add(1.0, "a").add(2.0, "b").add(3.0, "c");
+                redis.zadd_many(&first, builder).await.unwrap();
+                let builder = ZAddBuilder::new().add(10.0, "b").add(20.0, "d");
+                redis.zadd_many(&second, builder).await.unwrap();
+
+                assert_eq!(
+                    redis
+                        .zunionstore(&destination, &[&first, &second], None, None)
+                        .await
+                        .unwrap(),
+                    4
+                );
+                assert_eq!(redis.zscore(&destination, "b").await.unwrap(), Some(12.0));
+                assert_eq!(
+                    redis
+                        .zinterstore(
+                            &destination,
+                            &[&first, &second],
+                            Some(&[2.0, 1.0]),
+                            Some(Aggregate::Max)
+                        )
+                        .await
+                        .unwrap(),
+                    1
+                );
+                assert_eq!(
+                    redis
+                        .zrange_withscores(&destination, ZRangeBuilder::new(0, -1))
+                        .await
+                        .unwrap(),
+                    vec![(b"b".to_vec(), 10.0)]
+                );
+
+                assert_eq!(
+                    redis.zpopmin(&first, 2).await.unwrap(),
+                    vec![(b"a".to_vec(), 1.0), (b"b".to_vec(), 2.0)]
+                );
+                assert_eq!(
+                    redis.zpopmax(&second, 1).await.unwrap(),
+                    vec![(b"d".to_vec(), 20.0)]
+                );
+                assert_eq!(
+                    redis.bzpopmin(&[&destination, &first], 1).await.unwrap(),
+                    Some((destination.clone(), b"b".to_vec(), 10.0))
+                );
+                assert_eq!(
+                    redis.bzpopmax(&[&destination, &first], 1).await.unwrap(),
+                    Some((first.clone(), b"c".to_vec(), 3.0))
+                );
+                redis.del(&second).await.unwrap();
+            }
+            redis.hello(ProtocolVersion::Resp2).await.unwrap();
+        },
+        first,
+        second,
+        destination
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn geo() {
+    redis_test!(
+        redis,
+        {
+            let palermo = GeoPosition::new(13.361389, 38.115556);
+            let catania = GeoPosition::new(15.087269, 37.502669);
+            assert!(redis.geoadd(&places, palermo, "Palermo").await.unwrap());
+            assert!(!redis.geoadd(&places, palermo, "Palermo").await.unwrap());
+            let more = [
+                (catania, "Catania"),
+                (GeoPosition::new(12.758489, 38.788135), "edge1"),
+                (GeoPosition::new(17.241510, 38.788135), "edge2"),
+            ];
+            assert_eq!(redis.geoadd_slice(&places, &more).await.unwrap(), 3);
+            assert!(redis
+                .geoadd(&places, GeoPosition::new(0.0, 90.0), "pole")
+                .await
+                .is_err());
+
+            let positions = redis
+                .geopos(&places, &["Palermo", "nowhere"])
+                .await
+                .unwrap();
+            let position = positions[0].unwrap();
+            assert!((position.longitude - palermo.longitude).abs() < 0.0001);
+            assert!((position.latitude - palermo.latitude).abs() < 0.0001);
+            assert_eq!(positions[1], None);
+
+            let distance = redis
+                .geodist(&places, "Palermo", "Catania", GeoUnit::Kilometers)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!((distance - 166.2742).abs() < 0.01);
+            assert_eq!(
+                redis
+                    .geodist(&places, "Palermo", "nowhere", GeoUnit::Meters)
+                    .await
+                    .unwrap(),
+                None
+            );
+
+            let hashes = redis
+                .geohash(&places, &["Palermo", "nowhere"])
+                .await
+                .unwrap();
+            assert_eq!(hashes[0].as_ref().map(String::len), Some(11));
+            assert!(hashes[0].as_ref().unwrap().starts_with("sqc8b49rny"));
+            assert_eq!(hashes[1], None);
+
+            let shape = GeoShape::Radius {
+                radius: 200.0,
+                unit: GeoUnit::Kilometers,
+            };
+            let search = GeoSearchBuilder::from_position(GeoPosition::new(15.0, 37.0), shape)
+                .asc()
+                .with_coord()
+                .with_dist()
+                .with_hash();
+            let results = redis.geosearch(&places, search).await.unwrap();
+            let members: Vec<_> = results.iter().map(|r| r.member.as_slice()).collect();
+            assert_eq!(members, vec![&b"Catania"[..], b"Palermo"]);
+            assert!((results[0].distance.unwrap() - 56.4413).abs() < 0.01);
+            assert!(results[0].hash.is_some());
+            let position = results[1].position.unwrap();
+            assert!((position.longitude - palermo.longitude).abs() < 0.0001);
+
+            let shape = GeoShape::Box {
+                width: 400.0,
+                height: 400.0,
+                unit: GeoUnit::Kilometers,
+            };
+            let search = GeoSearchBuilder::from_position(GeoPosition::new(15.0, 37.0), shape)
+                .desc()
+                .count(3);
+            let results = redis.geosearch(&places, search).await.unwrap();
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[0].member, b"edge1");
+            assert_eq!(results[0].distance, None);
+            let search = GeoSearchBuilder::from_member("Palermo", shape).count_any(1);
+            assert_eq!(redis.geosearch(&places, search).await.unwrap().len(), 1);
+
+            let search = GeoSearchBuilder::from_member("Catania", shape).with_dist();
+            assert_eq!(
+                redis
+                    .geosearchstore(&stored, &places, search.clone(), false)
+                    .await
+                    .unwrap(),
+                3
+            );
+            assert!(redis.geopos(&stored, &["edge2"]).await.unwrap()[0].is_some());
+            assert_eq!(
+                redis
+                    .geosearchstore(&stored, &places, search, true)
+                    .await
+                    .unwrap(),
+                3
+            );
+            assert_eq!(redis.zscore(&stored, "Catania").await.unwrap(), Some(0.0));
+
+            let search = GeoSearchBuilder::from_member("nowhere", shape);
+            assert!(redis.geosearch(&places, search).await.is_err());
+        },
+        places,
+        stored
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn hyperloglog() {
+    redis_test!(
+        redis,
+        {
+            assert!(redis.pfadd(&visitors1, "alice").await.unwrap());
+            assert!(!redis.pfadd(&visitors1, "alice").await.unwrap());
+            assert!(redis
+                .pfadd_slice(&visitors1, &["bob", "carol"])
+                .await
+                .unwrap());
+            assert!(redis
+                .pfadd_slice(&visitors2, &["carol", "dave"])
+                .await
+                .unwrap());
+            assert!(redis.pfadd_slice::<_, &str>(&visitors2, &[]).await.is_err());
+
+            assert_eq!(redis.pfcount(&visitors1).await.unwrap(), 3);
+            assert_eq!(redis.pfcount(&merged).await.unwrap(), 0);
+            assert_eq!(
+                redis
+                    .pfcount_slice(&[&visitors1, &visitors2])
+                    .await
+                    .unwrap(),
+                4
+            );
+
+            redis
+                .pfmerge(&merged, &[&visitors1, &visitors2])
+                .await
+                .unwrap();
+            assert_eq!(redis.pfcount(&merged).await.unwrap(), 4);
+            assert_eq!(
+                redis.key_type(&merged).await.unwrap(),
+                Some(DataType::String)
+            );
+        },
+        visitors1,
+        visitors2,
+        merged
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn bitmaps() {
+    redis_test!(
+        redis,
+        {
+            assert!(!redis.setbit(&bits1, 1, true).await.unwrap());
+            assert!(redis.setbit(&bits1, 1, true).await.unwrap());
+            redis.setbit(&bits1, 7, true).await.unwrap();
+            redis.setbit(&bits1, 9, true).await.unwrap();
+            assert!(redis.getbit(&bits1, 7).await.unwrap());
+            assert!(!redis.getbit(&bits1, 8).await.unwrap());
+            assert!(!redis.getbit(&bits1, 100).await.unwrap());
+
+            assert_eq!(redis.bitcount(&bits1, None).await.unwrap(), 3);
+            assert_eq!(
+                redis
+                    .bitcount(&bits1, Some(BitRange::Bytes(1, -1)))
+                    .await
+                    .unwrap(),
+                1
+            );
+            assert_eq!(
+                redis
+                    .bitcount(&bits1, Some(BitRange::Bits(0, 7)))
+                    .await
+                    .unwrap(),
+                2
+            );
+            assert_eq!(redis.bitcount(&missing, None).await.unwrap(), 0);
+
+            assert_eq!(redis.bitpos(&bits1, true, None).await.unwrap(), 1);
+            assert_eq!(redis.bitpos(&bits1, false, None).await.unwrap(), 0);
+            assert_eq!(
+                redis
+                    .bitpos(&bits1, true, Some(BitRange::Bits(2, -1)))
+                    .await
+                    .unwrap(),
+                7
+            );
+            assert_eq!(
+                redis
+                    .bitpos(&bits1, true, Some(BitRange::Bytes(1, 1)))
+                    .await
+                    .unwrap(),
+                9
+            );
+            assert_eq!(redis.bitpos(&missing, true, None).await.unwrap(), -1);
+
+            redis.set(&bits2, b"\xff\x0f").await.unwrap();
+            assert_eq!(
+                redis
+                    .bitop(BitOp::And, &dest, &[&bits1, &bits2])
+                    .await
+                    .unwrap(),
+                2
+            );
+            assert_eq!(redis.get(&dest).await.unwrap(), Some(b"\x41\x00".to_vec()));
+            redis
+                .bitop(BitOp::Or, &dest, &[&bits1, &bits2])
+                .await
+                .unwrap();
+            assert_eq!(redis.get(&dest).await.unwrap(), Some(b"\xff\x4f".to_vec()));
+            redis
+                .bitop(BitOp::Xor, &dest, &[&bits1, &bits2])
+                .await
+                .unwrap();
+            assert_eq!(redis.get(&dest).await.unwrap(), Some(b"\xbe\x4f".to_vec()));
+            redis.bitop(BitOp::Not, &dest, &[&bits2]).await.unwrap();
+            assert_eq!(redis.get(&dest).await.unwrap(), Some(b"\x00\xf0".to_vec()));
+            assert!(redis
+                .bitop(BitOp::Not, &dest, &[&bits1, &bits2])
+                .await
+                .is_err());
+
+            let byte = BitFieldType::Signed(8);
+            let builder = BitFieldBuilder::new()
+                .set(byte, 0, 100)
+                .incrby(byte, 0, 50)
+                .overflow(BitFieldOverflow::Sat)
+                .incrby(byte, 0, 300)
+                .overflow(BitFieldOverflow::Fail)
+                .incrby(byte, 0, 1)
+                .get(BitFieldType::Unsigned(4), 0)
+                .set(BitFieldType::Unsigned(16), 8, 65535);
+            assert_eq!(
+                redis.bitfield(&field, builder).await.unwrap(),
+                vec![Some(0), Some(-106), Some(127), None, Some(7), Some(0)]
+            );
+            let fields = [(byte, 0), (BitFieldType::Unsigned(16), 8), (byte, 24)];
+            assert_eq!(
+                redis.bitfield_ro(&field, &fields).await.unwrap(),
+                vec![127, 65535, 0]
+            );
+        },
+        bits1,
+        bits2,
+        dest,
+        field,
+        missing
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn streams() {
+    redis_test!(
+        redis,
+        {
+            let fields = MSetBuilder::new().set(b"a", b"1").set(b"b", b"2");
+            let first = redis
+                .xadd(&stream, Some(StreamId::new(1, 0)), fields, None)
+                .await
+                .unwrap();
+            assert_eq!(first, StreamId::new(1, 0));
+            let fields = MSetBuilder::new().set(b"a", b"1");
+            assert!(redis
+                .xadd(&stream, Some(StreamId::new(1, 0)), fields, None)
+                .await
+                .is_err());
+            for i in 0..4 {
+                let value = i.to_string();
+                let fields = MSetBuilder::new().set(b"i", &value);
+                redis.xadd(&stream, None, fields, None).await.unwrap();
+            }
+            assert_eq!(redis.xlen(&stream).await.unwrap(), 5);
+
+            let entries = redis.xrange(&stream, "-", "+", None).await.unwrap();
+            assert_eq!(entries.len(), 5);
+            assert_eq!(
+                entries[0],
+                StreamEntry {
+                    id: first,
+                    fields: vec![
+                        (b"a".to_vec(), b"1".to_vec()),
+                        (b"b".to_vec(), b"2".to_vec())
+                    ],
+                }
+            );
+            assert!(entries.windows(2).all(|w| w[0].id < w[1].id));
+            let last = entries[4].id;
+            assert_eq!(
+                redis.xrevrange(&stream, "+", "-", Some(2)).await.unwrap(),
+                vec![entries[4].clone(), entries[3].clone()]
+            );
+            assert_eq!(
+                redis
+                    .xrange(&stream, first, entries[1].id, None)
+                    .await
+                    .unwrap(),
+                entries[..2].to_vec()
+            );
+
+            assert_eq!(redis.xdel(&stream, &[entries[1].id]).await.unwrap(), 1);
+            assert_eq!(
+                redis.xtrim(&stream, StreamTrim::max_len(3)).await.unwrap(),
+                1
+            );
+            let fields = MSetBuilder::new().set(b"i", b"4");
+            let newest = redis
+                .xadd(&stream, None, fields, Some(StreamTrim::min_id(last)))
+                .await
+                .unwrap();
+            assert_eq!(redis.xlen(&stream).await.unwrap(), 2);
+
+            let read = redis
+                .xread(
+                    &[(&stream, last), (&other, StreamId::default())],
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+            assert_eq!(read.len(), 1);
+            assert_eq!(read[0].0, stream);
+            assert_eq!(read[0].1[0].id, newest);
+            assert!(redis
+                .xread(&[(&stream, newest)], Some(1), Some(10))
+                .await
+                .unwrap()
+                .is_empty());
+        },
+        stream,
+        other
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn xread_stream() {
+    redis_test!(
+        redis,
+        {
+            let fields = MSetBuilder::new().set(b"old", b"entry");
+            redis.xadd(&stream, None, fields, None).await.unwrap();
+
+            let tail = Connection::connect(TEST_ADDRESS).await.unwrap();
+            let mut entries = tail
+                .xread_stream(&[(&stream, None), (&other, None)], Some(1))
+                .await
+                .unwrap();
+
+            //Only entries added after the stream was created are returned
+            for (key, value) in &[(&other, b"1"), (&stream, b"2"), (&stream, b"3")] {
+                let fields = MSetBuilder::new().set(b"new", value);
+                redis.xadd(key, None, fields, None).await.unwrap();
+            }
+            let mut received = Vec::new();
+            for _ in 0..3 {
+                let (key, entry) = entries.next().await.unwrap().unwrap();
+                received.push((key, entry.get("new").unwrap().to_vec()));
+            }
+            received.sort();
+            assert_eq!(
+                received,
+                vec![
+                    (other.clone(), b"1".to_vec()),
+                    (stream.clone(), b"2".to_vec()),
+                    (stream.clone(), b"3".to_vec())
+                ]
+            );
+        },
+        stream,
+        other
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn xread_stream_errors() {
+    let address = fake_server(b":not-a-number\r\n");
+    let redis = Connection::connect(address).await.unwrap();
+    let mut entries = redis
+        .xread_stream(&[("stream", Some(StreamId::default()))], None)
+        .await
+        .unwrap();
+
+    match entries.next().await {
+        Some(Err(Error::Protocol(_, _))) => (),
+        other => panic!("expected protocol error, got {:?}", other),
+    }
+    assert!(entries.next().await.is_none());
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn consumer_groups() {
+    redis_test!(
+        redis,
+        {
+            assert!(redis
+                .xgroup_create(&stream, "group", None, false)
+                .await
+                .is_err());
+            redis
+                .xgroup_create(&stream, "group", None, true)
+                .await
+                .unwrap();
+            assert!(redis
+                .xgroup_create(&stream, "group", None, false)
+                .await
+                .is_err());
+            assert!(redis
+                .xgroup_createconsumer(&stream, "group", "alice")
+                .await
+                .unwrap());
+
+            let mut ids = Vec::new();
+            for i in 0..3 {
+                let value = i.to_string();
+                let fields = MSetBuilder::new().set(b"i", &value);
+                ids.push(redis.xadd(&stream, None, fields, None).await.unwrap());
+            }
+
+            let read = redis
+                .xreadgroup("group", "alice", &[(&stream, None)], Some(2), None, false)
+                .await
+                .unwrap();
+            assert_eq!(read.len(), 1);
+            assert_eq!(read[0].1.len(), 2);
+            let read = redis
+                .xreadgroup("group", "bob", &[(&stream, None)], None, Some(10), false)
+                .await
+                .unwrap();
+            assert_eq!(read[0].1[0].id, ids[2]);
+            assert!(redis
+                .xreadgroup("group", "bob", &[(&stream, None)], None, Some(10), false)
+                .await
+                .unwrap()
+                .is_empty());
+            //Reading with an ID returns the pending history of the consumer
+            let history = redis
+                .xreadgroup(
+                    "group",
+                    "alice",
+                    &[(&stream, Some(StreamId::default()))],
+                    None,
+                    None,
+                    false,
+                )
+                .await
+                .unwrap();
+            assert_eq!(history[0].1.len(), 2);
+
+            let summary = redis.xpending(&stream, "group").await.unwrap();
+            assert_eq!(
+                summary,
+                PendingSummary {
+                    count: 3,
+                    first_id: Some(ids[0]),
+                    last_id: Some(ids[2]),
+                    consumers: vec![(b"alice".to_vec(), 2), (b"bob".to_vec(), 1)],
+                }
+            );
+            let pending = redis
+                .xpending_range(
+                    &stream,
+                    "group",
+                    XPendingBuilder::new("-", "+", 10).consumer("alice"),
+                )
+                .await
+                .unwrap();
+            assert_eq!(pending.len(), 2);
+            assert_eq!(pending[0].id, ids[0]);
+            assert_eq!(pending[0].delivery_count, 1);
+            assert!(redis
+                .xpending_range(
+                    &stream,
+                    "group",
+                    XPendingBuilder::new("-", "+", 10).min_idle_ms(3_600_000),
+                )
+                .await
+                .unwrap()
+                .is_empty());
+
+            assert_eq!(redis.xack(&stream, "group", &[ids[0]]).await.unwrap(), 1);
+            let claimed = redis
+                .xclaim(&stream, "group", "bob", 0, &[ids[1]])
+                .await
+                .unwrap();
+            assert_eq!(claimed[0].id, ids[1]);
+            let claimed = redis
+                .xautoclaim(&stream, "group", "carol", 0, StreamId::default(), Some(10))
+                .await
+                .unwrap();
+            assert_eq!(claimed.next, StreamId::default());
+            assert_eq!(
+                claimed.entries.iter().map(|e| e.id).collect::<Vec<_>>(),
+                vec![ids[1], ids[2]]
+            );
+
+            let info = redis.xinfo_stream(&stream).await.unwrap();
+            assert_eq!(info.length, 3);
+            assert_eq!(info.groups, 1);
+            assert_eq!(info.last_generated_id, ids[2]);
+            assert_eq!(info.first_entry.unwrap().id, ids[0]);
+            let groups = redis.xinfo_groups(&stream).await.unwrap();
+            assert_eq!(groups[0].name, "group");
+            assert_eq!(groups[0].pending, 2);
+            assert_eq!(groups[0].last_delivered_id, ids[2]);
+            let mut consumers = redis.xinfo_consumers(&stream, "group").await.unwrap();
+            consumers.sort_by(|a, b| a.name.cmp(&b.name));
+            assert_eq!(
+                consumers.iter().map(|c| c.pending).collect::<Vec<_>>(),
+                vec![0, 0, 2]
+            );
+
+            assert_eq!(
+                redis
+                    .xgroup_delconsumer(&stream, "group", "carol")
+                    .await
+                    .unwrap(),
+                2
+            );
+            redis
+                .xgroup_setid(&stream, "group", Some(StreamId::default()))
+                .await
+                .unwrap();
+            assert!(redis.xgroup_destroy(&stream, "group").await.unwrap());
+            assert!(!redis.xgroup_destroy(&stream, "group").await.unwrap());
+        },
+        stream
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn group_consumer() {
+    redis_test!(
+        redis,
+        {
+            redis
+                .xgroup_create(&stream, "group", None, true)
+                .await
+                .unwrap();
+            let mut ids = Vec::new();
+            for i in 0..3 {
+                let value = i.to_string();
+                let fields = MSetBuilder::new().set(b"i", &value);
+                ids.push(redis.xadd(&stream, None, fields, None).await.unwrap());
+            }
+            //One entry is left pending on a crashed consumer, and one on the consumer itself
+            redis
+                .xreadgroup("group", "crashed", &[(&stream, None)], Some(1), None, false)
+                .await
+                .unwrap();
+            redis
+                .xreadgroup("group", "worker", &[(&stream, None)], Some(1), None, false)
+                .await
+                .unwrap();
+
+            let worker = Connection::connect(TEST_ADDRESS).await.unwrap();
+            let mut entries = worker
+                .group_consumer(&stream, "group", "worker")
+                .count(1)
+                .min_idle(Duration::from_millis(0))
+                .run();
+            let mut received = Vec::new();
+            for _ in 0..3 {
+                let entry = entries.next().await.unwrap().unwrap();
+                redis
+                    .xack(&stream, "group", &[entry.entry.id])
+                    .await
+                    .unwrap();
+                received.push((entry.entry.id, entry.delivery_count));
+            }
+            received.sort();
+            assert_eq!(received, vec![(ids[0], 2), (ids[1], 1), (ids[2], 1)]);
+
+            //Entries added later are received as well
+            let fields = MSetBuilder::new().set(b"i", b"3");
+            let id = redis.xadd(&stream, None, fields, None).await.unwrap();
+            let entry = entries.next().await.unwrap().unwrap();
+            assert_eq!((entry.entry.id, entry.delivery_count), (id, 1));
+
+            let new_entries = Connection::connect(TEST_ADDRESS)
+                .await
+                .unwrap()
+                .xreadgroup_stream("group", "other", &[&stream], None, true)
+                .await
+                .unwrap();
+            let fields = MSetBuilder::new().set(b"i", b"4");
+            let id = redis.xadd(&stream, None, fields, None).await.unwrap();
+            let (key, entry) = new_entries.take(1).next().await.unwrap().unwrap();
+            assert_eq!((key, entry.id), (stream.clone(), id));
+        },
+        stream
+    );
+}
+
+#[cfg_attr(feature = "runtime_tokio", tokio::test)]
+#[cfg_attr(feature = "runtime_async_std", async_std::test)]
+async fn group_consumer_errors() {
+    let address = fake_server(b":not-a-number\r\n");
+    let redis = Connection::connect(address).await.unwrap();
+    let mut entries = redis.group_consumer("stream", "group", "worker").run();
+
+    match entries.next().await {
+        Some(Err(Error::Protocol(_, _))) => (),
+        other => panic!("expected protocol error, got {:?}", other),
+    }
+    assert!(entries.next().await.is_none());
+}
@@ -0,0 +1,76 @@
+use crate::Command;
+
+///A list of commands to be run atomically using `MULTI` and `EXEC`. Run it using
+///[`Connection::run_transaction`](struct.Connection.html#method.run_transaction), or
+///[`Connection::watch_transaction`](struct.Connection.html#method.watch_transaction) to retry it
+///whenever a watched key is modified.
+///
+///The whole transaction is sent in a single pipeline, so nothing is sent to Redis before it is run,
+///and dropping a `Transaction` does not require sending `DISCARD`. If any command fails to be queued,
+///for instance because of a syntax error, Redis discards the whole transaction.
+///# Example
+///```
+///use darkredis::{Command, Connection, Transaction};
+///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+///# async fn main() {
+///# let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+///# connection.del("transaction-counter").await.unwrap();
+///let transaction = Transaction::new()
+///    .command(Command::new("INCR").arg(b"transaction-counter"))
+///    .command(Command::new("INCRBY").arg(b"transaction-counter").arg(10));
+///
+///let result: Option<(isize, isize)> = connection.run_transaction(transaction).await.unwrap();
+///assert_eq!(result, Some((1, 11)));
+///# connection.del("transaction-counter").await.unwrap();
+///# }
+///```
+#[derive(Clone, Debug, Default)]
+pub struct Transaction<'a> {
+    commands: Vec<Command<'a>>,
+}
+
+impl<'a> Transaction<'a> {
+    ///Create an empty transaction.
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+
+    ///Add `command` to the transaction, builder-style.
+    ///# See also
+    ///[`append_command`](struct.Transaction.html#method.append_command)
+    pub fn command(mut self, command: Command<'a>) -> Self {
+        self.append_command(command);
+        self
+    }
+
+    ///Add `command` to the transaction.
+    ///# See also
+    ///[`command`](struct.Transaction.html#method.command)
+    pub fn append_command(&mut self, command: Command<'a>) {
+        self.commands.push(command);
+    }
+
+    ///Count the number of commands in the transaction, not including `MULTI` and `EXEC`.
+    pub fn command_count(&self) -> usize {
+        self.commands.len()
+    }
+
+    ///Convert `self` into a transaction which owns all of its arguments, copying any borrowed data.
+    pub fn into_owned(self) -> Transaction<'static> {
+        Transaction {
+            commands: self.commands.into_iter().map(Command::into_owned).collect(),
+        }
+    }
+
+    //Serialize the commands wrapped in MULTI and EXEC.
+    pub(crate) fn serialize(self, buffer: &mut Vec<u8>) {
+        Command::new("MULTI").serialize(buffer);
+        for command in self.commands {
+            command.serialize(buffer);
+        }
+        Command::new("EXEC").serialize(buffer);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Command, Connection, Result};
+use crate::{Command, Connection, ProtocolVersion, Result};
 use futures::lock::{Mutex, MutexGuard};
 use std::sync::Arc;
 
@@ -9,6 +9,7 @@ pub struct ConnectionPool {
     address: Arc<String>,
     password: Option<Arc<String>>,
     name: Arc<String>,
+    protocol: ProtocolVersion,
 }
 
 impl ConnectionPool {
@@ -34,6 +35,27 @@ impl ConnectionPool {
         address: String,
         password: Option<&str>,
         connection_count: usize,
+    ) -> Result<Self> {
+        Self::create_with_protocol(
+            name,
+            address,
+            password,
+            connection_count,
+            ProtocolVersion::Resp2,
+        )
+        .await
+    }
+
+    ///Create a connection pool where every connection uses `protocol`, naming each connection by `name`.
+    ///Connections created using [`spawn`](struct.ConnectionPool.html#method.spawn) will use `protocol` as well.
+    ///# Panics
+    ///Will panic if the number of connections is equal to zero.
+    pub async fn create_with_protocol(
+        name: &str,
+        address: String,
+        password: Option<&str>,
+        connection_count: usize,
+        protocol: ProtocolVersion,
     ) -> Result<Self> {
         assert!(connection_count > 0);
         let connections = Vec::new();
@@ -42,14 +64,11 @@ impl ConnectionPool {
             name: Arc::new(name.to_string()),
             password: password.map(|s| Arc::new(s.to_string())),
             address: Arc::new(address),
+            protocol,
         };
 
         for i in 0..connection_count {
-            let mut conn = if let Some(p) = password {
-                Connection::connect_and_auth(out.address.as_ref(), p).await?
-            } else {
-                Connection::connect(out.address.as_ref()).await?
-            };
+            let mut conn = out.connect().await?;
             let client_name = format!("{}-{}", name, i + 1);
             conn.run_command(Command::new("CLIENT").arg(b"SETNAME").arg(&client_name))
                 .await?;
@@ -59,6 +78,20 @@ impl ConnectionPool {
         Ok(out)
     }
 
+    //Create a new connection using the settings of the pool.
+    async fn connect(&self) -> Result<Connection> {
+        let mut out = if let Some(p) = &self.password {
+            Connection::connect_and_auth(self.address.as_ref(), p.as_bytes()).await?
+        } else {
+            Connection::connect(self.address.as_ref()).await?
+        };
+        if self.protocol != ProtocolVersion::Resp2 {
+            out.hello(self.protocol).await?;
+        }
+
+        Ok(out)
+    }
+
     ///Get an available connection from the pool, or wait for one to become available if none are
     ///available.
     pub async fn get(&self) -> MutexGuard<'_, Connection> {
@@ -78,14 +111,10 @@ impl ConnectionPool {
     where
         N: Into<Option<&'a str>>,
     {
-        let mut out = if let Some(p) = &self.password {
-            Connection::connect_and_auth(self.address.as_ref(), p.as_bytes()).await?
-        } else {
-            Connection::connect(self.address.as_ref()).await?
-        };
+        let mut out = self.connect().await?;
         let name = name.into().unwrap_or("spawned_connection");
         let name = format!("{}-{}", self.name, name);
-        let command = Command::new("CLIENT").arg(&"SETNAME").arg(&name);
+        let command = Command::new("CLIENT").arg("SETNAME").arg(&name);
         out.run_command(command).await?;
         Ok(out)
     }
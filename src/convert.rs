@@ -0,0 +1,455 @@
+use crate::{Error, Result, Value};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::hash::Hash;
+use std::io::Write;
+
+///Conversion from a [`Value`](enum.Value.html) received from Redis into a Rust type. Used by
+///[`Connection::query`](struct.Connection.html#method.query) to convert replies, returning an
+///[`Error::UnexpectedResponse`](enum.Error.html#variant.UnexpectedResponse) instead of panicking
+///if the reply is of the wrong type.
+///# Example
+///```
+///use darkredis::{FromValue, Value};
+///
+///let value = Value::Array(vec![Value::Integer(1), Value::String(b"2".to_vec())]);
+///assert_eq!(Vec::<u32>::from_value(value).unwrap(), vec![1, 2]);
+///assert!(String::from_value(Value::Integer(1)).is_err());
+///```
+pub trait FromValue: Sized {
+    ///Convert `value` into `Self`.
+    fn from_value(value: Value) -> Result<Self>;
+
+    ///Convert the elements of an array into a list of `Self`. By default every element is
+    ///converted separately, which tuples override to accept flat arrays as well.
+    fn from_values(values: Vec<Value>) -> Result<Vec<Self>> {
+        values.into_iter().map(Self::from_value).collect()
+    }
+}
+
+//Create the error returned when `value` can't be converted into `expected`. Error replies are
+//returned as the error they contain instead.
+fn unexpected(value: Value, expected: &str) -> Error {
+    match value {
+        Value::Error(e) => Error::RedisError(e),
+        other => Error::UnexpectedResponse(format!("expected {}, got {:?}", expected, other)),
+    }
+}
+
+//Get the elements of any kind of list, where nil is an empty list. Maps are turned into a list of
+//key-value pairs.
+fn into_values(value: Value, expected: &str) -> Result<Vec<Value>> {
+    match value {
+        Value::Array(a) | Value::Set(a) | Value::Push(a) => Ok(a),
+        Value::Map(m) => Ok(m
+            .into_iter()
+            .map(|(k, v)| Value::Array(vec![k, v]))
+            .collect()),
+        Value::Nil => Ok(Vec::new()),
+        other => Err(unexpected(other, expected)),
+    }
+}
+
+//Take the value of `field` out of a reply consisting of field-value pairs, such as the ones
+//returned by FUNCTION LIST and XINFO.
+pub(crate) fn take_field<T: FromValue>(
+    fields: &mut HashMap<String, Value>,
+    field: &str,
+) -> Result<T> {
+    let value = fields
+        .remove(field)
+        .ok_or_else(|| Error::UnexpectedResponse(format!("missing field {} in reply", field)))?;
+    T::from_value(value)
+}
+
+//Like `take_field`, but for fields which are only returned by some versions of Redis, or are nil.
+pub(crate) fn take_optional_field<T: FromValue>(
+    fields: &mut HashMap<String, Value>,
+    field: &str,
+) -> Result<Option<T>> {
+    match fields.remove(field) {
+        Some(value) => Option::<T>::from_value(value),
+        None => Ok(None),
+    }
+}
+
+//Parse a string reply containing a number, as Redis returns for things like INCRBYFLOAT.
+fn parse_string<T: std::str::FromStr>(value: Value, expected: &str) -> Result<T> {
+    let parsed = match &value {
+        Value::String(s) => std::str::from_utf8(s).ok().and_then(|s| s.parse().ok()),
+        Value::Status(s) | Value::BigNumber(s) => s.parse().ok(),
+        _ => None,
+    };
+    parsed.ok_or_else(|| unexpected(value, expected))
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value) -> Result<Self> {
+        Ok(value)
+    }
+}
+
+impl FromValue for () {
+    fn from_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Error(e) => Err(Error::RedisError(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+macro_rules! impl_from_value_int {
+    ($($t:ty),*) => {
+        $(
+            impl FromValue for $t {
+                fn from_value(value: Value) -> Result<Self> {
+                    match value {
+                        Value::Integer(i) => {
+                            <$t>::try_from(i).map_err(|_| unexpected(value, stringify!($t)))
+                        }
+                        other => parse_string(other, stringify!($t)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+//u8 is left out so that Vec<u8> can be used for strings.
+impl_from_value_int!(i8, i16, i32, i64, i128, isize, u16, u32, u64, u128, usize);
+
+macro_rules! impl_from_value_float {
+    ($($t:ty),*) => {
+        $(
+            impl FromValue for $t {
+                fn from_value(value: Value) -> Result<Self> {
+                    match value {
+                        Value::Double(d) => Ok(d as $t),
+                        Value::Integer(i) => Ok(i as $t),
+                        other => parse_string(other, stringify!($t)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_value_float!(f32, f64);
+
+impl FromValue for bool {
+    fn from_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Integer(i) => Ok(i != 0),
+            Value::Boolean(b) => Ok(b),
+            Value::Ok => Ok(true),
+            Value::String(ref s) if s == b"1" => Ok(true),
+            Value::String(ref s) if s == b"0" => Ok(false),
+            other => Err(unexpected(other, "bool")),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> Result<Self> {
+        match value {
+            Value::String(s) | Value::Verbatim { text: s, .. } => String::from_utf8(s)
+                .map_err(|e| unexpected(Value::String(e.into_bytes()), "String")),
+            Value::Status(s) | Value::BigNumber(s) => Ok(s),
+            Value::Ok => Ok("OK".to_string()),
+            other => Err(unexpected(other, "String")),
+        }
+    }
+}
+
+impl FromValue for Vec<u8> {
+    fn from_value(value: Value) -> Result<Self> {
+        match value {
+            Value::String(s) | Value::Verbatim { text: s, .. } => Ok(s),
+            Value::Status(s) | Value::BigNumber(s) => Ok(s.into_bytes()),
+            Value::Ok => Ok(b"OK".to_vec()),
+            other => Err(unexpected(other, "Vec<u8>")),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Nil => Ok(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: Value) -> Result<Self> {
+        T::from_values(into_values(value, "array")?)
+    }
+}
+
+impl<T: FromValue + Eq + Hash> FromValue for HashSet<T> {
+    fn from_value(value: Value) -> Result<Self> {
+        Ok(Vec::<T>::from_value(value)?.into_iter().collect())
+    }
+}
+
+impl<K: FromValue + Eq + Hash, V: FromValue> FromValue for HashMap<K, V> {
+    fn from_value(value: Value) -> Result<Self> {
+        Ok(Vec::<(K, V)>::from_value(value)?.into_iter().collect())
+    }
+}
+
+impl<K: FromValue + Ord, V: FromValue> FromValue for BTreeMap<K, V> {
+    fn from_value(value: Value) -> Result<Self> {
+        Ok(Vec::<(K, V)>::from_value(value)?.into_iter().collect())
+    }
+}
+
+macro_rules! impl_from_value_tuple {
+    ($len:expr; $($name:ident),*) => {
+        impl<$($name: FromValue),*> FromValue for ($($name,)*) {
+            fn from_value(value: Value) -> Result<Self> {
+                match value {
+                    Value::Array(a) if a.len() == $len => {
+                        let mut iter = a.into_iter();
+                        Ok(($($name::from_value(iter.next().unwrap())?,)*))
+                    }
+                    other => Err(unexpected(other, concat!("array of length ", $len))),
+                }
+            }
+
+            //Accept both nested arrays, such as `[[a, 1], [b, 2]]`, and flat arrays like `[a, 1, b, 2]`.
+            #[allow(clippy::modulo_one)]
+            fn from_values(values: Vec<Value>) -> Result<Vec<Self>> {
+                let nested = values
+                    .iter()
+                    .all(|v| matches!(v, Value::Array(a) if a.len() == $len));
+                if nested {
+                    return values.into_iter().map(Self::from_value).collect();
+                }
+
+                if values.len() % $len != 0 {
+                    return Err(unexpected(
+                        Value::Array(values),
+                        concat!("array with a multiple of ", $len, " elements"),
+                    ));
+                }
+                let mut out = Vec::with_capacity(values.len() / $len);
+                let mut iter = values.into_iter();
+                while iter.len() > 0 {
+                    out.push(($($name::from_value(iter.next().unwrap())?,)*));
+                }
+
+                Ok(out)
+            }
+        }
+    };
+}
+
+impl_from_value_tuple!(1; A);
+impl_from_value_tuple!(2; A, B);
+impl_from_value_tuple!(3; A, B, C);
+impl_from_value_tuple!(4; A, B, C, D);
+
+///Conversion of a Rust value into a command argument. Implemented for references to anything
+///which implements `AsRef<[u8]>` without copying them, as well as for numbers, booleans and owned
+///strings. Numbers are sent in their decimal representation and booleans as `1` or `0`.
+///# Example
+///```
+///use darkredis::Command;
+///
+///let key = "some-key".to_string();
+///let command = Command::new("SET").arg(&key).arg(10).arg("EX").arg(60);
+///```
+pub trait ToArg<'a> {
+    ///Convert `self` into the bytes sent to Redis.
+    fn to_arg(self) -> Cow<'a, [u8]>;
+}
+
+impl<'a, D> ToArg<'a> for &'a D
+where
+    D: AsRef<[u8]> + ?Sized,
+{
+    #[inline]
+    fn to_arg(self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.as_ref())
+    }
+}
+
+impl<'a> ToArg<'a> for Cow<'a, [u8]> {
+    #[inline]
+    fn to_arg(self) -> Cow<'a, [u8]> {
+        self
+    }
+}
+
+impl<'a> ToArg<'a> for Vec<u8> {
+    #[inline]
+    fn to_arg(self) -> Cow<'a, [u8]> {
+        Cow::Owned(self)
+    }
+}
+
+impl<'a> ToArg<'a> for String {
+    #[inline]
+    fn to_arg(self) -> Cow<'a, [u8]> {
+        Cow::Owned(self.into_bytes())
+    }
+}
+
+impl<'a> ToArg<'a> for bool {
+    #[inline]
+    fn to_arg(self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(if self { b"1" } else { b"0" })
+    }
+}
+
+//Numbers are formatted on the stack, so that only the argument itself is allocated. `$size` has to
+//fit the longest decimal representation of the types.
+macro_rules! impl_to_arg_number {
+    ($size:expr => $($t:ty),*) => {
+        $(
+            impl<'a> ToArg<'a> for $t {
+                #[inline]
+                fn to_arg(self) -> Cow<'a, [u8]> {
+                    let mut buffer = [0; $size];
+                    let mut remaining = &mut buffer[..];
+                    write!(remaining, "{}", self).expect("number fits in buffer");
+                    let len = $size - remaining.len();
+                    Cow::Owned(buffer[..len].to_vec())
+                }
+            }
+        )*
+    };
+}
+
+impl_to_arg_number!(40 => i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_to_arg_number!(330 => f32, f64);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::string;
+
+    #[test]
+    fn to_arg() {
+        let owned = "owned".to_string();
+        assert_eq!(owned.to_arg(), Cow::Borrowed(b"owned"));
+        assert!(matches!(b"borrowed".to_arg(), Cow::Borrowed(b"borrowed")));
+        assert_eq!((-42).to_arg(), Cow::Borrowed(b"-42"));
+        assert_eq!(1.5.to_arg(), Cow::Borrowed(b"1.5"));
+        assert_eq!(f64::NEG_INFINITY.to_arg(), Cow::Borrowed(b"-inf"));
+        for number in &[-f64::MAX, -f64::from_bits(1), f64::from(f32::MIN)] {
+            assert_eq!(
+                number.to_arg().into_owned(),
+                number.to_string().into_bytes()
+            );
+        }
+        assert_eq!(
+            i128::MIN.to_arg().into_owned(),
+            i128::MIN.to_string().into_bytes()
+        );
+        assert_eq!(
+            u128::MAX.to_arg().into_owned(),
+            u128::MAX.to_string().into_bytes()
+        );
+        assert_eq!(true.to_arg(), Cow::Borrowed(b"1"));
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(isize::from_value(Value::Integer(-5)).unwrap(), -5);
+        assert_eq!(u64::from_value(string("42")).unwrap(), 42);
+        assert_eq!(f64::from_value(string("1.5")).unwrap(), 1.5);
+        assert_eq!(f64::from_value(Value::Double(2.5)).unwrap(), 2.5);
+        assert_eq!(f32::from_value(Value::Integer(3)).unwrap(), 3.0);
+        assert!(u32::from_value(Value::Integer(-1)).is_err());
+        assert!(i32::from_value(string("abc")).is_err());
+        assert!(isize::from_value(Value::Nil).is_err());
+    }
+
+    #[test]
+    fn strings_and_bools() {
+        assert_eq!(String::from_value(string("foo")).unwrap(), "foo");
+        assert_eq!(
+            String::from_value(Value::Status("PONG".to_string())).unwrap(),
+            "PONG"
+        );
+        assert!(String::from_value(Value::String(vec![0xff])).is_err());
+        assert_eq!(Vec::<u8>::from_value(string("foo")).unwrap(), b"foo");
+        assert!(bool::from_value(Value::Integer(1)).unwrap());
+        assert!(!bool::from_value(Value::Boolean(false)).unwrap());
+        assert_eq!(Option::<String>::from_value(Value::Nil).unwrap(), None);
+    }
+
+    #[test]
+    fn errors() {
+        match isize::from_value(Value::Error("ERR oops".to_string())) {
+            Err(Error::RedisError(e)) => assert_eq!(e, "ERR oops"),
+            other => panic!("expected redis error, got {:?}", other),
+        }
+        assert!(matches!(
+            <()>::from_value(Value::Error("ERR oops".to_string())),
+            Err(Error::RedisError(_))
+        ));
+        assert!(matches!(
+            String::from_value(Value::Integer(1)),
+            Err(Error::UnexpectedResponse(_))
+        ));
+    }
+
+    #[test]
+    fn collections() {
+        let array = Value::Array(vec![string("a"), string("b"), string("a")]);
+        assert_eq!(
+            Vec::<String>::from_value(array.clone()).unwrap(),
+            vec!["a", "b", "a"]
+        );
+        assert_eq!(HashSet::<String>::from_value(array).unwrap().len(), 2);
+        assert_eq!(
+            Vec::<Option<Vec<u8>>>::from_value(Value::Array(vec![string("a"), Value::Nil]))
+                .unwrap(),
+            vec![Some(b"a".to_vec()), None]
+        );
+        assert!(Vec::<String>::from_value(Value::Nil).unwrap().is_empty());
+        assert!(Vec::<String>::from_value(Value::Integer(1)).is_err());
+    }
+
+    #[test]
+    fn tuples_and_maps() {
+        let flat = Value::Array(vec![string("a"), string("1"), string("b"), string("2")]);
+        let nested = Value::Array(vec![
+            Value::Array(vec![string("a"), Value::Integer(1)]),
+            Value::Array(vec![string("b"), Value::Integer(2)]),
+        ]);
+        let map = Value::Map(vec![
+            (string("a"), Value::Integer(1)),
+            (string("b"), Value::Integer(2)),
+        ]);
+        let expected = vec![("a".to_string(), 1), ("b".to_string(), 2)];
+
+        for value in [flat, nested, map] {
+            assert_eq!(
+                Vec::<(String, isize)>::from_value(value.clone()).unwrap(),
+                expected
+            );
+            assert_eq!(
+                HashMap::<String, isize>::from_value(value.clone()).unwrap(),
+                expected.iter().cloned().collect()
+            );
+            assert_eq!(
+                BTreeMap::<String, isize>::from_value(value).unwrap(),
+                expected.iter().cloned().collect()
+            );
+        }
+
+        assert_eq!(
+            <(isize, String)>::from_value(Value::Array(vec![Value::Integer(1), string("a")]))
+                .unwrap(),
+            (1, "a".to_string())
+        );
+        assert!(<(isize, isize)>::from_value(Value::Array(vec![Value::Integer(1)])).is_err());
+        assert!(Vec::<(String, String)>::from_value(Value::Array(vec![string("a")])).is_err());
+    }
+}
@@ -16,6 +16,12 @@ quick_error! {
         RedisError(err: String) {
             display("Redis replied with error: {}", err)
         }
+        ///Received data which is not valid according to the Redis protocol. Contains the offending
+        ///data and its position in the reply, in that order. The connection should not be used after
+        ///receiving this error.
+        Protocol(data: Vec<u8>, position: usize) {
+            display("Invalid Redis protocol data at position {}: \"{}\"", position, String::from_utf8_lossy(data))
+        }
         ///A command taking a slice was given an empty slice.
         EmptySlice {
             display("A command expecting a slice of values received an empty slice")
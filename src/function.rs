@@ -0,0 +1,127 @@
+use crate::{
+    convert::{take_field, take_optional_field},
+    FromValue, Result, Value,
+};
+use std::collections::HashMap;
+
+///Information about a function library, as returned by
+///[`Connection::function_list`](struct.Connection.html#method.function_list).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryInfo {
+    ///The name of the library.
+    pub name: String,
+    ///The engine used by the library, such as `LUA`.
+    pub engine: String,
+    ///The functions registered by the library.
+    pub functions: Vec<FunctionInfo>,
+    ///The source code of the library, only present if requested.
+    pub code: Option<String>,
+}
+
+///Information about a single function in a library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionInfo {
+    ///The name of the function.
+    pub name: String,
+    ///The description of the function, if it has one.
+    pub description: Option<String>,
+    ///The flags of the function, such as `no-writes`.
+    pub flags: Vec<String>,
+}
+
+///How [`Connection::function_restore`](struct.Connection.html#method.function_restore) handles
+///libraries which already exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionRestorePolicy {
+    ///Append the restored libraries, failing if any of them already exist. This is the default in Redis.
+    Append,
+    ///Append the restored libraries, replacing any which already exist.
+    Replace,
+    ///Delete every existing library before restoring.
+    Flush,
+}
+
+impl FunctionRestorePolicy {
+    pub(crate) fn as_arg(self) -> &'static str {
+        match self {
+            FunctionRestorePolicy::Append => "APPEND",
+            FunctionRestorePolicy::Replace => "REPLACE",
+            FunctionRestorePolicy::Flush => "FLUSH",
+        }
+    }
+}
+
+impl FromValue for LibraryInfo {
+    fn from_value(value: Value) -> Result<Self> {
+        let mut fields = HashMap::<String, Value>::from_value(value)?;
+        Ok(Self {
+            name: take_field(&mut fields, "library_name")?,
+            engine: take_field(&mut fields, "engine")?,
+            functions: take_field(&mut fields, "functions")?,
+            code: take_optional_field(&mut fields, "library_code")?,
+        })
+    }
+}
+
+impl FromValue for FunctionInfo {
+    fn from_value(value: Value) -> Result<Self> {
+        let mut fields = HashMap::<String, Value>::from_value(value)?;
+        Ok(Self {
+            name: take_field(&mut fields, "name")?,
+            description: take_field(&mut fields, "description")?,
+            flags: take_field(&mut fields, "flags")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::string;
+
+    #[test]
+    fn library_info() {
+        let function = Value::Array(vec![
+            string("name"),
+            string("myfunc"),
+            string("description"),
+            Value::Nil,
+            string("flags"),
+            Value::Array(vec![string("no-writes")]),
+        ]);
+        let library = Value::Array(vec![
+            string("library_name"),
+            string("mylib"),
+            string("engine"),
+            string("LUA"),
+            string("functions"),
+            Value::Array(vec![function]),
+        ]);
+
+        assert_eq!(
+            LibraryInfo::from_value(library).unwrap(),
+            LibraryInfo {
+                name: "mylib".to_string(),
+                engine: "LUA".to_string(),
+                functions: vec![FunctionInfo {
+                    name: "myfunc".to_string(),
+                    description: None,
+                    flags: vec!["no-writes".to_string()],
+                }],
+                code: None,
+            }
+        );
+
+        let library = Value::Map(vec![
+            (string("library_name"), string("mylib")),
+            (string("engine"), string("LUA")),
+            (string("functions"), Value::Array(vec![])),
+            (string("library_code"), string("#!lua name=mylib")),
+        ]);
+        let info = LibraryInfo::from_value(library).unwrap();
+        assert_eq!(info.code, Some("#!lua name=mylib".to_string()));
+        assert!(info.functions.is_empty());
+
+        assert!(LibraryInfo::from_value(Value::Array(vec![])).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+use crate::{Command, Error, FromValue, Result, Value};
+
+///A position on the earth, as used by the geospatial commands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPosition {
+    ///The longitude, between -180 and 180 degrees.
+    pub longitude: f64,
+    ///The latitude, between -85.05112878 and 85.05112878 degrees.
+    pub latitude: f64,
+}
+
+impl GeoPosition {
+    ///Create a new position from a longitude and a latitude.
+    pub fn new(longitude: f64, latitude: f64) -> Self {
+        Self {
+            longitude,
+            latitude,
+        }
+    }
+}
+
+impl FromValue for GeoPosition {
+    fn from_value(value: Value) -> Result<Self> {
+        let (longitude, latitude) = FromValue::from_value(value)?;
+        Ok(Self {
+            longitude,
+            latitude,
+        })
+    }
+}
+
+///The unit of a distance used by the geospatial commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeoUnit {
+    ///Meters.
+    Meters,
+    ///Kilometers.
+    Kilometers,
+    ///Miles.
+    Miles,
+    ///Feet.
+    Feet,
+}
+
+impl GeoUnit {
+    pub(crate) fn as_arg(self) -> &'static str {
+        match self {
+            GeoUnit::Meters => "m",
+            GeoUnit::Kilometers => "km",
+            GeoUnit::Miles => "mi",
+            GeoUnit::Feet => "ft",
+        }
+    }
+}
+
+///The area searched by a [`GeoSearchBuilder`](struct.GeoSearchBuilder.html), centered on its origin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoShape {
+    ///A circle with the given radius, using `BYRADIUS`.
+    Radius {
+        ///The radius of the circle.
+        radius: f64,
+        ///The unit of `radius`.
+        unit: GeoUnit,
+    },
+    ///An axis-aligned rectangle, using `BYBOX`.
+    Box {
+        ///The width of the rectangle.
+        width: f64,
+        ///The height of the rectangle.
+        height: f64,
+        ///The unit of `width` and `height`.
+        unit: GeoUnit,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum GeoOrigin<'a> {
+    Member(&'a [u8]),
+    Position(GeoPosition),
+}
+
+///A builder for [`Connection::geosearch`](struct.Connection.html#method.geosearch) and
+///[`Connection::geosearchstore`](struct.Connection.html#method.geosearchstore).
+///# Example
+///```
+///use darkredis::{GeoPosition, GeoSearchBuilder, GeoShape, GeoUnit};
+///
+/////The 10 closest members within 200 km of the given position, with their distance
+///let shape = GeoShape::Radius { radius: 200.0, unit: GeoUnit::Kilometers };
+///let search = GeoSearchBuilder::from_position(GeoPosition::new(15.0, 37.0), shape)
+///    .asc()
+///    .count(10)
+///    .with_dist();
+///```
+#[derive(Debug, Clone)]
+pub struct GeoSearchBuilder<'a> {
+    origin: GeoOrigin<'a>,
+    shape: GeoShape,
+    order: Option<&'static str>,
+    count: Option<(usize, bool)>,
+    with_coord: bool,
+    with_dist: bool,
+    with_hash: bool,
+}
+
+impl<'a> GeoSearchBuilder<'a> {
+    fn new(origin: GeoOrigin<'a>, shape: GeoShape) -> Self {
+        Self {
+            origin,
+            shape,
+            order: None,
+            count: None,
+            with_coord: false,
+            with_dist: false,
+            with_hash: false,
+        }
+    }
+
+    ///Search the area around the position of `member`, using `FROMMEMBER`.
+    pub fn from_member<M>(member: &'a M, shape: GeoShape) -> Self
+    where
+        M: AsRef<[u8]> + ?Sized,
+    {
+        Self::new(GeoOrigin::Member(member.as_ref()), shape)
+    }
+
+    ///Search the area around `position`, using `FROMLONLAT`.
+    pub fn from_position(position: GeoPosition, shape: GeoShape) -> Self {
+        Self::new(GeoOrigin::Position(position), shape)
+    }
+
+    ///Sort the results from the nearest to the farthest.
+    pub fn asc(mut self) -> Self {
+        self.order = Some("ASC");
+        self
+    }
+
+    ///Sort the results from the farthest to the nearest.
+    pub fn desc(mut self) -> Self {
+        self.order = Some("DESC");
+        self
+    }
+
+    ///Return at most `count` results.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some((count, false));
+        self
+    }
+
+    ///Like [`count`](struct.GeoSearchBuilder.html#method.count), but return as soon as enough
+    ///matches are found, using `COUNT ANY`. The results may not be the closest ones.
+    pub fn count_any(mut self, count: usize) -> Self {
+        self.count = Some((count, true));
+        self
+    }
+
+    ///Include the position of each result. Ignored by `geosearchstore`.
+    pub fn with_coord(mut self) -> Self {
+        self.with_coord = true;
+        self
+    }
+
+    ///Include the distance of each result from the origin, in the unit of the shape. Ignored
+    ///by `geosearchstore`.
+    pub fn with_dist(mut self) -> Self {
+        self.with_dist = true;
+        self
+    }
+
+    ///Include the raw geohash of each result. Ignored by `geosearchstore`.
+    pub fn with_hash(mut self) -> Self {
+        self.with_hash = true;
+        self
+    }
+
+    pub(crate) fn build(&self, command: &mut Command<'a>, with_fields: bool) {
+        match self.origin {
+            GeoOrigin::Member(member) => {
+                command.append_arg("FROMMEMBER");
+                command.append_arg(member);
+            }
+            GeoOrigin::Position(position) => {
+                command.append_arg("FROMLONLAT");
+                command.append_arg(position.longitude);
+                command.append_arg(position.latitude);
+            }
+        }
+        match self.shape {
+            GeoShape::Radius { radius, unit } => {
+                command.append_arg("BYRADIUS");
+                command.append_arg(radius);
+                command.append_arg(unit.as_arg());
+            }
+            GeoShape::Box {
+                width,
+                height,
+                unit,
+            } => {
+                command.append_arg("BYBOX");
+                command.append_arg(width);
+                command.append_arg(height);
+                command.append_arg(unit.as_arg());
+            }
+        }
+        if let Some(order) = self.order {
+            command.append_arg(order);
+        }
+        if let Some((count, any)) = self.count {
+            command.append_arg("COUNT");
+            command.append_arg(count);
+            if any {
+                command.append_arg("ANY");
+            }
+        }
+        if with_fields {
+            if self.with_coord {
+                command.append_arg("WITHCOORD");
+            }
+            if self.with_dist {
+                command.append_arg("WITHDIST");
+            }
+            if self.with_hash {
+                command.append_arg("WITHHASH");
+            }
+        }
+    }
+
+    //The reply only contains the member names unless extra fields are requested, in which case
+    //each result is an array of the member followed by the distance, hash and coordinates.
+    pub(crate) fn parse(&self, value: Value) -> Result<Vec<GeoSearchResult>> {
+        if !(self.with_coord || self.with_dist || self.with_hash) {
+            return Ok(Vec::<Vec<u8>>::from_value(value)?
+                .into_iter()
+                .map(GeoSearchResult::new)
+                .collect());
+        }
+
+        Vec::<Vec<Value>>::from_value(value)?
+            .into_iter()
+            .map(|fields| {
+                let mut fields = fields.into_iter();
+                let mut next = || {
+                    fields.next().ok_or_else(|| {
+                        Error::UnexpectedResponse("too few fields in GEOSEARCH result".into())
+                    })
+                };
+                let mut result = GeoSearchResult::new(FromValue::from_value(next()?)?);
+                if self.with_dist {
+                    result.distance = Some(FromValue::from_value(next()?)?);
+                }
+                if self.with_hash {
+                    result.hash = Some(FromValue::from_value(next()?)?);
+                }
+                if self.with_coord {
+                    result.position = Some(FromValue::from_value(next()?)?);
+                }
+                Ok(result)
+            })
+            .collect()
+    }
+}
+
+///A member found by [`Connection::geosearch`](struct.Connection.html#method.geosearch). The
+///optional fields are only present if requested using the matching
+///[`GeoSearchBuilder`](struct.GeoSearchBuilder.html) option.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoSearchResult {
+    ///The name of the member.
+    pub member: Vec<u8>,
+    ///The distance from the origin of the search, in the unit of the search shape.
+    pub distance: Option<f64>,
+    ///The raw 52-bit geohash of the member, which is also its score in the sorted set.
+    pub hash: Option<u64>,
+    ///The position of the member.
+    pub position: Option<GeoPosition>,
+}
+
+impl GeoSearchResult {
+    fn new(member: Vec<u8>) -> Self {
+        Self {
+            member,
+            distance: None,
+            hash: None,
+            position: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::string;
+
+    #[test]
+    fn search_results() {
+        let shape = GeoShape::Radius {
+            radius: 100.0,
+            unit: GeoUnit::Kilometers,
+        };
+        let search = GeoSearchBuilder::from_member("Palermo", shape);
+        let value = Value::Array(vec![string("Palermo"), string("Catania")]);
+        let results = search.parse(value).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1], GeoSearchResult::new(b"Catania".to_vec()));
+
+        let search = search.with_coord().with_hash().with_dist();
+        let value = Value::Array(vec![Value::Array(vec![
+            string("Palermo"),
+            string("190.4424"),
+            Value::Integer(3479099956230698),
+            Value::Array(vec![string("13.361389"), string("38.115556")]),
+        ])]);
+        assert_eq!(
+            search.parse(value).unwrap(),
+            vec![GeoSearchResult {
+                member: b"Palermo".to_vec(),
+                distance: Some(190.4424),
+                hash: Some(3479099956230698),
+                position: Some(GeoPosition::new(13.361389, 38.115556)),
+            }]
+        );
+
+        let value = Value::Array(vec![Value::Array(vec![string("Palermo")])]);
+        assert!(search.parse(value).is_err());
+    }
+}
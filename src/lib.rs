@@ -33,7 +33,12 @@ extern crate quick_error;
 mod command;
 mod connection;
 mod connectionpool;
+mod convert;
 mod error;
+mod function;
+mod geo;
+mod script;
+mod streams;
 
 ///Export the ToSocketAddrs trait to be used for deadpool-darkredis. You probably won't need this unless you're implementing an adapter crate for a different connection pool.
 #[cfg(feature = "runtime_async_std")]
@@ -44,22 +49,42 @@ pub use tokio::net::ToSocketAddrs;
 #[cfg(feature = "bench")]
 pub mod test;
 
+#[cfg(feature = "bench")]
+pub use connection::parse_bench;
+
 #[cfg(all(not(feature = "bench"), test))]
 mod test;
 
-pub use command::{Command, CommandList};
+pub use command::{
+    Command, CommandList, OwnedCommand, OwnedCommandList, SerializedCommand, SerializedCommandList,
+};
 pub use connection::{
-    builder::MSetBuilder, Connection, HScanBuilder, HScanStream, Message, MessageStream, PMessage,
-    PMessageStream, ResponseStream, ScanBuilder, ScanStream,
+    builder::{
+        Aggregate, BitFieldBuilder, BitFieldOverflow, BitFieldType, BitOp, BitRange,
+        ExpireCondition, Expiry, GetExOption, InsertPosition, ListSide, MSetBuilder,
+        MigrateOptions, RestoreOptions, SetOptions, SortBuilder, ZAddBuilder, ZRangeBuilder,
+    },
+    Connection, CopyKeysStream, CopyProgress, HScanBuilder, HScanStream, Message, MessageStream,
+    PMessage, PMessageStream, ProtocolVersion, PubSub, PubSubEvent, ResponseStream, ScanBuilder,
+    ScanStream, Transaction, ZScanBuilder, ZScanStream,
 };
 pub use connectionpool::ConnectionPool;
+pub use convert::{FromValue, ToArg};
 pub use error::Error;
+pub use function::{FunctionInfo, FunctionRestorePolicy, LibraryInfo};
+pub use geo::{GeoPosition, GeoSearchBuilder, GeoSearchResult, GeoShape, GeoUnit};
+pub use script::{Script, ScriptInvocation};
+pub use streams::{
+    AutoClaim, ConsumerInfo, GroupConsumer, GroupConsumerBuilder, GroupEntry, GroupInfo,
+    PendingEntry, PendingSummary, StreamEntry, StreamId, StreamInfo, StreamTrim, XPendingBuilder,
+    XReadStream,
+};
 
 ///Result type used in the whole crate.
 pub type Result<T> = std::result::Result<T, Error>;
 
 ///Enum depicting the various possible responses one can get from Redis.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     ///A Redis `OK` response.
     Ok,
@@ -71,6 +96,40 @@ pub enum Value {
     Integer(isize),
     ///String response. This cannot be a `String` type, because Redis strings need not be valid UTF-8, unlike Rust.
     String(Vec<u8>),
+    ///Status response other than `OK`, such as `PONG` or `QUEUED`.
+    Status(String),
+    ///Error response contained in another value, such as one of the results of a transaction.
+    ///Top-level error responses are returned as [`Error::RedisError`](enum.Error.html#variant.RedisError) instead.
+    Error(String),
+    ///Map response, only sent when using RESP3. Stored as a list of key-value pairs in the order
+    ///they were received.
+    Map(Vec<(Value, Value)>),
+    ///Set response, only sent when using RESP3.
+    Set(Vec<Value>),
+    ///Floating point number response, only sent when using RESP3.
+    Double(f64),
+    ///Boolean response, only sent when using RESP3.
+    Boolean(bool),
+    ///Integer response too large for an `isize`, only sent when using RESP3. Contains the decimal
+    ///representation of the number.
+    BigNumber(String),
+    ///String response with a format hint, only sent when using RESP3.
+    Verbatim {
+        ///The format of `text`, such as `txt` for plain text or `mkd` for markdown.
+        format: String,
+        ///The string itself.
+        text: Vec<u8>,
+    },
+    ///A value with additional out-of-band information attached, only sent when using RESP3.
+    Attribute {
+        ///The actual value.
+        data: Box<Value>,
+        ///The attributes describing `data`.
+        attributes: Vec<(Value, Value)>,
+    },
+    ///Data pushed by Redis outside of the regular request-response flow, such as pub/sub messages.
+    ///Only sent when using RESP3.
+    Push(Vec<Value>),
 }
 
 impl Value {
@@ -86,27 +145,55 @@ impl Value {
         }
     }
 
-    ///Returns the inner `Vec<Value>` of a `Value::Array`.
+    ///Returns the inner `Vec<Value>` of a `Value::Array`. Since they are also lists of values,
+    ///[`Value::Set`](enum.Value.html#Set.v) and [`Value::Push`](enum.Value.html#Push.v) are accepted as well.
     ///# Panics
-    ///Panics if `self` is not a [`Value::Array`](enum.Value.html#Array.v)
+    ///Panics if `self` is not a [`Value::Array`](enum.Value.html#Array.v), [`Value::Set`](enum.Value.html#Set.v) or [`Value::Push`](enum.Value.html#Push.v).
     #[inline]
     pub fn unwrap_array(self) -> Vec<Value> {
-        if let Value::Array(a) = self {
-            a
-        } else {
-            panic!("expected array value, got {:?}", self)
+        match self {
+            Value::Array(a) | Value::Set(a) | Value::Push(a) => a,
+            _ => panic!("expected array value, got {:?}", self),
         }
     }
 
-    ///Returns the inner `Vec<u8>` of a [`Value::String`](enum.Value.html#String.v).
+    ///Returns the inner `Vec<u8>` of a [`Value::String`](enum.Value.html#String.v). The text of a
+    ///[`Value::Verbatim`](enum.Value.html#Verbatim.v) is accepted as well.
     ///# Panics
-    ///Panics if `self` is not a [`Value::String`](enum.Value.html#String.v)
+    ///Panics if `self` is not a [`Value::String`](enum.Value.html#String.v) or [`Value::Verbatim`](enum.Value.html#Verbatim.v)
     #[inline]
     pub fn unwrap_string(self) -> Vec<u8> {
-        if let Value::String(s) = self {
+        match self {
+            Value::String(s) | Value::Verbatim { text: s, .. } => s,
+            _ => panic!("expected string value, got {:?}", self),
+        }
+    }
+
+    ///Returns the inner `String` of a [`Value::Status`](enum.Value.html#Status.v).
+    ///# Panics
+    ///Panics if `self` is not a [`Value::Status`](enum.Value.html#Status.v)
+    #[inline]
+    pub fn unwrap_status(self) -> String {
+        if let Value::Status(s) = self {
             s
         } else {
-            panic!("expected string value, got {:?}", self)
+            panic!("expected status value, got {:?}", self)
+        }
+    }
+
+    ///Returns `true` if `self` is a [`Value::Error`](enum.Value.html#Error.v).
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        matches!(self, Value::Error(_))
+    }
+
+    ///Converts a [`Value::Error`](enum.Value.html#Error.v) into an [`Error::RedisError`](enum.Error.html#variant.RedisError),
+    ///returning any other value unchanged.
+    #[inline]
+    pub fn into_result(self) -> Result<Value> {
+        match self {
+            Value::Error(e) => Err(Error::RedisError(e)),
+            other => Ok(other),
         }
     }
 
@@ -133,7 +220,16 @@ impl Value {
     #[inline]
     pub fn optional_string(self) -> Option<Vec<u8>> {
         match self {
-            Value::String(s) => Some(s),
+            Value::String(s) | Value::Verbatim { text: s, .. } => Some(s),
+            _ => None,
+        }
+    }
+
+    ///Like `unwrap_status`, but returns an `Option` instead of panicking.
+    #[inline]
+    pub fn optional_status(self) -> Option<String> {
+        match self {
+            Value::Status(s) => Some(s),
             _ => None,
         }
     }
@@ -142,7 +238,7 @@ impl Value {
     #[inline]
     pub fn optional_array(self) -> Option<Vec<Value>> {
         match self {
-            Value::Array(a) => Some(a),
+            Value::Array(a) | Value::Set(a) | Value::Push(a) => Some(a),
             _ => None,
         }
     }
@@ -179,3 +275,57 @@ pub enum DataType {
     ///A stream.
     Stream,
 }
+
+///The time to live of a key or hash field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ttl {
+    ///The key or field doesn't exist.
+    Missing,
+    ///The key or field exists, but doesn't expire.
+    Persistent,
+    ///The key or field expires. This is the remaining time to live, or the Unix timestamp of the
+    ///expiry when using [`Connection::expiretime`](struct.Connection.html#method.expiretime), in
+    ///seconds or milliseconds depending on the command used.
+    Expires(u64),
+}
+
+impl FromValue for Ttl {
+    fn from_value(value: Value) -> Result<Self> {
+        match isize::from_value(value)? {
+            -2 => Ok(Ttl::Missing),
+            -1 => Ok(Ttl::Persistent),
+            ttl if ttl >= 0 => Ok(Ttl::Expires(ttl as u64)),
+            ttl => Err(Error::UnexpectedResponse(format!("invalid TTL {}", ttl))),
+        }
+    }
+}
+
+///The result of setting the expiry of a hash field using
+///[`Connection::hexpire`](struct.Connection.html#method.hexpire) or
+///[`Connection::hpexpire`](struct.Connection.html#method.hpexpire).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashExpireResult {
+    ///The field or hash doesn't exist.
+    Missing,
+    ///The expiry wasn't set, because the condition wasn't met.
+    ConditionNotMet,
+    ///The expiry was set.
+    Set,
+    ///The field was deleted, because the expiry was in the past.
+    Deleted,
+}
+
+impl FromValue for HashExpireResult {
+    fn from_value(value: Value) -> Result<Self> {
+        match isize::from_value(value)? {
+            -2 => Ok(HashExpireResult::Missing),
+            0 => Ok(HashExpireResult::ConditionNotMet),
+            1 => Ok(HashExpireResult::Set),
+            2 => Ok(HashExpireResult::Deleted),
+            other => Err(Error::UnexpectedResponse(format!(
+                "invalid HEXPIRE result {}",
+                other
+            ))),
+        }
+    }
+}
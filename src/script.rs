@@ -0,0 +1,197 @@
+use crate::{Command, Connection, Error, FromValue, Result, ToArg};
+use std::borrow::Cow;
+
+///A Lua script, which is run using [`EVALSHA`](https://redis.io/commands/evalsha) so that the
+///code only has to be sent to Redis when it isn't already cached. The SHA1 hash of the script is
+///computed locally when it is created.
+///# Example
+///```
+///use darkredis::{Connection, ConnectionPool, Script};
+///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+///# async fn main() {
+///let script = Script::new("return redis.call('SET', KEYS[1], ARGV[1])");
+///
+///let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+///let result: String = script
+///    .key("script-key")
+///    .arg(42)
+///    .invoke(&mut connection)
+///    .await
+///    .unwrap();
+///assert_eq!(result, "OK");
+///
+/////Connections from a pool work as well
+///let pool = ConnectionPool::create("127.0.0.1:6379".into(), None, 1).await.unwrap();
+///let result: String = script
+///    .key("script-key")
+///    .arg(43)
+///    .invoke(&mut *pool.get().await)
+///    .await
+///    .unwrap();
+///assert_eq!(result, "OK");
+///# connection.del("script-key").await.unwrap();
+///# }
+///```
+#[derive(Clone, Debug)]
+pub struct Script {
+    code: String,
+    hash: String,
+}
+
+impl Script {
+    ///Create a new script from its source code.
+    pub fn new<C>(code: C) -> Self
+    where
+        C: Into<String>,
+    {
+        let code = code.into();
+        let hash = sha1_smol::Sha1::from(&code).digest().to_string();
+        Self { code, hash }
+    }
+
+    ///The source code of the script.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    ///The SHA1 hash of the script, as used by `EVALSHA`.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    ///Start building an invocation of the script, without any keys or arguments.
+    pub fn prepare_invoke(&self) -> ScriptInvocation<'_> {
+        ScriptInvocation {
+            script: self,
+            keys: Vec::new(),
+            args: Vec::new(),
+        }
+    }
+
+    ///Start building an invocation of the script, with `key` as the first key.
+    pub fn key<'a, K>(&'a self, key: K) -> ScriptInvocation<'a>
+    where
+        K: ToArg<'a>,
+    {
+        self.prepare_invoke().key(key)
+    }
+
+    ///Start building an invocation of the script, with `arg` as the first argument.
+    pub fn arg<'a, A>(&'a self, arg: A) -> ScriptInvocation<'a>
+    where
+        A: ToArg<'a>,
+    {
+        self.prepare_invoke().arg(arg)
+    }
+
+    ///Load the script into the script cache of Redis without running it.
+    pub async fn load(&self, connection: &mut Connection) -> Result<()> {
+        let hash = connection.script_load(&self.code).await?;
+        if hash != self.hash {
+            return Err(Error::UnexpectedResponse(format!(
+                "SCRIPT LOAD returned hash {}, expected {}",
+                hash, self.hash
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+///The keys and arguments for an invocation of a [`Script`](struct.Script.html).
+#[derive(Clone, Debug)]
+pub struct ScriptInvocation<'a> {
+    script: &'a Script,
+    keys: Vec<Cow<'a, [u8]>>,
+    args: Vec<Cow<'a, [u8]>>,
+}
+
+impl<'a> ScriptInvocation<'a> {
+    ///Add a key, available to the script in the `KEYS` table.
+    pub fn key<K>(mut self, key: K) -> Self
+    where
+        K: ToArg<'a>,
+    {
+        self.keys.push(key.to_arg());
+        self
+    }
+
+    ///Add multiple keys from a slice or iterator.
+    pub fn keys<I>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: ToArg<'a>,
+    {
+        self.keys.extend(keys.into_iter().map(|k| k.to_arg()));
+        self
+    }
+
+    ///Add an argument, available to the script in the `ARGV` table.
+    pub fn arg<A>(mut self, arg: A) -> Self
+    where
+        A: ToArg<'a>,
+    {
+        self.args.push(arg.to_arg());
+        self
+    }
+
+    ///Add multiple arguments from a slice or iterator.
+    pub fn args<I>(mut self, args: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: ToArg<'a>,
+    {
+        self.args.extend(args.into_iter().map(|a| a.to_arg()));
+        self
+    }
+
+    ///Run the script on `connection`, converting the result into `T`. The script is run using
+    ///`EVALSHA`, falling back to `EVAL` if Redis doesn't have it cached, which also caches it for
+    ///the next invocation.
+    pub async fn invoke<T>(self, connection: &mut Connection) -> Result<T>
+    where
+        T: FromValue,
+    {
+        let script = self.script;
+        match connection
+            .query(self.command("EVALSHA", script.hash()))
+            .await
+        {
+            Err(Error::RedisError(ref e)) if e.starts_with("NOSCRIPT") => {
+                connection.query(self.command("EVAL", script.code())).await
+            }
+            result => result,
+        }
+    }
+
+    //Build an EVAL or EVALSHA command, which take the script and then the number of keys.
+    fn command(&self, command: &'static str, script: &'a str) -> Command<'a> {
+        Command::new(command)
+            .arg(script)
+            .arg(self.keys.len())
+            .args(self.keys.iter().cloned())
+            .args(self.args.iter().cloned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hash() {
+        let script = Script::new("return 1");
+        assert_eq!(script.hash(), "e0e1f9fabfc9d4800c877a703b823ac0578ff8db");
+    }
+
+    #[test]
+    fn invocation() {
+        let script = Script::new("return 1");
+        let invocation = script.key("a").keys(vec!["b"]).arg(1).args(&["x", "y"]);
+        assert_eq!(
+            String::from_utf8_lossy(&invocation.command("EVAL", script.code()).serialize_bench()),
+            "*8\r\n$4\r\nEVAL\r\n$8\r\nreturn 1\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\n1\r\n$1\r\nx\r\n$1\r\ny\r\n"
+        );
+    }
+}
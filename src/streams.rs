@@ -0,0 +1,413 @@
+use crate::{Command, Connection, Error, FromValue, Result, ToArg, Value};
+use futures::{
+    task::{Context, Poll},
+    Future, FutureExt, Stream,
+};
+use std::{borrow::Cow, collections::VecDeque, fmt, pin::Pin, str::FromStr};
+
+mod group;
+pub use group::{
+    AutoClaim, ConsumerInfo, GroupConsumer, GroupConsumerBuilder, GroupEntry, GroupInfo,
+    PendingEntry, PendingSummary, StreamInfo, XPendingBuilder,
+};
+
+///The ID of an entry in a Redis stream, consisting of a millisecond timestamp and a sequence
+///number. Formatted as `<ms>-<seq>` when sent to Redis.
+///# Example
+///```
+///use darkredis::StreamId;
+///
+///let id: StreamId = "1526919030474-55".parse().unwrap();
+///assert_eq!(id, StreamId::new(1526919030474, 55));
+///assert_eq!(id.to_string(), "1526919030474-55");
+///assert!(id < StreamId::new(1526919030474, 56));
+///```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    ///The millisecond timestamp part of the ID.
+    pub ms: u64,
+    ///The sequence number of the ID, for entries added in the same millisecond.
+    pub seq: u64,
+}
+
+impl StreamId {
+    ///Create a new ID from its parts.
+    pub fn new(ms: u64, seq: u64) -> Self {
+        Self { ms, seq }
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+impl FromStr for StreamId {
+    type Err = Error;
+
+    ///Parse an ID in the form `<ms>-<seq>`, or only `<ms>` in which case the sequence number is 0.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::UnexpectedResponse(format!("invalid stream ID {}", s));
+        let mut parts = s.splitn(2, '-');
+        let ms = parts
+            .next()
+            .and_then(|ms| ms.parse().ok())
+            .ok_or_else(invalid)?;
+        let seq = match parts.next() {
+            Some(seq) => seq.parse().map_err(|_| invalid())?,
+            None => 0,
+        };
+
+        Ok(Self { ms, seq })
+    }
+}
+
+impl FromValue for StreamId {
+    fn from_value(value: Value) -> Result<Self> {
+        String::from_value(value)?.parse()
+    }
+}
+
+impl<'a> ToArg<'a> for StreamId {
+    #[inline]
+    fn to_arg(self) -> Cow<'a, [u8]> {
+        Cow::Owned(self.to_string().into_bytes())
+    }
+}
+
+///An entry in a Redis stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamEntry {
+    ///The ID of the entry.
+    pub id: StreamId,
+    ///The fields of the entry and their values, in the order they were added.
+    pub fields: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl StreamEntry {
+    ///Get the value of `field`, if the entry has it.
+    pub fn get<F>(&self, field: F) -> Option<&[u8]>
+    where
+        F: AsRef<[u8]>,
+    {
+        self.fields
+            .iter()
+            .find(|(f, _)| f.as_slice() == field.as_ref())
+            .map(|(_, v)| v.as_slice())
+    }
+}
+
+impl FromValue for StreamEntry {
+    fn from_value(value: Value) -> Result<Self> {
+        let (id, fields) = FromValue::from_value(value)?;
+        Ok(Self { id, fields })
+    }
+}
+
+///How a stream is trimmed by [`Connection::xadd`](struct.Connection.html#method.xadd) and
+///[`Connection::xtrim`](struct.Connection.html#method.xtrim).
+///# Example
+///```
+///use darkredis::{StreamId, StreamTrim};
+///
+/////Keep roughly the last 1000 entries
+///let trim = StreamTrim::max_len(1000).approximate();
+/////Remove entries older than the given ID, evicting at most 100 entries at a time
+///let trim = StreamTrim::min_id(StreamId::new(1526919030474, 0)).approximate().limit(100);
+///```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamTrim {
+    strategy: TrimStrategy,
+    approximate: bool,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TrimStrategy {
+    MaxLen(usize),
+    MinId(StreamId),
+}
+
+impl StreamTrim {
+    ///Trim the stream to at most `len` entries, removing the oldest ones.
+    pub fn max_len(len: usize) -> Self {
+        Self {
+            strategy: TrimStrategy::MaxLen(len),
+            approximate: false,
+            limit: None,
+        }
+    }
+
+    ///Remove every entry with an ID lower than `id`.
+    pub fn min_id(id: StreamId) -> Self {
+        Self {
+            strategy: TrimStrategy::MinId(id),
+            approximate: false,
+            limit: None,
+        }
+    }
+
+    ///Allow Redis to trim fewer entries than requested when that is more efficient, using `~`.
+    pub fn approximate(mut self) -> Self {
+        self.approximate = true;
+        self
+    }
+
+    ///Remove at most `limit` entries. Only valid together with
+    ///[`approximate`](struct.StreamTrim.html#method.approximate).
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub(crate) fn build(&self, command: &mut Command) {
+        match self.strategy {
+            TrimStrategy::MaxLen(len) => {
+                command.append_arg("MAXLEN");
+                command.append_arg(if self.approximate { "~" } else { "=" });
+                command.append_arg(len);
+            }
+            TrimStrategy::MinId(id) => {
+                command.append_arg("MINID");
+                command.append_arg(if self.approximate { "~" } else { "=" });
+                command.append_arg(id);
+            }
+        }
+        if let Some(limit) = self.limit {
+            command.append_arg("LIMIT");
+            command.append_arg(limit);
+        }
+    }
+}
+
+//Build an XREAD command reading every stream in `streams` after the matching ID.
+pub(crate) fn xread_command<'a, K>(
+    streams: &'a [(K, StreamId)],
+    count: Option<usize>,
+    block_ms: Option<u32>,
+) -> Command<'a>
+where
+    K: AsRef<[u8]>,
+{
+    let mut command = Command::new("XREAD");
+    if let Some(count) = count {
+        command.append_arg("COUNT");
+        command.append_arg(count);
+    }
+    if let Some(block_ms) = block_ms {
+        command.append_arg("BLOCK");
+        command.append_arg(block_ms);
+    }
+    command.append_arg("STREAMS");
+    command.append_args(streams.iter().map(|(key, _)| key.as_ref()));
+    command.append_args(streams.iter().map(|(_, id)| *id));
+    command
+}
+
+//Build an XREADGROUP command, where a `None` ID reads entries never delivered to any consumer.
+pub(crate) fn xreadgroup_command<'a, K>(
+    group: &'a [u8],
+    consumer: &'a [u8],
+    streams: &'a [(K, Option<StreamId>)],
+    count: Option<usize>,
+    block_ms: Option<u32>,
+    noack: bool,
+) -> Command<'a>
+where
+    K: AsRef<[u8]>,
+{
+    let mut command = Command::new("XREADGROUP")
+        .arg("GROUP")
+        .arg(group)
+        .arg(consumer);
+    if let Some(count) = count {
+        command.append_arg("COUNT");
+        command.append_arg(count);
+    }
+    if let Some(block_ms) = block_ms {
+        command.append_arg("BLOCK");
+        command.append_arg(block_ms);
+    }
+    if noack {
+        command.append_arg("NOACK");
+    }
+    command.append_arg("STREAMS");
+    command.append_args(streams.iter().map(|(key, _)| key.as_ref()));
+    for (_, id) in streams {
+        match id {
+            Some(id) => command.append_arg(*id),
+            None => command.append_arg(">"),
+        }
+    }
+    command
+}
+
+type XReadFuture = Pin<Box<dyn Future<Output = Result<Vec<(Vec<u8>, Vec<StreamEntry>)>>> + Send>>;
+
+///A stream of entries added to one or more Redis streams, created using
+///[`Connection::xread_stream`](struct.Connection.html#method.xread_stream) or
+///[`Connection::xreadgroup_stream`](struct.Connection.html#method.xreadgroup_stream). Every entry is
+///returned together with the key of the stream it was added to. Errors are returned as items. After
+///an error returned by Redis, polling the stream again retries reading from the same position, while
+///the stream ends after any other error, such as the connection being closed.
+#[must_use = "No entries will be received if left unused"]
+#[allow(missing_debug_implementations)]
+pub struct XReadStream {
+    connection: Connection,
+    streams: Vec<(Vec<u8>, StreamId)>,
+    count: Option<usize>,
+    group: Option<GroupRead>,
+    poll_future: Option<XReadFuture>,
+    receive_buffer: VecDeque<(Vec<u8>, StreamEntry)>,
+}
+
+//The consumer group used by a stream created with `xreadgroup_stream`.
+#[derive(Clone)]
+pub(crate) struct GroupRead {
+    pub(crate) group: Vec<u8>,
+    pub(crate) consumer: Vec<u8>,
+    pub(crate) noack: bool,
+}
+
+impl XReadStream {
+    pub(crate) fn new(
+        connection: Connection,
+        streams: Vec<(Vec<u8>, StreamId)>,
+        count: Option<usize>,
+        group: Option<GroupRead>,
+    ) -> Self {
+        let poll_future =
+            Self::create_poll_future(connection.clone(), streams.clone(), count, group.clone());
+        Self {
+            connection,
+            streams,
+            count,
+            group,
+            poll_future: Some(poll_future),
+            receive_buffer: VecDeque::new(),
+        }
+    }
+
+    fn create_poll_future(
+        mut connection: Connection,
+        streams: Vec<(Vec<u8>, StreamId)>,
+        count: Option<usize>,
+        group: Option<GroupRead>,
+    ) -> XReadFuture {
+        async move {
+            //Block until at least one of the streams has new entries
+            match group {
+                Some(group) => {
+                    let streams: Vec<_> = streams.into_iter().map(|(k, _)| (k, None)).collect();
+                    let command = xreadgroup_command(
+                        &group.group,
+                        &group.consumer,
+                        &streams,
+                        count,
+                        Some(0),
+                        group.noack,
+                    );
+                    connection.query(command).await
+                }
+                None => {
+                    let command = xread_command(&streams, count, Some(0));
+                    connection.query(command).await
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+impl Stream for XReadStream {
+    type Item = Result<(Vec<u8>, StreamEntry)>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(entry) = self.receive_buffer.pop_front() {
+                return Poll::Ready(Some(Ok(entry)));
+            }
+
+            let result = match self.poll_future.as_mut() {
+                Some(future) => match future.as_mut().poll(cx) {
+                    Poll::Ready(result) => result,
+                    Poll::Pending => return Poll::Pending,
+                },
+                None => return Poll::Ready(None),
+            };
+
+            //Continue after the last entry received from each stream.
+            if let Ok(ref received) = result {
+                for (key, entries) in received {
+                    let last = entries.last().map(|e| e.id);
+                    let position = self.streams.iter_mut().find(|(k, _)| k == key);
+                    if let (Some(last), Some((_, id))) = (last, position) {
+                        *id = last;
+                    }
+                }
+            }
+            //Retrying is pointless if the connection is broken or the reply can't be understood.
+            self.poll_future = match result {
+                Ok(_) | Err(Error::RedisError(_)) => Some(Self::create_poll_future(
+                    self.connection.clone(),
+                    self.streams.clone(),
+                    self.count,
+                    self.group.clone(),
+                )),
+                Err(_) => None,
+            };
+
+            for (key, entries) in result? {
+                for entry in entries {
+                    self.receive_buffer.push_back((key.clone(), entry));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::string;
+
+    #[test]
+    fn stream_id() {
+        assert_eq!("5-3".parse::<StreamId>().unwrap(), StreamId::new(5, 3));
+        assert_eq!("5".parse::<StreamId>().unwrap(), StreamId::new(5, 0));
+        assert!("5-".parse::<StreamId>().is_err());
+        assert!("a-1".parse::<StreamId>().is_err());
+        assert!(StreamId::new(1, 5) < StreamId::new(2, 0));
+        assert_eq!(StreamId::new(1, 2).to_arg(), Cow::Borrowed(b"1-2"));
+    }
+
+    #[test]
+    fn stream_entry() {
+        let value = Value::Array(vec![
+            string("1-1"),
+            Value::Array(vec![string("a"), string("1"), string("b"), string("2")]),
+        ]);
+        let entry = StreamEntry::from_value(value).unwrap();
+        assert_eq!(entry.id, StreamId::new(1, 1));
+        assert_eq!(entry.get("b"), Some(&b"2"[..]));
+        assert_eq!(entry.get("c"), None);
+
+        //Deleted entries have no fields
+        let value = Value::Array(vec![string("1-2"), Value::Nil]);
+        assert!(StreamEntry::from_value(value).unwrap().fields.is_empty());
+    }
+
+    #[test]
+    fn trim() {
+        let mut command = Command::new("XTRIM").arg("key");
+        StreamTrim::min_id(StreamId::new(1, 0))
+            .approximate()
+            .limit(10)
+            .build(&mut command);
+        assert_eq!(
+            String::from_utf8_lossy(&command.serialize_bench()),
+            "*7\r\n$5\r\nXTRIM\r\n$3\r\nkey\r\n$5\r\nMINID\r\n$1\r\n~\r\n$3\r\n1-0\r\n$5\r\nLIMIT\r\n$2\r\n10\r\n"
+        );
+    }
+}
@@ -0,0 +1,568 @@
+use super::{xreadgroup_command, StreamEntry, StreamId};
+use crate::{
+    convert::{take_field, take_optional_field},
+    Command, Connection, Error, FromValue, Result, ToArg, Value,
+};
+use futures::{
+    task::{Context, Poll},
+    Future, FutureExt, Stream,
+};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+///A summary of the pending entries of a consumer group, as returned by
+///[`Connection::xpending`](struct.Connection.html#method.xpending).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingSummary {
+    ///The number of entries which have been delivered but not acknowledged.
+    pub count: usize,
+    ///The lowest ID of the pending entries, if there are any.
+    pub first_id: Option<StreamId>,
+    ///The highest ID of the pending entries, if there are any.
+    pub last_id: Option<StreamId>,
+    ///Every consumer with pending entries, together with how many entries it has pending.
+    pub consumers: Vec<(Vec<u8>, usize)>,
+}
+
+impl FromValue for PendingSummary {
+    fn from_value(value: Value) -> Result<Self> {
+        let (count, first_id, last_id, consumers) = FromValue::from_value(value)?;
+        Ok(Self {
+            count,
+            first_id,
+            last_id,
+            consumers,
+        })
+    }
+}
+
+///A pending entry of a consumer group, as returned by
+///[`Connection::xpending_range`](struct.Connection.html#method.xpending_range).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingEntry {
+    ///The ID of the entry.
+    pub id: StreamId,
+    ///The consumer the entry was last delivered to.
+    pub consumer: Vec<u8>,
+    ///The number of milliseconds since the entry was last delivered.
+    pub idle_ms: u64,
+    ///The number of times the entry has been delivered.
+    pub delivery_count: u64,
+}
+
+impl FromValue for PendingEntry {
+    fn from_value(value: Value) -> Result<Self> {
+        let (id, consumer, idle_ms, delivery_count) = FromValue::from_value(value)?;
+        Ok(Self {
+            id,
+            consumer,
+            idle_ms,
+            delivery_count,
+        })
+    }
+}
+
+///A builder for the extended form of [`XPENDING`](https://redis.io/commands/xpending), used with
+///[`Connection::xpending_range`](struct.Connection.html#method.xpending_range).
+///# Example
+///```
+///# use darkredis::XPendingBuilder;
+/// //At most 10 entries of any ID which have been pending for a minute on `worker-1`
+///let builder = XPendingBuilder::new("-", "+", 10).consumer("worker-1").min_idle_ms(60_000);
+///```
+#[derive(Debug, Clone)]
+pub struct XPendingBuilder<'a> {
+    start: Cow<'a, [u8]>,
+    end: Cow<'a, [u8]>,
+    count: usize,
+    consumer: Option<Cow<'a, [u8]>>,
+    min_idle_ms: Option<u64>,
+}
+
+impl<'a> XPendingBuilder<'a> {
+    ///Create a new instance, returning at most `count` entries with IDs between `start` and `end`.
+    ///Like with [`Connection::xrange`](struct.Connection.html#method.xrange), `-` and `+` can be
+    ///used for the lowest and highest possible IDs.
+    pub fn new<S, E>(start: S, end: E, count: usize) -> Self
+    where
+        S: ToArg<'a>,
+        E: ToArg<'a>,
+    {
+        Self {
+            start: start.to_arg(),
+            end: end.to_arg(),
+            count,
+            consumer: None,
+            min_idle_ms: None,
+        }
+    }
+
+    ///Only return entries pending on `consumer`.
+    pub fn consumer<C>(mut self, consumer: C) -> Self
+    where
+        C: ToArg<'a>,
+    {
+        self.consumer = Some(consumer.to_arg());
+        self
+    }
+
+    ///Only return entries which were delivered at least `min_idle_ms` milliseconds ago.
+    pub fn min_idle_ms(mut self, min_idle_ms: u64) -> Self {
+        self.min_idle_ms = Some(min_idle_ms);
+        self
+    }
+
+    pub(crate) fn build(&self, command: &mut Command<'a>) {
+        if let Some(min_idle_ms) = self.min_idle_ms {
+            command.append_arg("IDLE");
+            command.append_arg(min_idle_ms);
+        }
+        command.append_arg(self.start.clone());
+        command.append_arg(self.end.clone());
+        command.append_arg(self.count);
+        if let Some(ref consumer) = self.consumer {
+            command.append_arg(consumer.clone());
+        }
+    }
+}
+
+///The result of [`Connection::xautoclaim`](struct.Connection.html#method.xautoclaim).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoClaim {
+    ///The ID to continue claiming from, which is `0-0` once every pending entry has been checked.
+    pub next: StreamId,
+    ///The entries which were claimed.
+    pub entries: Vec<StreamEntry>,
+    ///The IDs of pending entries which no longer exist in the stream, and were removed from the
+    ///pending entries list. Always empty before Redis 7.
+    pub deleted: Vec<StreamId>,
+}
+
+impl FromValue for AutoClaim {
+    fn from_value(value: Value) -> Result<Self> {
+        let mut values = Vec::<Value>::from_value(value)?.into_iter();
+        let next = StreamId::from_value(values.next().unwrap_or(Value::Nil))?;
+        //Redis 6.2 returns deleted entries as nil instead of in a separate list.
+        let entries = Vec::<Option<StreamEntry>>::from_value(values.next().unwrap_or(Value::Nil))?;
+        let deleted = Vec::from_value(values.next().unwrap_or(Value::Nil))?;
+
+        Ok(Self {
+            next,
+            entries: entries.into_iter().flatten().collect(),
+            deleted,
+        })
+    }
+}
+
+///Information about a stream, as returned by
+///[`Connection::xinfo_stream`](struct.Connection.html#method.xinfo_stream).
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamInfo {
+    ///The number of entries in the stream.
+    pub length: usize,
+    ///The ID of the last entry added to the stream, even if it has since been deleted.
+    pub last_generated_id: StreamId,
+    ///The number of consumer groups of the stream.
+    pub groups: usize,
+    ///The first entry of the stream, if it isn't empty.
+    pub first_entry: Option<StreamEntry>,
+    ///The last entry of the stream, if it isn't empty.
+    pub last_entry: Option<StreamEntry>,
+}
+
+impl FromValue for StreamInfo {
+    fn from_value(value: Value) -> Result<Self> {
+        let mut fields = HashMap::<String, Value>::from_value(value)?;
+        Ok(Self {
+            length: take_field(&mut fields, "length")?,
+            last_generated_id: take_field(&mut fields, "last-generated-id")?,
+            groups: take_field(&mut fields, "groups")?,
+            first_entry: take_optional_field(&mut fields, "first-entry")?,
+            last_entry: take_optional_field(&mut fields, "last-entry")?,
+        })
+    }
+}
+
+///Information about a consumer group, as returned by
+///[`Connection::xinfo_groups`](struct.Connection.html#method.xinfo_groups).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupInfo {
+    ///The name of the group.
+    pub name: String,
+    ///The number of consumers in the group.
+    pub consumers: usize,
+    ///The number of entries which have been delivered but not acknowledged.
+    pub pending: usize,
+    ///The ID of the last entry delivered to the group.
+    pub last_delivered_id: StreamId,
+    ///The number of entries read by the group, if known. Only returned since Redis 7.
+    pub entries_read: Option<u64>,
+    ///The number of entries which haven't been delivered to the group yet, if known. Only returned
+    ///since Redis 7.
+    pub lag: Option<u64>,
+}
+
+impl FromValue for GroupInfo {
+    fn from_value(value: Value) -> Result<Self> {
+        let mut fields = HashMap::<String, Value>::from_value(value)?;
+        Ok(Self {
+            name: take_field(&mut fields, "name")?,
+            consumers: take_field(&mut fields, "consumers")?,
+            pending: take_field(&mut fields, "pending")?,
+            last_delivered_id: take_field(&mut fields, "last-delivered-id")?,
+            entries_read: take_optional_field(&mut fields, "entries-read")?,
+            lag: take_optional_field(&mut fields, "lag")?,
+        })
+    }
+}
+
+///Information about a consumer in a consumer group, as returned by
+///[`Connection::xinfo_consumers`](struct.Connection.html#method.xinfo_consumers).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsumerInfo {
+    ///The name of the consumer.
+    pub name: String,
+    ///The number of entries pending on the consumer.
+    pub pending: usize,
+    ///The number of milliseconds since the consumer last interacted with the server.
+    pub idle_ms: u64,
+    ///The number of milliseconds since the consumer last read or claimed an entry, if it ever
+    ///has. Only returned since Redis 7.2.
+    pub inactive_ms: Option<u64>,
+}
+
+impl FromValue for ConsumerInfo {
+    fn from_value(value: Value) -> Result<Self> {
+        let mut fields = HashMap::<String, Value>::from_value(value)?;
+        //Consumers which never read anything have an inactive time of -1.
+        let inactive_ms: Option<i64> = take_optional_field(&mut fields, "inactive")?;
+        Ok(Self {
+            name: take_field(&mut fields, "name")?,
+            pending: take_field(&mut fields, "pending")?,
+            idle_ms: take_field(&mut fields, "idle")?,
+            inactive_ms: inactive_ms.filter(|i| *i >= 0).map(|i| i as u64),
+        })
+    }
+}
+
+///An entry returned by a [`GroupConsumer`](struct.GroupConsumer.html).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupEntry {
+    ///The entry itself.
+    pub entry: StreamEntry,
+    ///The number of times the entry has been delivered, including this time. Anything above 1 means
+    ///that a previous attempt at processing it failed to acknowledge it.
+    pub delivery_count: u64,
+}
+
+///Builder for a [`GroupConsumer`](struct.GroupConsumer.html), created using
+///[`Connection::group_consumer`](struct.Connection.html#method.group_consumer).
+#[derive(Debug)]
+pub struct GroupConsumerBuilder {
+    state: ConsumerState,
+}
+
+impl GroupConsumerBuilder {
+    pub(crate) fn new(connection: Connection, key: &[u8], group: &[u8], consumer: &[u8]) -> Self {
+        Self {
+            state: ConsumerState {
+                connection,
+                key: key.to_vec(),
+                group: group.to_vec(),
+                consumer: consumer.to_vec(),
+                count: 10,
+                min_idle: Duration::from_secs(60),
+                claim_interval: Duration::from_secs(5),
+                history: Some(StreamId::default()),
+                claim_cursor: StreamId::default(),
+                next_claim: Instant::now(),
+            },
+        }
+    }
+
+    ///Request at most `count` entries from Redis at a time. Defaults to 10.
+    pub fn count(mut self, count: usize) -> Self {
+        self.state.count = count;
+        self
+    }
+
+    ///Claim entries which have been pending for at least `min_idle`, assuming that the consumer
+    ///processing them has died. This includes entries returned by this consumer which haven't been
+    ///acknowledged in time. Defaults to one minute.
+    pub fn min_idle(mut self, min_idle: Duration) -> Self {
+        self.state.min_idle = min_idle;
+        self
+    }
+
+    ///Look for idle pending entries to claim every `claim_interval`. Defaults to five seconds.
+    pub fn claim_interval(mut self, claim_interval: Duration) -> Self {
+        self.state.claim_interval = claim_interval;
+        self
+    }
+
+    ///Consume `self` and start consuming entries.
+    pub fn run(self) -> GroupConsumer {
+        GroupConsumer {
+            poll_future: Some(self.state.next_batch().boxed()),
+            receive_buffer: VecDeque::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ConsumerState {
+    connection: Connection,
+    key: Vec<u8>,
+    group: Vec<u8>,
+    consumer: Vec<u8>,
+    count: usize,
+    min_idle: Duration,
+    claim_interval: Duration,
+    //The position in the pending entries of this consumer while recovering them at startup.
+    history: Option<StreamId>,
+    claim_cursor: StreamId,
+    next_claim: Instant,
+}
+
+impl ConsumerState {
+    //Get the next batch of entries, which may be empty, handing the state back for the next call.
+    async fn next_batch(mut self) -> (Self, Result<Vec<GroupEntry>>) {
+        let result = self.read().await;
+        (self, result)
+    }
+
+    async fn read(&mut self) -> Result<Vec<GroupEntry>> {
+        //First recover the entries which were delivered to this consumer before it was restarted.
+        if let Some(position) = self.history {
+            let entries = self.read_group(Some(position), None).await?;
+            self.history = entries.last().map(|e| e.id);
+            return self.with_delivery_counts(entries).await;
+        }
+
+        if Instant::now() >= self.next_claim {
+            let claimed = self
+                .connection
+                .xautoclaim(
+                    &self.key,
+                    &self.group,
+                    &self.consumer,
+                    self.min_idle.as_millis() as u64,
+                    self.claim_cursor,
+                    Some(self.count),
+                )
+                .await?;
+            self.claim_cursor = claimed.next;
+            if claimed.next == StreamId::default() {
+                self.next_claim = Instant::now() + self.claim_interval;
+            }
+            if !claimed.entries.is_empty() {
+                return self.with_delivery_counts(claimed.entries).await;
+            }
+        }
+
+        //Wait for new entries until it's time to claim idle entries again.
+        let timeout = self.next_claim.saturating_duration_since(Instant::now());
+        let block_ms = (timeout.as_millis() as u32).max(1);
+        let entries = self.read_group(None, Some(block_ms)).await?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| GroupEntry {
+                entry,
+                delivery_count: 1,
+            })
+            .collect())
+    }
+
+    async fn read_group(
+        &mut self,
+        id: Option<StreamId>,
+        block_ms: Option<u32>,
+    ) -> Result<Vec<StreamEntry>> {
+        let streams = [(&self.key, id)];
+        let command = xreadgroup_command(
+            &self.group,
+            &self.consumer,
+            &streams,
+            Some(self.count),
+            block_ms,
+            false,
+        );
+        let reply: Vec<(Vec<u8>, Vec<StreamEntry>)> = self.connection.query(command).await?;
+        Ok(reply.into_iter().flat_map(|(_, e)| e).collect())
+    }
+
+    //Look up how many times each entry has been delivered.
+    async fn with_delivery_counts(&mut self, entries: Vec<StreamEntry>) -> Result<Vec<GroupEntry>> {
+        let (first, last) = match (entries.first(), entries.last()) {
+            (Some(first), Some(last)) => (first.id, last.id),
+            _ => return Ok(Vec::new()),
+        };
+        let range = XPendingBuilder::new(first, last, entries.len()).consumer(&self.consumer);
+        let counts: HashMap<StreamId, u64> = self
+            .connection
+            .xpending_range(&self.key, &self.group, range)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, p.delivery_count))
+            .collect();
+
+        Ok(entries
+            .into_iter()
+            .map(|entry| GroupEntry {
+                delivery_count: counts.get(&entry.id).copied().unwrap_or(1),
+                entry,
+            })
+            .collect())
+    }
+}
+
+type ConsumerFuture =
+    Pin<Box<dyn Future<Output = (ConsumerState, Result<Vec<GroupEntry>>)> + Send>>;
+
+///A stream of entries from a consumer group, which recovers entries left pending by crashed
+///consumers. When started it first returns the entries still pending on this consumer, after which
+///it reads new entries, and periodically claims entries which have been pending on other consumers
+///for too long using [`XAUTOCLAIM`](https://redis.io/commands/xautoclaim). Entries must be
+///acknowledged using [`Connection::xack`](struct.Connection.html#method.xack) once they have been
+///processed, using a different connection.
+///
+///Errors are returned as items. After an error returned by Redis, polling the stream again retries
+///the failed operation, while the stream ends after any other error, such as the connection being
+///closed.
+///# Example
+///```
+///use darkredis::{Connection, MSetBuilder};
+///use futures::StreamExt;
+///# #[cfg_attr(feature = "runtime_tokio", tokio::main)]
+///# #[cfg_attr(feature = "runtime_async_std", async_std::main)]
+///# async fn main() {
+///let mut connection = Connection::connect("127.0.0.1:6379").await.unwrap();
+///# connection.del("group-consumer-example").await.unwrap();
+///connection.xgroup_create("group-consumer-example", "workers", None, true).await.unwrap();
+///let fields = MSetBuilder::new().set(b"job", b"42");
+///connection.xadd("group-consumer-example", None, fields, None).await.unwrap();
+///
+///let worker = Connection::connect("127.0.0.1:6379").await.unwrap();
+///let mut entries = worker
+///    .group_consumer("group-consumer-example", "workers", "worker-1")
+///    .run();
+///let job = entries.next().await.unwrap().unwrap();
+///assert_eq!(job.entry.get("job"), Some(&b"42"[..]));
+///assert_eq!(job.delivery_count, 1);
+///connection.xack("group-consumer-example", "workers", &[job.entry.id]).await.unwrap();
+///# connection.del("group-consumer-example").await.unwrap();
+///# }
+///```
+#[must_use = "No entries will be received if left unused"]
+#[allow(missing_debug_implementations)]
+pub struct GroupConsumer {
+    poll_future: Option<ConsumerFuture>,
+    receive_buffer: VecDeque<GroupEntry>,
+}
+
+impl Stream for GroupConsumer {
+    type Item = Result<GroupEntry>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(entry) = self.receive_buffer.pop_front() {
+                return Poll::Ready(Some(Ok(entry)));
+            }
+
+            let (state, result) = match self.poll_future.as_mut() {
+                Some(future) => match future.as_mut().poll(cx) {
+                    Poll::Ready(output) => output,
+                    Poll::Pending => return Poll::Pending,
+                },
+                None => return Poll::Ready(None),
+            };
+            //Retrying is pointless if the connection is broken or the reply can't be understood.
+            self.poll_future = match result {
+                Ok(_) | Err(Error::RedisError(_)) => Some(state.next_batch().boxed()),
+                Err(_) => None,
+            };
+            self.receive_buffer.extend(result?);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::string;
+
+    #[test]
+    fn pending() {
+        let value = Value::Array(vec![
+            Value::Integer(3),
+            string("1-0"),
+            string("3-0"),
+            Value::Array(vec![
+                Value::Array(vec![string("a"), string("2")]),
+                Value::Array(vec![string("b"), string("1")]),
+            ]),
+        ]);
+        assert_eq!(
+            PendingSummary::from_value(value).unwrap(),
+            PendingSummary {
+                count: 3,
+                first_id: Some(StreamId::new(1, 0)),
+                last_id: Some(StreamId::new(3, 0)),
+                consumers: vec![(b"a".to_vec(), 2), (b"b".to_vec(), 1)],
+            }
+        );
+
+        let empty = Value::Array(vec![Value::Integer(0), Value::Nil, Value::Nil, Value::Nil]);
+        let summary = PendingSummary::from_value(empty).unwrap();
+        assert_eq!(summary.first_id, None);
+        assert!(summary.consumers.is_empty());
+    }
+
+    #[test]
+    fn autoclaim() {
+        let entry = Value::Array(vec![
+            string("1-0"),
+            Value::Array(vec![string("a"), string("1")]),
+        ]);
+        let value = Value::Array(vec![
+            string("0-0"),
+            Value::Array(vec![entry.clone(), Value::Nil]),
+        ]);
+        let claimed = AutoClaim::from_value(value).unwrap();
+        assert_eq!(claimed.next, StreamId::default());
+        assert_eq!(claimed.entries.len(), 1);
+        assert!(claimed.deleted.is_empty());
+
+        let value = Value::Array(vec![
+            string("5-0"),
+            Value::Array(vec![entry]),
+            Value::Array(vec![string("2-0")]),
+        ]);
+        let claimed = AutoClaim::from_value(value).unwrap();
+        assert_eq!(claimed.next, StreamId::new(5, 0));
+        assert_eq!(claimed.deleted, vec![StreamId::new(2, 0)]);
+    }
+
+    #[test]
+    fn consumer_info() {
+        let value = Value::Map(vec![
+            (string("name"), string("worker")),
+            (string("pending"), Value::Integer(2)),
+            (string("idle"), Value::Integer(100)),
+            (string("inactive"), Value::Integer(-1)),
+        ]);
+        assert_eq!(
+            ConsumerInfo::from_value(value).unwrap(),
+            ConsumerInfo {
+                name: "worker".to_string(),
+                pending: 2,
+                idle_ms: 100,
+                inactive_ms: None,
+            }
+        );
+    }
+}
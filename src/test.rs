@@ -1,6 +1,13 @@
+use crate::Value;
+
 ///The address to connect to when testing
 pub const TEST_ADDRESS: &str = "127.0.0.1:6379";
 
+///Create a bulk string value, for testing conversions.
+pub fn string(s: &str) -> Value {
+    Value::String(s.as_bytes().to_vec())
+}
+
 #[macro_export]
 ///Clean up any keys given as identifiers.
 macro_rules! cleanup_keys {
@@ -37,8 +44,8 @@ macro_rules! create_key {
 ///Define a test which mutates the test Redis connection, clearing state before and after running to ensure consistent results. This also prevents leaving a mess in the testing Redis instance.
 macro_rules! redis_test {
     ($redis:ident, $block:tt, $( $key:ident ),+) => {
-        use crate::create_key;
-        use crate::cleanup_keys;
+        use $crate::create_key;
+        use $crate::cleanup_keys;
         //Would use a static connection pool like before, but using `futures::block_on` doesn't really work.
         let mut $redis = Connection::connect(TEST_ADDRESS).await.unwrap();
         $(